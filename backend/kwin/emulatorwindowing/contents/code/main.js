@@ -0,0 +1 @@
+var re=Object.defineProperty;var q=Object.getOwnPropertySymbols;var ie=Object.prototype.hasOwnProperty,ae=Object.prototype.propertyIsEnumerable;var N=(e,t,n)=>t in e?re(e,t,{enumerable:!0,configurable:!0,writable:!0,value:n}):e[t]=n,B=(e,t)=>{for(var n in t||(t={}))ie.call(t,n)&&N(e,n,t[n]);if(q)for(var n of q(t))ae.call(t,n)&&N(e,n,t[n]);return e};function _(){let e=y("swapScreens",!1),t=y("keepAbove",!0);return print("General Settings:: keepAbove:",t,", swapScreens:",e),{swapScreens:e,keepAbove:t}}function $(){return[{primary:/Steam(.*)Keyboard/,classes:["steam"]}]}function U(){let e=[];for(let t=0;t<1e3;t++){let n=y("secondaryAppWindowMatcher"+t,""),o=y("secondaryAppWindowClasses"+t,"").split(",").map(s=>s.trim()),a=y("secondaryAppWindowingBehavior"+t,"Fullscreen"),r=y("secondaryAppScreenPreference"+t,"PreferSecondary"),s=y("secondaryAppTiling"+t,"Auto"),i=parseInt(y("secondaryAppZOrder"+t,""+t));if(n.length>0&&o.length>0)print("SecondaryApp settings:: primary:",n,"classes:",o,"windowing:",a,"tiling:",s,"z-order:",i),e.push({primary:new RegExp(n),classes:o,screenPreference:r,windowingBehavior:a,tiling:s,zOrder:isNaN(i)?t:i});else{print("no secondary app config available at index"+t);break}}return print("loaded",e.length,"secondary apps"),e}function j(){let e={Cemu:{classes:["cemu","cemu_relwithdebinfo"],primary:/^Cemu/,secondary:/^GamePad View/,settings:{secondaryWindowAspectRatio:16/9,singleScreenLayout:"column-right",multiScreenSingleSecondaryLayout:"separate",multiScreenMultiSecondaryLayout:"separate"}},"Cemu (Proton)":{classes:["steam_app_"],primary:/^Cemu/,secondary:/^GamePad View/,settings:{secondaryWindowAspectRatio:16/9,singleScreenLayout:"column-right",multiScreenSingleSecondaryLayout:"separate",multiScreenMultiSecondaryLayout:"separate",delayReconfigure:!0}},Citra:{classes:["citra","citra-qt","lime-qt","lime3ds"],primary:/^Citra[^\|]+\|[^\|]+$|^Citra[^\|]+\|[^\|]+\|[^\|]+Primary[^\|]*$|^Lime3DS[^\|]+\|[^\|]+$|^Lime3DS[^\|]+\|[^\|]+\|[^\|]+Primary[^\|]*$/,secondary:/^Citra.*Secondary|Lime3DS.*Secondary/,settings:{secondaryWindowAspectRatio:4/3,singleScreenLayout:"column-right",multiScreenSingleSecondaryLayout:"separate",multiScreenMultiSecondaryLayout:"separate",watchCaption:!0}},melonDS:{classes:["melonds","net.kuribo64.melonds"],primary:/^melonDS(?!.*\[\d+\])/,secondary:/^melonDS.*\[\d+\]/,settings:{secondaryWindowAspectRatio:4/3,singleScreenLayout:"column-right",multiScreenSingleSecondaryLayout:"separate",multiScreenMultiSecondaryLayout:"separate"}},Dolphin:{classes:["dolphin-emu"],primary:/^Dolphin$|^(Dolphin.*\|)/,secondary:/^GBA\d+/,settings:{secondaryWindowAspectRatio:3/2,singleScreenLayout:"column-right",multiScreenSingleSecondaryLayout:"separate",multiScreenMultiSecondaryLayout:"column-right"}}};for(let n of["Cemu","Cemu (Proton)"])se(e[n]);ce(e.Citra),Me(e.melonDS),le(e.Dolphin);let t=pe();return t&&(e.Custom=t),e}function se(e){let t=y("cemuSingleScreenLayout","column-right").toLowerCase(),n=y("cemuMultiScreenSingleSecondaryLayout","separate").toLowerCase();print("Cemu Settings:: single:",t,", multi:",n);let o=e.settings;o.singleScreenLayout=t,o.multiScreenSingleSecondaryLayout=n,o.multiScreenMultiSecondaryLayout=n}function ce(e){let t=y("citraSingleScreenLayout","column-right").toLowerCase(),n=y("citraMultiScreenSingleSecondaryLayout","separate").toLowerCase();print("Citra Settings:: single:",t,", multi:",n);let o=e.settings;o.singleScreenLayout=t,o.multiScreenSingleSecondaryLayout=n,o.multiScreenMultiSecondaryLayout=n}function Me(e){let t=y("melonDSSingleScreenLayout","column-right").toLowerCase(),n=y("melonDSMultiScreenSingleSecondaryLayout","separate").toLowerCase();print("melonDS Settings:: single:",t,", multi:",n);let o=e.settings;o.singleScreenLayout=t,o.multiScreenSingleSecondaryLayout=n,o.multiScreenMultiSecondaryLayout=n}function le(e){let t=y("dolphinSingleScreenLayout","column-right").toLowerCase(),n=y("dolphinMultiScreenSingleSecondaryLayout","separate").toLowerCase(),o=y("dolphinMultiScreenMultiSecondaryLayout","column-right").toLowerCase(),a=y("dolphinBlacklist","").trim().toUpperCase();print("Dolphin Settings:: single:",t,", multi1:",n,", multi+:",o,", blacklist:",a);let r=e.settings;r.singleScreenLayout=t,r.multiScreenSingleSecondaryLayout=n,r.multiScreenMultiSecondaryLayout=o,r.blacklist=a.split(",").filter(s=>s.trim().length>0).map(s=>new RegExp(`^${s.trim()}`))}function pe(){let e=y("customPrimaryWindowMatcher",""),t=y("customSecondaryWindowMatcher",""),n=y("customWindowClasses",""),o=y("customSingleScreenLayout","column-right").toLowerCase(),a=y("customMultiScreenSingleSecondaryLayout","separate").toLowerCase(),r=y("customMultiScreenMultiSecondaryLayout","separate").toLowerCase();return e.length>0?(print("Custom Settings::primary_window:",e,", secondary_window:",t,", classes:",n,", single:",o,", multi1:",a,", multi+:",r),{primary:new RegExp(e),secondary:new RegExp(t),classes:n.length>0?n.split(",").map(s=>s.trim()):[],settings:{singleScreenLayout:o,multiScreenSingleSecondaryLayout:a,multiScreenMultiSecondaryLayout:r,secondaryWindowAspectRatio:16/9}}):(print("custom app windows not configured"),null)}function y(e,t){let n=readConfig(e,t);return typeof n=="string"?n.replace(/"/g,"").trim():n}print("!!!EMULATOR_WINDOWING_KWINSCRIPT!!!");var S=0;function k(){return S=(S+1)%1024,S}var R=workspace.numScreens,L=0,h=0,m={},w={},b=new Set,M={},I={},W=!1,F=!1,H=_(),v=j(),ue=U();function P(e){if(e!=S)return;print("Configuring screens");for(let n=0;n<R;n++){let o=n,a=workspace.clientArea(KWin.FullScreenArea,o,1),r=workspace.clientArea(KWin.FullScreenArea,L,1),s=workspace.clientArea(KWin.FullScreenArea,h,1);a.height*a.width>r.height*r.width&&(L=o),a.height*a.width<s.height*s.width&&(h=o)}if(H.swapScreens){let n=L;L=h,h=n}print("primary display: ",L,", geometry: ",workspace.clientArea(KWin.FullScreenArea,L,1)),print("secondary display: ",h,", geometry: ",workspace.clientArea(KWin.FullScreenArea,h,1)),m={},w={},b=new Set;let t=workspace.clientList();for(let n of t)fe(n,e);for(let n in m){let o=m[n];Q(o),G({app:n,type:"primary",settings:v[n].settings},o,e)}}function V(e,t){let n=t[e.windowId];n&&(e.frameGeometry=n.frameGeometry,e.fullScreen=n.fullScreen,e.keepAbove=n.keepAbove,e.keepBelow=n.keepBelow)}function Q(e){for(let t in e)for(let n of e[t])if(!n){let o="Scope "+t+" contains invalid window";print(o),assert(n,o)}}function J(e,t,n,o,a){var p;if(a!=S)return;let r=R===1?t.singleScreenLayout:o===1?t.multiScreenSingleSecondaryLayout:(p=t.multiScreenMultiSecondaryLayout)!=null?p:t.multiScreenSingleSecondaryLayout,s=n===0||r!="separate"?L:h;print("setting client",e.caption,"on screen",s,"with layout",r,"index",n,"with",o,"secondaries");let i=workspace.clientArea(KWin.FullScreenArea,s,workspace.currentDesktop),f=r;switch(o<3&&(r=r.replace("square","column")),o===0&&(r="separate"),print("layout set to",r,"from",f,"after analysis"),r){case"separate":if(print("handling separate layout"),n>0){let u=o>2?i.height/2:i.height,d=o>1?i.width/2:i.width;n%2==0&&(i.x+=d),n===3&&o===3||(i.width=d),i.height=u,n>2&&(i.y+=u)}break;case"column-left":case"column-right":{let u=i.width/2,d=i.height/o,l=t.secondaryWindowAspectRatio*d;if(l=u>l?l:u,o>1&&(d=1/t.secondaryWindowAspectRatio*l),n===0)r==="column-left"&&(i.x+=l),i.width-=l;else if(n>0){r==="column-right"&&(i.x+=i.width-l);let C=n-1;i.y+=C*d,i.width=l,i.height=d}}break;case"square-left":case"square-right":{let u=i.width/4,d=i.height/2,l=t.secondaryWindowAspectRatio*d;l=u>l?l:u,d=1/t.secondaryWindowAspectRatio*l;let C=i.height;if(n===0)r==="square-left"&&(i.x+=l*2),i.width-=l*2;else{if(n%2==0&&(i.x+=l),n>2&&(i.y+=d),r==="square-right"){let x=i.width-l*2;i.x+=x}i.width=l,i.height=d,o===3?(n===3&&(i.width=l*2,i.height=d*2),i.y+=(C-d*3)/2):i.y+=(C-d*2)/2}break}default:throw"unhandled layout: "+r}n!==0&&(e.minimized=!1,e.fullScreen=!0),H.keepAbove&&(e.keepAbove=!0),e.frameGeometry=i,print("client final geometry: x:",i.x,"y:",i.y,"width:",i.width,"height:",i.height),t.delayReconfigure?T(100,()=>{a==S&&workspace.sendClientToScreen(e,s)}):workspace.sendClientToScreen(e,s)}function de(e){return e.primary.length+e.secondary.length+e.other.length+Object.getOwnPropertyNames(w).length}function ye(e,t){let n=de(t);print("Setting",n,"windows for app: ",e,":"),print("primary:",t.primary.map(o=>o.caption)),print("secondary:",t.secondary.map(o=>o.caption)),print("other:",t.other.map(o=>o.caption)),print("secondary app:",Object.getOwnPropertyNames(w).map(o=>w[parseInt(o)].client.caption))}function G(e,t,n){var x,z;if(n!=S)return;print("setting client windows for app",e.app);let o=e.app,a=[...t.primary],r=t.secondary.map(c=>({client:c})),s=[...t.other],i=Object.getOwnPropertyNames(w).map(c=>w[parseInt(c)]);r.sort((c,g)=>c.client.caption<g.client.caption?-1:1),i.sort((c,g)=>c.client.caption<g.client.caption?-1:1);let f=i.filter(c=>{var g,A,E;return((g=c.secondaryConfig)==null?void 0:g.windowingBehavior)==="Fullscreen"&&((E=(A=c.secondaryConfig)==null?void 0:A.tiling)!=null?E:"Auto")==="Auto"}),te=i.filter(c=>{var g,A,E;return((g=c.secondaryConfig)==null?void 0:g.windowingBehavior)==="Fullscreen"&&((E=(A=c.secondaryConfig)==null?void 0:A.tiling)!=null?E:"Auto")!=="Auto"});ye(o,t),a.sort((c,g)=>c.caption.length>g.caption.length?-1:1);let p=a[0],u=[],d=[],l=B({},e.settings),C=B({},e.settings);if(R<2)u.push(...r),u.push(...f);else{e.settings.multiScreenMultiSecondaryLayout==="separate"?d.push(...r):u.push(...r);for(let c of f){if(print("current screen preference for secondary app:",(x=c.secondaryConfig)==null?void 0:x.screenPreference),((z=c.secondaryConfig)==null?void 0:z.screenPreference)==="PreferPrimary"){u.push(c);let g="separate";l.multiScreenSingleSecondaryLayout===g&&(l.multiScreenSingleSecondaryLayout="column-right"),l.multiScreenMultiSecondaryLayout===g&&(l.multiScreenMultiSecondaryLayout="column-right"),print("set secondary app to primary screen; primary settings single:",l.multiScreenSingleSecondaryLayout,"multi:",l.multiScreenMultiSecondaryLayout)}else d.push(c),C.multiScreenSingleSecondaryLayout="separate",C.multiScreenMultiSecondaryLayout="separate",print("sending secondary app to secondary screen");print("handled secondary app",c.client.caption)}}if(p){if(a.length>1){let c=a.splice(1,a.length-1);print("too many primary windows; using",p.caption,", ignoring",c.map(g=>g.caption)),s.push(...c)}if(p.fullScreen){J(p,l,0,u.length,n);for(let{settings:c,secondaries:g}of[{settings:l,secondaries:u},{settings:C,secondaries:d}])for(let A of g){let E=g.indexOf(A)+1;E<=4?W?(J(A.client,c,E,g.length,n),A.client.fullScreen=!0):(V(A.client,M),A.client.fullScreen=!1,A.client.keepAbove=!1):print("too many secondary views; ignoring",A.client.caption)}if(W)Te(te,n);else for(let{client:c}of te)V(c,M),c.fullScreen=!1,c.keepAbove=!1;for(let c of s)print("handling other window:",c.caption),workspace.sendClientToScreen(c,h),c.fullScreen=!1,c.setMaximize(!0,!0);for(let c of b)workspace.sendClientToScreen(c,h)}}}function Te(e,t){var o,a;if(t!=S)return;let n={};for(let r of e){let s=R<2||((o=r.secondaryConfig)==null?void 0:o.screenPreference)==="PreferPrimary"?L:h;n[s]=[...(a=n[s])!=null?a:[],r]}for(let r in n){var s;let i=parseInt(r),f=n[i];f.sort((d,l)=>{var C,x,z,c;return((x=(C=d.secondaryConfig)==null?void 0:C.zOrder)!=null?x:0)-((c=(z=l.secondaryConfig)==null?void 0:z.zOrder)!=null?c:0)});let p=(s=f[0].secondaryConfig)==null?void 0:s.tiling,u=p!=null?p:"Stacked",d=f.length,l=u==="SplitHorizontal"?d:u==="Grid"?Math.ceil(Math.sqrt(d)):1,C=u==="SplitVertical"?d:u==="Grid"?Math.ceil(d/l):1;if(print("tiling",d,"secondary apps on screen",i,"with",u),f.forEach(({client:x},z)=>{let c=u==="Stacked"?0:z,g=workspace.clientArea(KWin.FullScreenArea,i,workspace.currentDesktop);g.width/=l,g.height/=C,g.x+=c%l*g.width,g.y+=Math.floor(c/l)*g.height,workspace.sendClientToScreen(x,i),x.minimized=!1,x.fullScreen=d===1||u==="Stacked",x.keepAbove=H.keepAbove,x.frameGeometry=g}),u==="Stacked"){let x=workspace.activeClient;for(let{client:z}of f)workspace.activeClient=z;workspace.activeClient=x}}}function K(e){var o,a;let t=e.caption,n=e.resourceClass.toString().toLowerCase();for(let r of ue){let s=r.primary.test(t);if(r.classes.some(f=>n.includes(f.toLowerCase()))&&s)switch(r.windowingBehavior){case"Minimized":return e.fullScreen=!1,e.minimized=!0,b.add(e),null;case"Unmanaged":return b.add(e),null;case"Maximized":e.fullScreen=!1,e.minimized=!1,e.setMaximize(!0,!0),b.add(e);case"Fullscreen":let f={client:e,secondaryConfig:r};return w[e.windowId]=f,f;default:let p=r.windowingBehavior;throw p!=null?p:"windowing behavior failed to typecheck"}}for(let r in v){let s=v[r],i=s.primary.test(t);if(s.classes.some(p=>n.toLowerCase().includes(p.toLowerCase()))&&i){if((o=s.settings.blacklist)==null?void 0:o.some(d=>d.test(t)))return e.fullScreen=!1,e.minimized=!0,print(t,"blacklisted by:",s.settings.blacklist),null;let u={app:r,type:"primary",settings:s.settings};return print("matched",t,"with",u.app,"priority",u.type),u}}for(let r in v){let s=v[r],i=s.secondary.test(t);if(s.classes.some(p=>n.toLowerCase().includes(p.toLowerCase()))){if((a=s.settings.blacklist)==null?void 0:a.some(d=>d.test(t)))return e.fullScreen=!1,e.minimized=!0,print(t,"blacklisted by:",s.settings.blacklist),null;let u={app:r,type:i?"secondary":"other",settings:s.settings};return print("matched",t,"with",u.app,"priority",u.type),u}}return print(e.caption,"with class",n,"not matched; ignoring"),b.add(e),null}function D(e){return!!(e==null?void 0:e.settings)}function fe(e,t){let n=K(e);if(n&&ee(e,M),D(n)&&((n==null?void 0:n.settings.watchCaption)&&(e.captionChanged.disconnect(()=>P(S)),e.captionChanged.connect(()=>P(S))),e.normalWindow)){let o=n.app;if(n.type==="primary"&&(print("attaching fullscreen listener to primary window"),W=e.fullScreen,F=W,e.fullScreenChanged.connect(()=>{var a;((a=m[o])==null?void 0:a.primary.includes(e))&&(!e.fullScreen&&Z(!1)&&W?(print("setting fullscreen to former value from fullscreen change"),e.fullScreen=W):(F=W,W=e.fullScreen,print(e.caption,"now fullscreen:",e.fullScreen),G(n,m[o],k())))})),m[o]){let a=m[o],r=a[n.type];a[n.type]=r?[...r,e]:[e],m[o]=a}else{let a={primary:[],secondary:[],other:[]};a[n.type]=[e],m[o]=a}}}var X=new Date("1969-12-29"),Y=new Date("1969-12-29");function Z(e){let t=new Date;return e?Y=t:X=t,Math.abs(X.getTime()-Y.getTime())<100}function ee(e,t){t[e.windowId]||(t[e.windowId]={frameGeometry:e.frameGeometry,fullScreen:e.fullScreen,keepAbove:e.keepAbove,keepBelow:e.keepBelow})}function T(e,t){var n=new QTimer;return n.timeout.connect(function(){n.stop(),t()}),n.start(e),n}function O(e,t){for(let n of e)T(n,()=>P(t))}function ne(e){return!!$().find(n=>n.primary.test(e.caption)&&n.classes.find(o=>o.toString().toLowerCase().includes(e.resourceClass.toString().toLowerCase())))}function ge(){let e=te();console.log("sending",e.length,"clients to background for keyboard");for(let t of e)ee(t,I),t.keepAbove=!1,t.keepBelow=!0}function me(){let e=te();console.log("restoring",e.length,"clients from background from keyboard");for(let t of e)V(t,I),delete I[t.windowId]}function te(){let e=Array.from(b);for(let t in w){let n=w[t];e.push(n.client)}for(let t in m){let n=m[t];e.push(...n.other),e.push(...n.secondary),e.push(...n.primary)}return e}var oe=0;workspace.clientAdded.connect(e=>{if(ne(e)){ge();return}if(!(e.windowId in M)){let t=K(e);D(t)?t.type==="primary"&&workspace.sendClientToScreen(e,L):workspace.sendClientToScreen(e,h)}P(S)});workspace.clientRemoved.connect(e=>{var n;let t=k();if(ne(e)){me();return}if(b.delete(e),e.windowId in M){delete M[e.windowId];let o=K(e);if(D(o)){let a=o.app,r=m[a],s=r.primary,i=++oe;if(s&&s[0]&&Z(!0)){let f=s[0];T(1e3,()=>{let p=m[a];i==oe&&p&&p.primary&&p.primary[0]==f&&(print("setting fullscreen to former value from remove window change"),f.fullScreen=F)})}for(let f of[r.primary,r.secondary,r.other].filter(p=>p)){let p=f.indexOf(e);p>-1&&f.splice(p,1)}m[a]=r,Q(r),G(o,r,t)}else if(o){print("deleting secondary app client",e.windowId);let a=K(e);a&&!D(a)&&(delete w[e.windowId],((n=a.secondaryConfig)==null?void 0:n.windowingBehavior)==="Fullscreen"&&(print("resetting screens after removing client"),O([200],t)))}}});workspace.numberScreensChanged.connect(e=>{R=e,O([2e3,5e3],k())});workspace.virtualScreenGeometryChanged.connect(()=>{O([500,1e3],k())});P(k());
@@ -1,31 +1,51 @@
+pub mod action;
 pub mod autostart;
 pub mod client_pipeline;
+pub mod emulator;
 pub mod general;
 pub mod profile;
 pub mod request_handler;
 pub mod secondary_app;
+pub mod window;
 
 use client_pipeline::{
-    AddClientTeardownActionRequest, GetClientTeardownActionsResponse,
+    AddClientTeardownActionRequest, ApplyGamescopeLaunchOptionsRequest,
+    ApplyGamescopeLaunchOptionsResponse, GetClientTeardownActionsResponse,
     RemoveClientTeardownActionsRequest,
 };
 use schemars::{schema::RootSchema, JsonSchema};
 use usdpl_back::core::serdes::Primitive;
 
 use self::{
+    action::{GetActionUiSchemasResponse, GetOptionsRequest, GetOptionsResponse},
     autostart::AutoStartRequest,
-    general::{
-        GetAudioDeviceInfoResponse, GetDisplayInfoResponse, GetSettingsResponse, SetSettingsRequest,
-    },
+    emulator::DiscoverEmulatorsResponse,
+    general::{GetDisplayInfoResponse, GetSettingsResponse, SetSettingsRequest},
     profile::{
-        CreateProfileRequest, CreateProfileResponse, DeleteProfileRequest, GetAppProfileRequest,
-        GetAppProfileResponse, GetDefaultAppOverrideForProfileRequest,
-        GetDefaultAppOverrideForProfileResponse, GetProfileRequest, GetProfileResponse,
-        GetProfilesResponse, GetTemplatesResponse, GetTopLevelResponse, PatchPipelineActionRequest,
-        PatchPipelineActionResponse, ReifyPipelineRequest, ReifyPipelineResponse,
-        SetAppProfileOverrideRequest, SetAppProfileSettingsRequest, SetProfileRequest,
+        CopyAppProfileOverrideRequest, CreateProfileBackupResponse, CreateProfileRequest,
+        CreateProfileResponse, CreateTemplateRequest, CreateTemplateResponse, DeleteProfileRequest,
+        DeleteTemplateRequest, DiffProfileRevisionsRequest, DiffProfileRevisionsResponse,
+        ExportProfilesRequest, ExportProfilesResponse, GetAppProfileRequest, GetAppProfileResponse,
+        GetDefaultAppOverrideForProfileRequest, GetDefaultAppOverrideForProfileResponse,
+        GetProfileRequest, GetProfileResponse, GetProfileRulesResponse, GetProfilesResponse,
+        GetTemplatesResponse, GetTopLevelResponse, ImportProfilesRequest, ImportProfilesResponse,
+        ListProfileBackupsResponse, ListProfileRevisionsRequest, ListProfileRevisionsResponse,
+        PatchPipelineActionRequest, PatchPipelineActionResponse, ReifyPipelineRequest,
+        ReifyPipelineResponse, ResetAppProfileOverridesRequest, ResetAppProfileOverridesResponse,
+        RestoreProfileBackupRequest, RestoreProfileBackupResponse, RestoreProfileRevisionRequest,
+        SelectAppProfileRequest, SelectAppProfileResponse, SetAppProfileOverrideRequest,
+        SetAppProfileOverridesRequest, SetAppProfileSettingsRequest, SetAppsProfileSettingsRequest,
+        SetProfileRequest, SetProfileRulesRequest, SetProfileRulesResponse, UpdateTemplateRequest,
+        UpdateTemplateResponse,
+    },
+    secondary_app::{
+        CreateSecondaryAppPresetRequest, CreateSecondaryAppPresetResponse,
+        DeleteSecondaryAppPresetRequest, UpdateSecondaryAppPresetRequest,
+    },
+    window::{
+        GetLearnedWindowsResponse, ListWindowsResponse, ResetLearnedWindowsRequest,
+        SetLearnedWindowRequest, TestWindowMatcherRequest, TestWindowMatcherResponse,
     },
-    secondary_app::GetSecondaryAppInfoResponse,
 };
 
 pub(super) type ApiParameterType = Vec<Primitive>;
@@ -108,6 +128,11 @@ pub struct Api {
     pub get_app_profile_response: GetAppProfileResponse,
     pub set_app_profile_settings_request: SetAppProfileSettingsRequest,
     pub set_app_profile_override_request: SetAppProfileOverrideRequest,
+    pub set_app_profile_overrides_request: SetAppProfileOverridesRequest,
+    pub copy_app_profile_override_request: CopyAppProfileOverrideRequest,
+    pub reset_app_profile_overrides_request: ResetAppProfileOverridesRequest,
+    pub reset_app_profile_overrides_response: ResetAppProfileOverridesResponse,
+    pub set_apps_profile_settings_request: SetAppsProfileSettingsRequest,
     pub get_default_app_override_for_profile_request: GetDefaultAppOverrideForProfileRequest,
     pub get_default_app_override_for_profile_response: GetDefaultAppOverrideForProfileResponse,
     pub patch_pipeline_action_request: PatchPipelineActionRequest,
@@ -116,14 +141,58 @@ pub struct Api {
     pub reify_pipeline_response: ReifyPipelineResponse,
     pub get_toplevel_response: GetTopLevelResponse,
     pub get_templates_response: GetTemplatesResponse,
+    pub create_template_request: CreateTemplateRequest,
+    pub create_template_response: CreateTemplateResponse,
+    pub update_template_request: UpdateTemplateRequest,
+    pub update_template_response: UpdateTemplateResponse,
+    pub delete_template_request: DeleteTemplateRequest,
+    pub export_profiles_request: ExportProfilesRequest,
+    pub export_profiles_response: ExportProfilesResponse,
+    pub import_profiles_request: ImportProfilesRequest,
+    pub import_profiles_response: ImportProfilesResponse,
+    pub list_profile_backups_response: ListProfileBackupsResponse,
+    pub create_profile_backup_response: CreateProfileBackupResponse,
+    pub restore_profile_backup_request: RestoreProfileBackupRequest,
+    pub restore_profile_backup_response: RestoreProfileBackupResponse,
+    pub list_profile_revisions_request: ListProfileRevisionsRequest,
+    pub list_profile_revisions_response: ListProfileRevisionsResponse,
+    pub diff_profile_revisions_request: DiffProfileRevisionsRequest,
+    pub diff_profile_revisions_response: DiffProfileRevisionsResponse,
+    pub restore_profile_revision_request: RestoreProfileRevisionRequest,
+    pub get_profile_rules_response: GetProfileRulesResponse,
+    pub set_profile_rules_request: SetProfileRulesRequest,
+    pub set_profile_rules_response: SetProfileRulesResponse,
+    pub select_app_profile_request: SelectAppProfileRequest,
+    pub select_app_profile_response: SelectAppProfileResponse,
+
+    // actions
+    pub get_action_ui_schemas_response: GetActionUiSchemasResponse,
+    pub get_options_request: GetOptionsRequest,
+    pub get_options_response: GetOptionsResponse,
 
     // client pipeline
     pub add_client_teardown_action_request: AddClientTeardownActionRequest,
     pub remove_client_teardown_actions_request: RemoveClientTeardownActionsRequest,
     pub get_client_teardown_actions_response: GetClientTeardownActionsResponse,
+    pub apply_gamescope_launch_options_request: ApplyGamescopeLaunchOptionsRequest,
+    pub apply_gamescope_launch_options_response: ApplyGamescopeLaunchOptionsResponse,
 
     // secondary app
-    pub get_secondary_app_info: GetSecondaryAppInfoResponse,
+    pub create_secondary_app_preset_request: CreateSecondaryAppPresetRequest,
+    pub create_secondary_app_preset_response: CreateSecondaryAppPresetResponse,
+    pub update_secondary_app_preset_request: UpdateSecondaryAppPresetRequest,
+    pub delete_secondary_app_preset_request: DeleteSecondaryAppPresetRequest,
+
+    // windows
+    pub list_windows_response: ListWindowsResponse,
+    pub test_window_matcher_request: TestWindowMatcherRequest,
+    pub test_window_matcher_response: TestWindowMatcherResponse,
+    pub get_learned_windows_response: GetLearnedWindowsResponse,
+    pub set_learned_window_request: SetLearnedWindowRequest,
+    pub reset_learned_windows_request: ResetLearnedWindowsRequest,
+
+    // emulators
+    pub discover_emulators_response: DiscoverEmulatorsResponse,
 
     // settings
     pub get_settings_response: GetSettingsResponse,
@@ -131,7 +200,6 @@ pub struct Api {
 
     // system info
     pub get_display_info: GetDisplayInfoResponse,
-    pub get_audio_device_info: GetAudioDeviceInfoResponse,
 
     // autostart
     pub autostart_request: AutoStartRequest,
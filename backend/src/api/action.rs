@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    option_source::{OptionList, OptionSource, OptionSources},
+    pipeline::action::{ui_schema::ActionUiSchema, ActionType},
+};
+
+use super::{
+    request_handler::{exec_with_args, log_invoke, RequestHandler},
+    ResponseErr, StatusCode, ToResponse,
+};
+
+// Get Action UI Schemas
+
+crate::derive_api_marker!(GetActionUiSchemasResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GetActionUiSchemasResponse {
+    schemas: Vec<ActionUiSchema>,
+}
+
+pub fn get_action_ui_schemas() -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args| {
+        log_invoke("get_action_ui_schemas", &args);
+
+        GetActionUiSchemasResponse {
+            schemas: ActionType::iter().map(|t| t.ui_schema()).collect(),
+        }
+        .to_response()
+    }
+}
+
+// Get Options
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetOptionsRequest {
+    source: OptionSource,
+}
+
+crate::derive_api_marker!(GetOptionsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GetOptionsResponse {
+    list: OptionList,
+}
+
+pub fn get_options(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    option_sources: OptionSources,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "get_options",
+        request_handler,
+        move |args: GetOptionsRequest| {
+            option_sources
+                .get(args.source)
+                .map(|list| GetOptionsResponse { list })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
@@ -23,6 +23,7 @@ use crate::{
 
 use super::{
     request_handler::{exec_with_args, RequestHandler},
+    window::sync_learned_windows,
     ResponseErr, ResponseOk, StatusCode,
 };
 
@@ -52,12 +53,17 @@ pub fn autostart(
             let session =
                 check_session().map_err(|err| ResponseErr(StatusCode::ServerError, err))?;
 
+            // app overrides inherit from their profile, profiles from their parent
             let definition = profile_db
                 .get_app_profile(&args.app_id)
                 .and_then(|app| {
-                    app.overrides.get(&args.profile_id).cloned().ok_or_else(|| {
-                        anyhow::anyhow!("Failed to find app override for profile: {:?}", app.id)
-                    })
+                    app.overrides
+                        .get(&args.profile_id)
+                        .cloned()
+                        .map(|o| (o, Some(args.profile_id)))
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Failed to find app override for profile: {:?}", app.id)
+                        })
                 })
                 .or_else(|_| {
                     profile_db
@@ -67,11 +73,11 @@ pub fn autostart(
                                 anyhow::anyhow!("Failed to find profile for {:?}", &args.profile_id)
                             })
                         })
-                        .map(|p| p.pipeline)
+                        .map(|p| (p.pipeline, p.parent))
                 });
 
             match definition {
-                Ok(definition) => {
+                Ok((definition, parent)) => {
                     let profiles = profile_db.get_profiles().unwrap();
 
                     let global_config = {
@@ -82,18 +88,31 @@ pub fn autostart(
                         settings_lock.get_global_cfg()
                     };
                     let mut ctx = PipelineContext::new(None, global_config, decky_env.clone());
-                    let pipeline = definition.reify(&profiles, &mut ctx, &registrar).unwrap();
+                    let pipeline = definition
+                        .reify(&profiles, parent, &mut ctx, &registrar)
+                        .unwrap();
 
                     let id = args
                         .game_id
                         .map(Either::Right)
                         .unwrap_or(Either::Left(args.app_id.clone()));
 
+                    if let Err(err) = sync_learned_windows(profile_db, &decky_env) {
+                        log::warn!("failed to sync learned windows: {err:#?}");
+                    }
+
+                    let learned_window = profile_db
+                        .get_learned_window(&args.app_id)
+                        .inspect_err(|err| log::warn!("failed to load learned window: {err:#?}"))
+                        .ok()
+                        .flatten();
+
                     let launch_info = SteamLaunchInfo {
                         app_id: args.app_id,
                         user_id_64: args.user_id_64,
                         game_title: args.game_title,
                         is_steam_game: args.is_steam_game,
+                        learned_window,
                     };
                     let autostart_info = AutostartInfo {
                         id,
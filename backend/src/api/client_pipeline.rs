@@ -4,7 +4,12 @@ use egui::ahash::HashSet;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::client_pipeline::{ClientPipelineHandler, ClientTeardownAction};
+use crate::{
+    client_pipeline::{
+        get_gamescope_output_resolution, ClientPipelineHandler, ClientTeardownAction,
+    },
+    pipeline::action::multi_window::main_app_automatic_windowing::MainAppAutomaticWindowing,
+};
 
 use super::{
     request_handler::{exec_with_args, log_invoke, RequestHandler},
@@ -82,3 +87,57 @@ pub fn get_client_teardown_actions(
         GetClientTeardownActionsResponse { actions }.to_response()
     }
 }
+
+// Apply Gamescope Launch Options
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ApplyGamescopeLaunchOptionsRequest {
+    app_id: u32,
+    action: MainAppAutomaticWindowing,
+    /// The app's launch options, as currently set in Steam
+    launch_options: String,
+}
+
+crate::derive_api_marker!(ApplyGamescopeLaunchOptionsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ApplyGamescopeLaunchOptionsResponse {
+    /// The launch options to set in Steam, if gamescope is enabled. The originals
+    /// are restored by the recorded client teardown action.
+    launch_options: Option<String>,
+}
+
+pub fn apply_gamescope_launch_options(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    client_pipeline: Arc<Mutex<ClientPipelineHandler>>,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "apply_gamescope_launch_options",
+        request_handler,
+        move |args: ApplyGamescopeLaunchOptionsRequest| {
+            let gamescope = &args.action.gamescope;
+
+            if !gamescope.use_gamescope {
+                return Ok(ApplyGamescopeLaunchOptionsResponse {
+                    launch_options: None,
+                });
+            }
+
+            let output = get_gamescope_output_resolution(args.action.general.swap_screens)
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))?;
+
+            gamescope
+                .validate(output)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))?;
+
+            let mut lock = client_pipeline
+                .lock()
+                .expect("client pipeline should not be poisoned");
+
+            lock.apply_gamescope_launch_options(args.app_id, gamescope, output, args.launch_options)
+                .map(|launch_options| ApplyGamescopeLaunchOptionsResponse {
+                    launch_options: Some(launch_options),
+                })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
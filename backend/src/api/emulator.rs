@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    decky_env::DeckyEnv,
+    emu_discovery::{discover_emulators as discover, EmuInstall},
+    pipeline::executor::PipelineContext,
+};
+
+use super::{request_handler::log_invoke, ToResponse};
+
+crate::derive_api_marker!(DiscoverEmulatorsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiscoverEmulatorsResponse {
+    installs: Vec<EmuInstall>,
+}
+
+pub fn discover_emulators(
+    decky_env: Arc<DeckyEnv>,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args: super::ApiParameterType| {
+        log_invoke("discover_emulators", &args);
+
+        let ctx = PipelineContext::new(None, Default::default(), decky_env.clone());
+        let installs = discover(&ctx);
+
+        DiscoverEmulatorsResponse { installs }.to_response()
+    }
+}
@@ -5,12 +5,9 @@ use serde::{Deserialize, Serialize};
 use usdpl_back::core::serdes::Primitive;
 
 use crate::{
-    decky_env::DeckyEnv,
+    db::ProfileDb,
     settings::{GlobalConfig, Settings},
-    sys::{
-        audio::{get_audio_sinks, get_audio_sources, AudioDeviceInfo},
-        display_info::{self, DisplayValues},
-    },
+    sys::display_info::{self, DisplayValues},
 };
 
 use super::{
@@ -52,6 +49,7 @@ pub struct SetSettingsRequest {
 pub fn set_settings(
     request_handler: Arc<Mutex<RequestHandler>>,
     settings: Arc<Mutex<Settings>>,
+    profiles: &'static ProfileDb,
 ) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
     exec_with_args(
         "set_settings",
@@ -62,7 +60,11 @@ pub fn set_settings(
                 .expect("settings mutex should not be poisoned");
 
             lock.set_global_cfg(&args.global_settings)
-                .map(|_| ResponseOk)
+                .map(|_| {
+                    profiles
+                        .set_revision_retention(args.global_settings.profile_revision_retention);
+                    ResponseOk
+                })
                 .map_err(|err| ResponseErr(StatusCode::ServerError, err))
         },
     )
@@ -86,26 +88,6 @@ pub fn get_display_info() -> impl Fn(super::ApiParameterType) -> super::ApiParam
     }
 }
 
-// Get Audio Device Info
-
-crate::derive_api_marker!(GetAudioDeviceInfoResponse);
-#[derive(Debug, Clone, Serialize, JsonSchema)]
-pub struct GetAudioDeviceInfoResponse {
-    sources: Vec<AudioDeviceInfo>,
-    sinks: Vec<AudioDeviceInfo>,
-}
-
-pub fn get_audio_device_info(
-    decky_env: Arc<DeckyEnv>,
-) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
-    move |args| {
-        log_invoke("get_audio_device_info", &args);
-        let sources = get_audio_sources(&decky_env);
-        let sinks = get_audio_sinks(&decky_env);
-        GetAudioDeviceInfoResponse { sources, sinks }.to_response()
-    }
-}
-
 /// Error Msg Test
 pub fn test_error() -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
     move |_| {
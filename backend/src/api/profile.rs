@@ -3,18 +3,26 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use anyhow::Context;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    db::ProfileDb,
+    db::{
+        backup::{BackupReason, ProfileDbBackup},
+        bundle::{ProfileBundle, ProfileImportReport},
+        revision::{ProfileRevision, RevisionChange, RevisionId},
+        rules::{AppMetadata, ProfileRule, ProfileSelection},
+        ProfileDb,
+    },
     decky_env::DeckyEnv,
     pipeline::{
         action::{Action, ActionId, ErasedPipelineAction},
         action_registar::PipelineActionRegistrar,
         data::{
-            ConfigSelection, Pipeline, PipelineActionId, PipelineDefinition, PipelineDefinitionId,
-            PipelineTarget, RuntimeSelection, Template, TopLevelDefinition, TopLevelId,
+            ConfigSelection, Pipeline, PipelineActionDefinition, PipelineActionId,
+            PipelineActionSettings, PipelineDefinition, PipelineDefinitionId, PipelineTarget,
+            RuntimeSelection, Template, TemplateId, TopLevelDefinition, TopLevelId,
         },
         dependency::DependencyError,
         executor::PipelineContext,
@@ -150,12 +158,17 @@ pub fn delete_profile(
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct GetAppProfileRequest {
     app_id: AppId,
+    /// Used to select the profile by rule, if the app has no default profile
+    #[serde(default)]
+    metadata: Option<AppMetadata>,
 }
 
 crate::derive_api_marker!(GetAppProfileResponse);
 #[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct GetAppProfileResponse {
     app: AppProfile,
+    /// The profile to launch the app with; the default profile if no rule applies
+    selected_profile: Option<ProfileId>,
 }
 
 pub fn get_app_profile(
@@ -166,10 +179,25 @@ pub fn get_app_profile(
         "get_app_profile",
         request_handler,
         |args: GetAppProfileRequest| {
-            profiles
+            let app = profiles
                 .get_app_profile(&args.app_id)
-                .map(|app| GetAppProfileResponse { app })
-                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))?;
+
+            let selected_profile = args
+                .metadata
+                .filter(|metadata| metadata.app_id == args.app_id)
+                .and_then(|metadata| {
+                    profiles
+                        .select_profile(&metadata)
+                        .inspect_err(|err| log::warn!("failed to select app profile: {err:#}"))
+                        .ok()
+                })
+                .map_or(app.default_profile, |selection| selection.profile_id);
+
+            Ok(GetAppProfileResponse {
+                app,
+                selected_profile,
+            })
         },
     )
 }
@@ -223,6 +251,105 @@ pub fn set_app_profile_override(
     )
 }
 
+// Bulk App Overrides
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SetAppProfileOverridesRequest {
+    app_ids: Vec<AppId>,
+    profile_id: ProfileId,
+    pipeline: PipelineDefinition,
+}
+
+pub fn set_app_profile_overrides(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "set_app_profile_overrides",
+        request_handler,
+        |args: SetAppProfileOverridesRequest| {
+            profiles
+                .set_app_profile_overrides(&args.app_ids, args.profile_id, args.pipeline)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct CopyAppProfileOverrideRequest {
+    source_app_id: AppId,
+    profile_id: ProfileId,
+    app_ids: Vec<AppId>,
+}
+
+pub fn copy_app_profile_override(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "copy_app_profile_override",
+        request_handler,
+        |args: CopyAppProfileOverrideRequest| {
+            profiles
+                .copy_app_profile_override(&args.source_app_id, args.profile_id, &args.app_ids)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ResetAppProfileOverridesRequest {
+    app_ids: Vec<AppId>,
+    /// The profile to reset overrides for; all profiles if not set
+    profile_id: Option<ProfileId>,
+}
+
+crate::derive_api_marker!(ResetAppProfileOverridesResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ResetAppProfileOverridesResponse {
+    reset: Vec<AppId>,
+}
+
+pub fn reset_app_profile_overrides(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "reset_app_profile_overrides",
+        request_handler,
+        |args: ResetAppProfileOverridesRequest| {
+            profiles
+                .reset_app_profile_overrides(&args.app_ids, args.profile_id)
+                .map(|reset| ResetAppProfileOverridesResponse { reset })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SetAppsProfileSettingsRequest {
+    app_ids: Vec<AppId>,
+    default_profile: Option<ProfileId>,
+}
+
+pub fn set_apps_profile_settings(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "set_apps_profile_settings",
+        request_handler,
+        |args: SetAppsProfileSettingsRequest| {
+            profiles
+                .set_apps_profile_settings(&args.app_ids, args.default_profile)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
 // Get Default App Override Pipline for Profile
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
@@ -309,6 +436,7 @@ pub enum PipelineActionUpdate {
     UpdateOneOf { selection: PipelineActionId },
     UpdateAction { action: Action },
     UpdateVisibleOnQAM { is_visible: bool },
+    Inherit,
 }
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
@@ -356,15 +484,17 @@ pub fn patch_pipeline_action(
                                 _ => None,
                             })
                             .unwrap_or(ActionId::nil());
-                        let pipeline_action = tl
-                            .actions
-                            .actions
-                            .entry(args.action_id.clone())
-                            .or_insert(registered.clone().settings.into());
+                        let actions = &mut tl.actions.actions;
+                        let id = &args.action_id;
 
                         match args.update {
+                            PipelineActionUpdate::Inherit => {
+                                // without its own settings, the action is inherited from the parent
+                                // profile, or the profile itself for app overrides
+                                actions.remove(id);
+                            }
                             PipelineActionUpdate::UpdateEnabled { is_enabled } => {
-                                pipeline_action.enabled = Some(is_enabled);
+                                own_settings(actions, id, registered).enabled = Some(is_enabled);
                             }
                             PipelineActionUpdate::UpdateProfileOverride { profile_override } => {
                                 log::info!(
@@ -373,24 +503,31 @@ pub fn patch_pipeline_action(
                                     profile_override
                                 );
 
-                                pipeline_action.profile_override = profile_override
+                                own_settings(actions, id, registered).profile_override =
+                                    profile_override
                             }
                             PipelineActionUpdate::UpdateOneOf { selection } => {
-                                pipeline_action.selection = ConfigSelection::OneOf { selection }
+                                own_settings(actions, id, registered).selection =
+                                    ConfigSelection::OneOf { selection }
                             }
                             PipelineActionUpdate::UpdateAction { action } => {
-                                pipeline_action.selection =
+                                own_settings(actions, id, registered).selection =
                                     ConfigSelection::Action(action.cloned_with_id(current_id))
                             }
                             PipelineActionUpdate::UpdateVisibleOnQAM { is_visible } => {
-                                pipeline_action.is_visible_on_qam = is_visible
+                                own_settings(actions, id, registered).is_visible_on_qam = is_visible
                             }
                         }
 
-                        if let ConfigSelection::OneOf { selection } = &mut pipeline_action.selection
+                        if let Some(ConfigSelection::OneOf { selection }) =
+                            actions.get_mut(id).map(|v| &mut v.selection)
                         {
-                            let reified = registrar.get(selection, args.target).unwrap().id.clone();
-                            *selection = reified
+                            let reified = registrar
+                                .get(selection, args.target)
+                                .with_context(|| format!("selection {selection:?} not registered"))
+                                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))?;
+
+                            *selection = reified.id.clone();
                         }
 
                         Ok(PatchPipelineActionResponse { pipeline })
@@ -411,11 +548,30 @@ pub fn patch_pipeline_action(
     )
 }
 
+/// The settings `actions` has of its own for `id`, added from the `registered` defaults if missing.
+fn own_settings<'a>(
+    actions: &'a mut HashMap<PipelineActionId, PipelineActionSettings<ConfigSelection>>,
+    id: &PipelineActionId,
+    registered: &PipelineActionDefinition,
+) -> &'a mut PipelineActionSettings<ConfigSelection> {
+    actions
+        .entry(id.clone())
+        .or_insert_with(|| registered.settings.clone().into())
+}
+
 // Reify Pipeline
 
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ReifyPipelineRequest {
     pipeline: PipelineDefinition,
+    /// The stored profile the pipeline belongs to, if any. Actions the pipeline doesn't
+    /// specify are inherited from the profile's parent.
+    #[serde(default)]
+    profile_id: Option<ProfileId>,
+    /// The app the pipeline overrides `profile_id` for, if it is an app override. Actions
+    /// the override doesn't specify are inherited from the profile itself.
+    #[serde(default)]
+    app_id: Option<AppId>,
 }
 
 crate::derive_api_marker!(ReifyPipelineResponse);
@@ -436,8 +592,11 @@ pub fn reify_pipeline(
         request_handler,
         move |args: ReifyPipelineRequest| match profiles.get_profiles() {
             Ok(profiles) => {
+                let parent = stored_parent(&profiles, args.profile_id, args.app_id.is_some())
+                    .map_err(|err| ResponseErr(StatusCode::BadRequest, err))?;
+
                 let ctx = &mut PipelineContext::new(None, Default::default(), decky_env.clone());
-                let res = args.pipeline.reify(&profiles, ctx, &registrar);
+                let res = args.pipeline.reify(&profiles, parent, ctx, &registrar);
 
                 match res {
                     Ok(pipeline) => Ok(ReifyPipelineResponse {
@@ -452,6 +611,30 @@ pub fn reify_pipeline(
     )
 }
 
+/// Returns the profile a pipeline inherits from; the stored parent of `profile_id`,
+/// or the profile itself for an app override.
+fn stored_parent(
+    profiles: &[CategoryProfile],
+    profile_id: Option<ProfileId>,
+    is_app_override: bool,
+) -> anyhow::Result<Option<ProfileId>> {
+    match profile_id {
+        Some(id) if is_app_override => Ok(Some(id)),
+        Some(id) => profiles
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| p.parent)
+            .ok_or_else(|| anyhow::anyhow!("Failed to find profile for {id:?}")),
+        None => {
+            anyhow::ensure!(
+                !is_app_override,
+                "app overrides must specify the overridden profile"
+            );
+            Ok(None)
+        }
+    }
+}
+
 fn check_config_errors(
     pipeline: &Pipeline,
     ctx: &mut PipelineContext,
@@ -560,9 +743,380 @@ pub fn get_templates(
     move |args: super::ApiParameterType| {
         log_invoke("get_templates", &args);
 
-        let lock = profiles;
-        let templates = lock.get_templates().to_vec();
+        match profiles.get_templates() {
+            Ok(templates) => GetTemplatesResponse { templates }.to_response(),
+            Err(err) => ResponseErr(StatusCode::ServerError, err).to_response(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct CreateTemplateRequest {
+    /// The profile to save as a template
+    profile_id: ProfileId,
+    description: Option<String>,
+    tags: Vec<String>,
+}
+
+crate::derive_api_marker!(CreateTemplateResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CreateTemplateResponse {
+    template: Template,
+}
+
+pub fn create_template(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "create_template",
+        request_handler,
+        |args: CreateTemplateRequest| {
+            profiles
+                .create_template(args.profile_id, args.description, args.tags)
+                .map(|template| CreateTemplateResponse { template })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct UpdateTemplateRequest {
+    template_id: TemplateId,
+    description: Option<String>,
+    tags: Vec<String>,
+}
+
+crate::derive_api_marker!(UpdateTemplateResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UpdateTemplateResponse {
+    template: Template,
+}
+
+pub fn update_template(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "update_template",
+        request_handler,
+        |args: UpdateTemplateRequest| {
+            profiles
+                .update_template(&args.template_id, args.description, args.tags)
+                .map(|template| UpdateTemplateResponse { template })
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct DeleteTemplateRequest {
+    template_id: TemplateId,
+}
+
+pub fn delete_template(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "delete_template",
+        request_handler,
+        |args: DeleteTemplateRequest| {
+            profiles
+                .delete_template(&args.template_id)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+// Export Profiles
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ExportProfilesRequest {
+    /// Profiles to export; all profiles if unset
+    profile_ids: Option<Vec<ProfileId>>,
+}
+
+crate::derive_api_marker!(ExportProfilesResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ExportProfilesResponse {
+    bundle: ProfileBundle,
+}
+
+pub fn export_profiles(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "export_profiles",
+        request_handler,
+        |args: ExportProfilesRequest| {
+            profiles
+                .export_profiles(args.profile_ids.as_deref())
+                .map(|bundle| ExportProfilesResponse { bundle })
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
 
-        GetTemplatesResponse { templates }.to_response()
+// Import Profiles
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ImportProfilesRequest {
+    bundle: ProfileBundle,
+}
+
+crate::derive_api_marker!(ImportProfilesResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ImportProfilesResponse {
+    report: ProfileImportReport,
+}
+
+pub fn import_profiles(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+    registrar: PipelineActionRegistrar,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "import_profiles",
+        request_handler,
+        move |args: ImportProfilesRequest| {
+            profiles
+                .import_profiles(args.bundle, &registrar)
+                .map(|report| ImportProfilesResponse { report })
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+// Profile Backups
+
+crate::derive_api_marker!(ListProfileBackupsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListProfileBackupsResponse {
+    /// Newest first
+    backups: Vec<ProfileDbBackup>,
+}
+
+pub fn list_profile_backups(
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args: super::ApiParameterType| {
+        log_invoke("list_profile_backups", &args);
+
+        match profiles.list_backups() {
+            Ok(backups) => ListProfileBackupsResponse { backups }.to_response(),
+            Err(err) => ResponseErr(StatusCode::ServerError, err).to_response(),
+        }
+    }
+}
+
+crate::derive_api_marker!(CreateProfileBackupResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CreateProfileBackupResponse {
+    backup: ProfileDbBackup,
+}
+
+pub fn create_profile_backup(
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args: super::ApiParameterType| {
+        log_invoke("create_profile_backup", &args);
+
+        match profiles.backup(BackupReason::Manual) {
+            Ok(backup) => CreateProfileBackupResponse { backup }.to_response(),
+            Err(err) => ResponseErr(StatusCode::ServerError, err).to_response(),
+        }
     }
 }
+
+/// Restores a profile backup. The restore is staged, and replaces the profile db
+/// the next time the plugin starts.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct RestoreProfileBackupRequest {
+    backup_id: String,
+}
+
+crate::derive_api_marker!(RestoreProfileBackupResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RestoreProfileBackupResponse {
+    /// Whether the plugin must restart before the restored profiles are used
+    restart_required: bool,
+}
+
+pub fn restore_profile_backup(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "restore_profile_backup",
+        request_handler,
+        |args: RestoreProfileBackupRequest| {
+            profiles
+                .stage_restore(&args.backup_id)
+                .map(|_| RestoreProfileBackupResponse {
+                    restart_required: true,
+                })
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+// Profile Revisions
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ListProfileRevisionsRequest {
+    profile_id: ProfileId,
+    /// Lists the revisions of the profile's override for this app, rather than the profile itself
+    app_id: Option<AppId>,
+}
+
+crate::derive_api_marker!(ListProfileRevisionsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListProfileRevisionsResponse {
+    /// Newest first
+    revisions: Vec<ProfileRevision>,
+}
+
+pub fn list_profile_revisions(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "list_profile_revisions",
+        request_handler,
+        |args: ListProfileRevisionsRequest| {
+            profiles
+                .list_revisions(args.profile_id, args.app_id.as_ref())
+                .map(|revisions| ListProfileRevisionsResponse { revisions })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct DiffProfileRevisionsRequest {
+    from: RevisionId,
+    to: RevisionId,
+}
+
+crate::derive_api_marker!(DiffProfileRevisionsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DiffProfileRevisionsResponse {
+    changes: Vec<RevisionChange>,
+}
+
+pub fn diff_profile_revisions(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "diff_profile_revisions",
+        request_handler,
+        |args: DiffProfileRevisionsRequest| {
+            profiles
+                .diff_revisions(args.from, args.to)
+                .map(|changes| DiffProfileRevisionsResponse { changes })
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct RestoreProfileRevisionRequest {
+    revision_id: RevisionId,
+}
+
+pub fn restore_profile_revision(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "restore_profile_revision",
+        request_handler,
+        |args: RestoreProfileRevisionRequest| {
+            profiles
+                .restore_revision(args.revision_id)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+// Profile Rules
+
+crate::derive_api_marker!(GetProfileRulesResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GetProfileRulesResponse {
+    /// In the order they are evaluated
+    rules: Vec<ProfileRule>,
+}
+
+pub fn get_profile_rules(
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args: super::ApiParameterType| {
+        log_invoke("get_profile_rules", &args);
+
+        match profiles.get_profile_rules() {
+            Ok(rules) => GetProfileRulesResponse { rules }.to_response(),
+            Err(err) => ResponseErr(StatusCode::ServerError, err).to_response(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SetProfileRulesRequest {
+    rules: Vec<ProfileRule>,
+}
+
+crate::derive_api_marker!(SetProfileRulesResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SetProfileRulesResponse {
+    /// The saved rules, with ids assigned
+    rules: Vec<ProfileRule>,
+}
+
+pub fn set_profile_rules(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "set_profile_rules",
+        request_handler,
+        |args: SetProfileRulesRequest| {
+            profiles
+                .set_profile_rules(args.rules)
+                .map(|rules| SetProfileRulesResponse { rules })
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SelectAppProfileRequest {
+    app: AppMetadata,
+}
+
+crate::derive_api_marker!(SelectAppProfileResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SelectAppProfileResponse {
+    selection: ProfileSelection,
+}
+
+pub fn select_app_profile(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profiles: &'static ProfileDb,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "select_app_profile",
+        request_handler,
+        |args: SelectAppProfileRequest| {
+            profiles
+                .select_profile(&args.app)
+                .map(|selection| SelectAppProfileResponse { selection })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
@@ -1,37 +1,87 @@
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{
-    secondary_app::{SecondaryAppManager, SecondaryAppPreset, SecondaryAppPresetId},
-    sys::flatpak::{list_installed_flatpaks, FlatpakInfo},
+use crate::secondary_app::{SecondaryAppManager, SecondaryAppPreset, SecondaryAppPresetId};
+
+use super::{
+    request_handler::{exec_with_args, RequestHandler},
+    ResponseErr, ResponseOk, StatusCode,
 };
 
-use super::{request_handler::log_invoke, ToResponse};
+// Create Preset
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct CreateSecondaryAppPresetRequest {
+    preset: SecondaryAppPreset,
+}
 
-crate::derive_api_marker!(GetSecondaryAppInfoResponse);
+crate::derive_api_marker!(CreateSecondaryAppPresetResponse);
 #[derive(Debug, Clone, Serialize, JsonSchema)]
-pub struct GetSecondaryAppInfoResponse {
-    presets: HashMap<SecondaryAppPresetId, SecondaryAppPreset>,
-    installed_flatpaks: Vec<FlatpakInfo>,
+pub struct CreateSecondaryAppPresetResponse {
+    preset_id: SecondaryAppPresetId,
+}
+
+pub fn create_secondary_app_preset(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    secondary_app_manager: SecondaryAppManager,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "create_secondary_app_preset",
+        request_handler,
+        move |args: CreateSecondaryAppPresetRequest| {
+            secondary_app_manager
+                .create_preset(args.preset)
+                .map(|preset_id| CreateSecondaryAppPresetResponse { preset_id })
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+// Update Preset
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct UpdateSecondaryAppPresetRequest {
+    preset_id: SecondaryAppPresetId,
+    preset: SecondaryAppPreset,
+}
+
+pub fn update_secondary_app_preset(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    secondary_app_manager: SecondaryAppManager,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "update_secondary_app_preset",
+        request_handler,
+        move |args: UpdateSecondaryAppPresetRequest| {
+            secondary_app_manager
+                .update_preset(args.preset_id, args.preset)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
+}
+
+// Delete Preset
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct DeleteSecondaryAppPresetRequest {
+    preset_id: SecondaryAppPresetId,
 }
 
-pub fn get_secondary_app_info(
+pub fn delete_secondary_app_preset(
+    request_handler: Arc<Mutex<RequestHandler>>,
     secondary_app_manager: SecondaryAppManager,
 ) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
-    move |args: super::ApiParameterType| {
-        log_invoke("get_secondary_app_info", &args);
-
-        let presets = secondary_app_manager.get_presets();
-        let installed_flatpaks = list_installed_flatpaks()
-            .inspect_err(|err| log::warn!("Unable to fetch installed flatpaks: {err}"))
-            .unwrap_or_default();
-
-        GetSecondaryAppInfoResponse {
-            presets,
-            installed_flatpaks,
-        }
-        .to_response()
-    }
+    exec_with_args(
+        "delete_secondary_app_preset",
+        request_handler,
+        move |args: DeleteSecondaryAppPresetRequest| {
+            secondary_app_manager
+                .delete_preset(args.preset_id)
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))
+        },
+    )
 }
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::ProfileDb,
+    decky_env::DeckyEnv,
+    pipeline::action::multi_window::main_app_automatic_windowing::{
+        take_learned_windows, LearnedWindowMatch,
+    },
+    settings::AppId,
+    sys::kwin::{list_windows as list_kwin_windows, KWinClientInfo, KWinWindowMatcher},
+};
+
+use super::{
+    request_handler::{exec_with_args, log_invoke, RequestHandler},
+    ResponseErr, ResponseOk, StatusCode, ToResponse,
+};
+
+// List Windows
+
+crate::derive_api_marker!(ListWindowsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ListWindowsResponse {
+    windows: Vec<KWinClientInfo>,
+}
+
+pub fn list_windows() -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args: super::ApiParameterType| {
+        log_invoke("list_windows", &args);
+
+        match list_kwin_windows() {
+            Ok(windows) => ListWindowsResponse { windows }.to_response(),
+            Err(err) => ResponseErr(StatusCode::ServerError, err).to_response(),
+        }
+    }
+}
+
+// Test Window Matcher
+
+/// A candidate caption regex and window class list, as used by the custom
+/// and secondary app window options.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct TestWindowMatcherRequest {
+    window_matcher: Option<String>,
+    classes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WindowMatch {
+    window: KWinClientInfo,
+    caption_matches: bool,
+    class_matches: bool,
+}
+
+crate::derive_api_marker!(TestWindowMatcherResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TestWindowMatcherResponse {
+    /// All open windows, with which parts of the matcher they satisfy
+    windows: Vec<WindowMatch>,
+}
+
+pub fn test_window_matcher(
+    request_handler: Arc<Mutex<RequestHandler>>,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "test_window_matcher",
+        request_handler,
+        move |args: TestWindowMatcherRequest| {
+            let matcher = KWinWindowMatcher::new(args.window_matcher.as_deref(), &args.classes)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))?;
+
+            let windows = list_kwin_windows()
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))?
+                .into_iter()
+                .map(|window| WindowMatch {
+                    caption_matches: matcher.matches_caption(&window),
+                    class_matches: matcher.matches_class(&window),
+                    window,
+                })
+                .collect();
+
+            Ok(TestWindowMatcherResponse { windows })
+        },
+    )
+}
+
+// Learned Windows
+
+/// Saves the windows learned by automatic windowing since the last sync to the profile db.
+pub(super) fn sync_learned_windows(profile_db: &ProfileDb, decky_env: &DeckyEnv) -> Result<()> {
+    let pending = take_learned_windows(decky_env)?;
+
+    if !pending.is_empty() {
+        log::debug!("saving {} learned window(s)", pending.len());
+        profile_db.set_learned_windows(pending)?;
+    }
+
+    Ok(())
+}
+
+crate::derive_api_marker!(GetLearnedWindowsResponse);
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GetLearnedWindowsResponse {
+    learned: HashMap<AppId, LearnedWindowMatch>,
+}
+
+pub fn get_learned_windows(
+    profile_db: &'static ProfileDb,
+    decky_env: Arc<DeckyEnv>,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    move |args: super::ApiParameterType| {
+        log_invoke("get_learned_windows", &args);
+
+        if let Err(err) = sync_learned_windows(profile_db, &decky_env) {
+            log::warn!("failed to sync learned windows: {err:#?}");
+        }
+
+        let learned = profile_db
+            .get_learned_windows()
+            .inspect_err(|err| log::warn!("Unable to load learned windows: {err}"))
+            .unwrap_or_default();
+
+        GetLearnedWindowsResponse { learned }.to_response()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SetLearnedWindowRequest {
+    app_id: AppId,
+    learned: LearnedWindowMatch,
+}
+
+pub fn set_learned_window(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profile_db: &'static ProfileDb,
+    decky_env: Arc<DeckyEnv>,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "set_learned_window",
+        request_handler,
+        move |args: SetLearnedWindowRequest| {
+            KWinWindowMatcher::new(Some(&args.learned.window_matcher), &args.learned.classes)
+                .map_err(|err| ResponseErr(StatusCode::BadRequest, err))?;
+
+            // sync first, so a pending match can't overwrite the edit
+            sync_learned_windows(profile_db, &decky_env)
+                .and_then(|_| profile_db.set_learned_windows([(args.app_id, args.learned)]))
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ResetLearnedWindowsRequest {
+    /// Apps to forget; all apps if unset
+    app_ids: Option<Vec<AppId>>,
+}
+
+pub fn reset_learned_windows(
+    request_handler: Arc<Mutex<RequestHandler>>,
+    profile_db: &'static ProfileDb,
+    decky_env: Arc<DeckyEnv>,
+) -> impl Fn(super::ApiParameterType) -> super::ApiParameterType {
+    exec_with_args(
+        "reset_learned_windows",
+        request_handler,
+        move |args: ResetLearnedWindowsRequest| {
+            sync_learned_windows(profile_db, &decky_env)
+                .and_then(|_| profile_db.reset_learned_windows(args.app_ids.as_deref()))
+                .map(|_| ResponseOk)
+                .map_err(|err| ResponseErr(StatusCode::ServerError, err))
+        },
+    )
+}
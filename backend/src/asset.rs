@@ -71,7 +71,7 @@ impl<'a> AssetManager<'a> {
                         AssetDirEntry::Dir(dir.path().to_path_buf()),
                     ),
                     include_dir::DirEntry::File(file) => entries.insert(
-                        dir.path().to_path_buf(),
+                        file.path().to_path_buf(),
                         AssetDirEntry::File(file.path().to_path_buf()),
                     ),
                 };
@@ -57,6 +57,7 @@ impl AutoStart {
                                     description: None,
                                     enabled: None,
                                     profile_override: None,
+                                    inherited_from: None,
                                     selection: action,
                                     is_visible_on_qam: false,
                                 }
@@ -9,7 +9,14 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use serde_with::DefaultOnError;
 
-use crate::decky_env::DeckyEnv;
+use crate::{
+    decky_env::DeckyEnv,
+    pipeline::action::multi_window::main_app_automatic_windowing::GamescopeOptions,
+    sys::{display_info::get_display_info, x_display::Resolution},
+};
+
+/// Resolution of the Deck's internal display
+const INTERNAL_DISPLAY_RESOLUTION: Resolution = Resolution { w: 1280, h: 800 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
@@ -27,6 +34,12 @@ impl ClientTeardownAction {
             ClientTeardownAction::MainAppAutomaticWindowing { action_id: id, .. } => id,
         }
     }
+
+    fn get_app_id(&self) -> u32 {
+        match self {
+            ClientTeardownAction::MainAppAutomaticWindowing { app_id, .. } => *app_id,
+        }
+    }
 }
 
 pub struct ClientPipelineHandler {
@@ -70,6 +83,38 @@ impl ClientPipelineHandler {
         self.state.teardown.values().cloned().collect()
     }
 
+    /// Builds the gamescope launch options for `app_id`, recording a teardown action
+    /// to restore `current_launch_options` if one isn't already pending. Returns the
+    /// launch options the client should apply.
+    pub fn apply_gamescope_launch_options(
+        &mut self,
+        app_id: u32,
+        gamescope: &GamescopeOptions,
+        output: Resolution,
+        current_launch_options: String,
+    ) -> Result<String> {
+        let launch_options = gamescope.to_launch_options(output, &current_launch_options)?;
+
+        // If a previous session wasn't torn down, the current options are already
+        // wrapped; keep the originals so they are what gets restored.
+        let has_pending_restore = self.state.teardown.values().any(|action| {
+            matches!(
+                action,
+                ClientTeardownAction::MainAppAutomaticWindowing { .. }
+            ) && action.get_app_id() == app_id
+        });
+
+        if !has_pending_restore {
+            self.add_client_teardown_action(ClientTeardownAction::MainAppAutomaticWindowing {
+                app_id,
+                action_id: uuid::Uuid::new_v4().to_string(),
+                previous_launch_options: current_launch_options,
+            })?;
+        }
+
+        Ok(launch_options)
+    }
+
     fn save_state(&self) -> Result<()> {
         let path = get_state_path(&self.decky_env);
         std::fs::write(path, serde_json::to_string_pretty(&self.state)?)?;
@@ -81,3 +126,68 @@ impl ClientPipelineHandler {
 fn get_state_path(decky_env: &DeckyEnv) -> PathBuf {
     decky_env.decky_plugin_runtime_dir.join("client_state.json")
 }
+
+/// Gets the resolution gamescope should output at; the internal display if
+/// `use_internal` is set, otherwise the largest mode of the external display.
+pub fn get_gamescope_output_resolution(use_internal: bool) -> Result<Resolution> {
+    if use_internal {
+        return Ok(INTERNAL_DISPLAY_RESOLUTION);
+    }
+
+    get_display_info()
+        .and_then(|modes| modes.into_iter().max())
+        .map(|mode| mode.resolution())
+        .context("failed to read external display modes")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::action::multi_window::main_app_automatic_windowing::GamescopeFilter;
+
+    use super::*;
+
+    #[test]
+    fn test_gamescope_restore_keeps_original_launch_options() -> Result<()> {
+        let decky_env = Arc::new(DeckyEnv::new_test("client_pipeline_gamescope"));
+        let mut handler = ClientPipelineHandler::new(decky_env);
+
+        let gamescope = GamescopeOptions::default();
+        let applied = handler.apply_gamescope_launch_options(
+            42,
+            &gamescope,
+            INTERNAL_DISPLAY_RESOLUTION,
+            "-novid".to_string(),
+        )?;
+
+        assert!(applied.starts_with("gamescope "));
+        assert!(applied.ends_with(" -- %command% -novid"));
+
+        // re-applying over already wrapped options shouldn't replace the original
+        handler.apply_gamescope_launch_options(
+            42,
+            &gamescope,
+            INTERNAL_DISPLAY_RESOLUTION,
+            applied,
+        )?;
+
+        let actions = handler.get_client_teardown_actions();
+        assert_eq!(1, actions.len());
+        assert!(matches!(
+            &actions[0],
+            ClientTeardownAction::MainAppAutomaticWindowing { previous_launch_options, .. }
+                if previous_launch_options == "-novid"
+        ));
+
+        // invalid options shouldn't record anything
+        let invalid = GamescopeOptions {
+            filter: GamescopeFilter::Fsr,
+            ..Default::default()
+        };
+        assert!(handler
+            .apply_gamescope_launch_options(7, &invalid, INTERNAL_DISPLAY_RESOLUTION, String::new())
+            .is_err());
+        assert_eq!(1, handler.get_client_teardown_actions().len());
+
+        Ok(())
+    }
+}
@@ -1,72 +1,93 @@
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
 
 use native_db::transaction::{RTransaction, RwTransaction};
 use native_db::Database;
 
+use crate::pipeline::action::multi_window::main_app_automatic_windowing::LearnedWindowMatch;
 use crate::pipeline::action_registar::PipelineActionRegistrar;
 use crate::pipeline::data::PipelineDefinition;
 use crate::pipeline::data::Template;
-use crate::pipeline::data::TemplateId;
 use crate::settings::AppId;
 use crate::settings::AppProfile;
 use crate::util::create_dir_all;
 
-use self::model::{DbAppOverride, DbAppSettings, MODELS};
+use self::backup::BackupReason;
+use self::model::DbCategoryProfile;
+use self::model::{DbAppOverride, DbAppSettings, DbLearnedWindowMatch, MODELS};
+use self::revision::{RevisionSource, DEFAULT_REVISION_RETENTION};
+use self::rules::CompiledRule;
 use self::templates::build_templates;
-use self::{migrate::migrate, model::DbCategoryProfile};
 
 use crate::settings::CategoryProfile;
 
 use crate::settings::ProfileId;
-use anyhow::Result;
+use anyhow::{Context, Result};
 
+pub mod backup;
+#[cfg(test)]
+mod benches;
+pub mod bulk;
+pub mod bundle;
 mod codec;
 mod convert;
-mod migrate;
+pub mod migrate;
 mod model;
-mod templates;
+pub mod revision;
+pub mod rules;
+pub mod templates;
 
 pub struct ProfileDb {
     db: Database<'static>,
+    db_path: PathBuf,
     templates: Vec<Template>,
+    revision_retention: AtomicUsize,
+    /// Reconstructed profiles, since reconstructing reads every action of every profile.
+    /// Cleared after each write that can change a profile.
+    profile_cache: Mutex<Option<Vec<CategoryProfile>>>,
+    /// Profile selection rules with their regexes compiled; replaced when rules are saved.
+    rules_cache: Mutex<Option<Arc<Vec<CompiledRule>>>>,
 }
 
 impl ProfileDb {
-    pub fn new(db_path: PathBuf, registrar: PipelineActionRegistrar) -> Self {
+    pub fn new(db_path: PathBuf, registrar: PipelineActionRegistrar) -> Result<Self> {
         let parent = db_path
             .parent()
-            .expect("db_path should have parent directory");
+            .context("db_path should have parent directory")?;
 
         if !parent.exists() {
-            create_dir_all(parent).expect("should be able to create db dir");
+            create_dir_all(parent).context("failed to create profile db dir")?;
         }
 
-        let mut db = native_db::Builder::new()
-            .create(&MODELS, db_path)
-            .expect("database should be instantiable");
-
-        let rw = db
-            .rw_transaction()
-            .expect("initial migration transaction should be valid");
-        migrate(&rw).expect("db migrations should succeed");
-        rw.commit().expect("migrations should commit");
-
-        db.compact().expect("db compact should succeed");
+        let db = backup::open_or_restore(&db_path)?;
         let templates = build_templates(registrar);
 
-        ProfileDb { db, templates }
+        Ok(ProfileDb {
+            db,
+            db_path,
+            templates,
+            revision_retention: AtomicUsize::new(DEFAULT_REVISION_RETENTION),
+            profile_cache: Mutex::new(None),
+            rules_cache: Mutex::new(None),
+        })
     }
 
     pub fn create_profile(&self, pipeline: PipelineDefinition) -> Result<CategoryProfile> {
         let id = ProfileId::new();
-        let profile = CategoryProfile {
+        let pipeline = pipeline.with_new_ids();
+
+        let rw = self.read_write();
+        self.record_revision(&rw, id, None, RevisionSource::CreateProfile, &pipeline)?;
+
+        CategoryProfile {
             id,
             tags: vec![],
             pipeline,
-        };
-
-        let rw = self.read_write();
-        profile.save_all(&rw)?;
+            parent: None,
+        }
+        .save_all(&rw)?;
 
         let saved = rw
             .get()
@@ -74,23 +95,41 @@ impl ProfileDb {
             .expect("inserted profile should exist");
 
         rw.commit()?;
+        self.invalidate_profiles();
 
         // Ideally, the reconstruct would happen inside the rw transaction,
         // but the db types make that more complicated than I'd like
 
         let ro = self.read_only();
 
-        let profile = saved.reconstruct(&ro)?;
-
-        Ok(profile)
+        saved.reconstruct(&ro)
     }
 
     pub fn delete_profile(&self, id: &ProfileId) -> Result<()> {
         let rw = self.read_write();
         let profile = rw.get().primary::<DbCategoryProfile>(*id)?;
         if let Some(profile) = profile {
+            self.backup(BackupReason::BeforeDelete)
+                .context("failed to back up profiles before delete")?;
+
+            // children inherit from the deleted profile's parent instead
+            let children = rw
+                .scan()
+                .primary::<DbCategoryProfile>()?
+                .all()?
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|child| child.parent == Some(*id));
+
+            for mut child in children {
+                child.parent = profile.parent;
+                rw.upsert(child)?;
+            }
+
             profile.remove_all(&rw)?;
+            revision::remove_revisions(&rw, id)?;
             rw.commit()?;
+            self.invalidate_profiles();
         }
 
         Ok(())
@@ -98,25 +137,75 @@ impl ProfileDb {
 
     pub fn get_profile(&self, id: &ProfileId) -> Result<Option<CategoryProfile>> {
         let ro = self.read_only();
-        let profile = ro.get().primary::<DbCategoryProfile>(*id)?;
 
-        profile.map(|p| p.reconstruct(&ro)).transpose()
+        ro.get()
+            .primary::<DbCategoryProfile>(*id)?
+            .map(|p| p.reconstruct(&ro))
+            .transpose()
     }
 
     pub fn set_profile(&self, profile: CategoryProfile) -> Result<()> {
+        self.save_profile(profile, RevisionSource::SetProfile)
+    }
+
+    fn save_profile(&self, profile: CategoryProfile, source: RevisionSource) -> Result<()> {
+        let id = profile.id;
+
+        let mut parents = self.with_profiles(|profiles| {
+            profiles
+                .iter()
+                .map(|p| (p.id, p.parent))
+                .collect::<HashMap<_, _>>()
+        })?;
+        parents.insert(id, profile.parent);
+        check_inheritance(&parents, id)?;
+
+        let profile = CategoryProfile {
+            pipeline: profile.pipeline.with_new_ids(),
+            ..profile
+        };
+
         let rw = self.read_write();
+        self.record_revision(&rw, id, None, source, &profile.pipeline)?;
         profile.save_all(&rw)?;
-        Ok(rw.commit()?)
+        rw.commit()?;
+        self.invalidate_profiles();
+
+        Ok(())
     }
 
     pub fn get_profiles(&self) -> Result<Vec<CategoryProfile>> {
+        self.with_profiles(|profiles| profiles.to_vec())
+    }
+
+    fn with_profiles<T>(&self, f: impl FnOnce(&[CategoryProfile]) -> T) -> Result<T> {
+        // the lock is held while loading, so a write can't be invalidated by a stale load
+        let mut cache = self
+            .profile_cache
+            .lock()
+            .expect("profile cache mutex should not be poisoned");
+
+        if cache.is_none() {
+            *cache = Some(self.load_profiles()?);
+        }
+
+        Ok(f(cache.as_deref().unwrap_or_default()))
+    }
+
+    fn invalidate_profiles(&self) {
+        *self
+            .profile_cache
+            .lock()
+            .expect("profile cache mutex should not be poisoned") = None;
+    }
+
+    fn load_profiles(&self) -> Result<Vec<CategoryProfile>> {
         let ro = self.read_only();
         let profiles = ro
             .scan()
-            .primary::<DbCategoryProfile>()
-            .expect("failed to scan category profiles")
+            .primary::<DbCategoryProfile>()?
             .all()?
-            .map(|p| p.unwrap().reconstruct(&ro))
+            .map(|p| p?.reconstruct(&ro))
             .collect::<Result<_>>()?;
         Ok(profiles)
     }
@@ -133,14 +222,36 @@ impl ProfileDb {
         profile_id: ProfileId,
         definition: PipelineDefinition,
     ) -> Result<()> {
+        self.save_app_profile_override(
+            app_id,
+            profile_id,
+            definition,
+            RevisionSource::SetAppProfileOverride,
+        )
+    }
+
+    fn save_app_profile_override(
+        &self,
+        app_id: AppId,
+        profile_id: ProfileId,
+        definition: PipelineDefinition,
+        source: RevisionSource,
+    ) -> Result<()> {
+        let definition = definition.with_new_ids();
+
         let rw = self.read_write();
+        self.record_revision(&rw, profile_id, Some(app_id.clone()), source, &definition)?;
 
-        rw.upsert(DbAppOverride {
-            id: (app_id, profile_id),
-            pipeline: definition.save_all_and_transform(&rw)?,
-        })?;
+        rw.upsert(DbAppOverride::new(
+            app_id,
+            profile_id,
+            definition.save_all_and_transform(&rw)?,
+        ))?;
 
-        Ok(rw.commit()?)
+        rw.commit()?;
+        self.invalidate_profiles();
+
+        Ok(())
     }
 
     pub fn set_app_profile_settings(
@@ -158,13 +269,61 @@ impl ProfileDb {
         Ok(rw.commit()?)
     }
 
-    // In-memory configuration (currently readonly, but should ideally be configurable)
-    pub fn get_template(&self, id: &TemplateId) -> Option<&Template> {
-        self.templates.iter().find(|t| t.id == *id)
+    pub fn get_learned_window(&self, app_id: &AppId) -> Result<Option<LearnedWindowMatch>> {
+        let ro = self.read_only();
+
+        Ok(ro
+            .get()
+            .primary::<DbLearnedWindowMatch>(app_id.clone())?
+            .map(|learned| <(AppId, LearnedWindowMatch)>::from(learned).1))
     }
 
-    pub fn get_templates(&self) -> &[Template] {
-        &self.templates
+    pub fn get_learned_windows(&self) -> Result<HashMap<AppId, LearnedWindowMatch>> {
+        let ro = self.read_only();
+        let learned = ro
+            .scan()
+            .primary::<DbLearnedWindowMatch>()?
+            .all()?
+            .map(|learned| Ok(learned?.into()))
+            .collect::<Result<_>>()?;
+
+        Ok(learned)
+    }
+
+    pub fn set_learned_windows(
+        &self,
+        learned: impl IntoIterator<Item = (AppId, LearnedWindowMatch)>,
+    ) -> Result<()> {
+        let rw = self.read_write();
+
+        for (app_id, learned) in learned {
+            rw.upsert(DbLearnedWindowMatch {
+                app_id,
+                window_matcher: learned.window_matcher,
+                classes: learned.classes,
+            })?;
+        }
+
+        Ok(rw.commit()?)
+    }
+
+    /// Forgets the learned windows for `app_ids`, or for all apps if `None`.
+    pub fn reset_learned_windows(&self, app_ids: Option<&[AppId]>) -> Result<()> {
+        let rw = self.read_write();
+
+        let existing = rw
+            .scan()
+            .primary::<DbLearnedWindowMatch>()?
+            .all()?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for learned in existing {
+            if app_ids.is_none_or(|ids| ids.contains(&learned.app_id)) {
+                rw.remove(learned)?;
+            }
+        }
+
+        Ok(rw.commit()?)
     }
 
     fn read_only(&self) -> RTransaction {
@@ -180,6 +339,25 @@ impl ProfileDb {
     }
 }
 
+/// Fails if the parent of `id` in `parents` doesn't exist, or `id` would inherit from itself.
+fn check_inheritance(parents: &HashMap<ProfileId, Option<ProfileId>>, id: ProfileId) -> Result<()> {
+    let mut seen = HashSet::from([id]);
+    let mut next = parents.get(&id).copied().flatten();
+
+    while let Some(parent) = next {
+        anyhow::ensure!(
+            seen.insert(parent),
+            "profile {id:?} would inherit from itself through {parent:?}"
+        );
+
+        next = *parents
+            .get(&parent)
+            .with_context(|| format!("Failed to find parent profile {parent:?}"))?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -191,8 +369,9 @@ mod tests {
             action_registar::PipelineActionRegistrar,
             data::{
                 PipelineActionId, PipelineActionLookup, PipelineDefinitionId, PipelineTarget,
-                TopLevelDefinition, TopLevelId,
+                RuntimeSelection, TopLevelDefinition, TopLevelId,
             },
+            executor::PipelineContext,
         },
         util::create_dir_all,
     };
@@ -211,7 +390,7 @@ mod tests {
         let parent = path.parent().unwrap();
         create_dir_all(parent).unwrap();
 
-        let db = ProfileDb::new(path.clone(), registrar.clone());
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
 
         let pipeline_action_id = PipelineActionId::new("core:citra:layout");
 
@@ -219,6 +398,7 @@ mod tests {
 
         let mut expected: CategoryProfile = CategoryProfile {
             id: ProfileId::new(),
+            parent: None,
             tags: vec!["Test".to_string()],
             pipeline: PipelineDefinition {
                 id: PipelineDefinitionId::nil(),
@@ -295,8 +475,10 @@ mod tests {
         db.delete_profile(&expected.id)?;
 
         assert!(db.get_profile(&expected.id)?.is_none());
+        assert_eq!(1, db.list_backups()?.len());
 
         std::fs::remove_file(path)?;
+        std::fs::remove_dir_all("test/out/.config/deck-ds/profile_crud_backups")?;
         Ok(())
     }
 
@@ -312,7 +494,7 @@ mod tests {
         let parent = path.parent().unwrap();
         create_dir_all(parent).unwrap();
 
-        let db = ProfileDb::new(path.clone(), registrar.clone());
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
 
         let app_id = AppId::new("appid");
         let pd_id_1 = PipelineDefinitionId::new();
@@ -411,4 +593,80 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_profile_inheritance() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let path: PathBuf = "test/out/.config/deck-ds/profile_inheritance.db".into();
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        create_dir_all(path.parent().unwrap())?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+
+        let root = PipelineActionId::new("core:citra:platform");
+        let definition = |actions| PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "Citra".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                root: root.clone(),
+                actions,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        };
+
+        let grandparent = db.create_profile(definition(registrar.make_lookup(&root)))?;
+        let mut parent = db.create_profile(definition(PipelineActionLookup::empty()))?;
+        let mut child = db.create_profile(definition(PipelineActionLookup::empty()))?;
+
+        parent.parent = Some(grandparent.id);
+        db.set_profile(parent.clone())?;
+        child.parent = Some(parent.id);
+        db.set_profile(child.clone())?;
+
+        // cycles and missing parents are rejected
+        let mut cyclic = db.get_profile(&grandparent.id)?.unwrap();
+        cyclic.parent = Some(child.id);
+        assert!(db.set_profile(cyclic.clone()).is_err());
+        cyclic.parent = Some(cyclic.id);
+        assert!(db.set_profile(cyclic.clone()).is_err());
+        cyclic.parent = Some(ProfileId::new());
+        assert!(db.set_profile(cyclic).is_err());
+        assert_eq!(None, db.get_profile(&grandparent.id)?.unwrap().parent);
+
+        // unspecified actions fall through to the grandparent
+        let profiles = db.get_profiles()?;
+        let ctx = &mut PipelineContext::new(None, Default::default(), Default::default());
+        let pipeline = child
+            .pipeline
+            .reify(&profiles, child.parent, ctx, &registrar)?;
+
+        let RuntimeSelection::AllOf(actions) = &pipeline.targets[&PipelineTarget::Desktop] else {
+            panic!("expected toplevel to be AllOf");
+        };
+        let platform = actions
+            .iter()
+            .find(|a| a.id.no_variant() == root.no_variant())
+            .expect("platform should be reified");
+
+        assert_eq!(Some(grandparent.id), platform.inherited_from);
+
+        // deleting a parent reparents its children
+        db.delete_profile(&parent.id)?;
+        assert_eq!(
+            Some(grandparent.id),
+            db.get_profile(&child.id)?.unwrap().parent
+        );
+
+        drop(db);
+        std::fs::remove_file(&path)?;
+        std::fs::remove_dir_all("test/out/.config/deck-ds/profile_inheritance_backups")?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,496 @@
+//! Rotating snapshots of the profile db, so a corrupted db or a failed migration
+//! can be recovered instead of taking the plugin down.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use native_db::Database;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::util::create_dir_all;
+
+use super::{migrate::migrate, model::MODELS, ProfileDb};
+
+/// The number of snapshots kept; older snapshots are removed as new ones are taken, except
+/// for the newest startup and scheduled snapshots.
+pub const MAX_BACKUPS: usize = 10;
+/// How often the plugin server snapshots the profile db.
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60 * 6);
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Display, EnumString,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum BackupReason {
+    /// Taken before migrations are run on startup
+    Startup,
+    Scheduled,
+    BeforeDelete,
+    /// Taken of the current db before a staged restore replaces it
+    BeforeRestore,
+    BeforeImport,
+    /// Taken before app overrides are reset to their profiles
+    BeforeReset,
+    /// Taken before a profile or app override revision is restored
+    BeforeRevisionRestore,
+    Manual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileDbBackup {
+    pub id: String,
+    /// Creation time, in milliseconds since the unix epoch
+    pub created: u64,
+    pub reason: BackupReason,
+}
+
+impl ProfileDbBackup {
+    fn parse(id: &str) -> Option<Self> {
+        let (created, reason) = id.split_once('-')?;
+
+        Some(Self {
+            id: id.to_string(),
+            created: created.parse().ok()?,
+            reason: reason.parse().ok()?,
+        })
+    }
+
+    fn path(&self, db_path: &Path) -> PathBuf {
+        backup_dir(db_path).join(format!("{}.db", self.id))
+    }
+}
+
+impl ProfileDb {
+    /// Snapshots the open profile db.
+    pub fn backup(&self, reason: BackupReason) -> Result<ProfileDbBackup> {
+        let (backup, path) = new_backup(&self.db_path, reason)?;
+
+        self.db
+            .snapshot(&MODELS, &path)
+            .with_context(|| format!("failed to snapshot profile db to {path:?}"))?;
+
+        log::info!("created profile db backup {}", backup.id);
+        rotate_backups(&self.db_path)?;
+
+        Ok(backup)
+    }
+
+    pub fn list_backups(&self) -> Result<Vec<ProfileDbBackup>> {
+        list_backups(&self.db_path)
+    }
+
+    /// Stages the backup `id` to replace the profile db the next time it is opened.
+    pub fn stage_restore(&self, id: &str) -> Result<()> {
+        stage_restore(&self.db_path, id)
+    }
+}
+
+/// Lists the backups of the db at `db_path`, newest first.
+pub fn list_backups(db_path: &Path) -> Result<Vec<ProfileDbBackup>> {
+    let dir = backup_dir(db_path);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut backups = std::fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "db" {
+                return None;
+            }
+
+            ProfileDbBackup::parse(path.file_stem()?.to_str()?)
+        })
+        .collect::<Vec<_>>();
+
+    backups.sort_by(|a, b| b.created.cmp(&a.created));
+
+    Ok(backups)
+}
+
+/// Stages the backup `id` to replace the db at `db_path` the next time it is opened.
+pub fn stage_restore(db_path: &Path, id: &str) -> Result<()> {
+    let backup = list_backups(db_path)?
+        .into_iter()
+        .find(|backup| backup.id == id)
+        .with_context(|| format!("profile db backup {id} not found"))?;
+
+    std::fs::copy(backup.path(db_path), staged_restore_path(db_path))?;
+    log::info!("staged profile db backup {id} for restore");
+
+    Ok(())
+}
+
+/// Opens the db at `db_path`, applying any staged restore and running migrations.
+///
+/// If the db can't be opened, it is set aside and the newest backup that opens is
+/// restored instead. If no backup is usable, an empty db is created.
+pub(super) fn open_or_restore(db_path: &Path) -> Result<Database<'static>> {
+    // a db held by another process would fail to open, but isn't broken
+    ensure_not_in_use(db_path)?;
+
+    if let Err(err) = apply_staged_restore(db_path) {
+        log::error!("failed to apply staged profile db restore: {err:#?}");
+    }
+
+    // Taken before migrations run, but only rotated in once the db is known to open,
+    // so a broken db can't push good backups out.
+    let startup_backup = db_path
+        .exists()
+        .then(|| copy_to_backup(db_path, BackupReason::Startup))
+        .and_then(|res| {
+            res.inspect_err(|err| log::warn!("failed to back up profile db: {err:#?}"))
+                .ok()
+        });
+
+    let err = match open(db_path) {
+        Ok(db) => {
+            if let Err(err) = rotate_backups(db_path) {
+                log::warn!("failed to rotate profile db backups: {err:#?}");
+            }
+
+            return Ok(db);
+        }
+        Err(err) => err,
+    };
+
+    log::error!("failed to open profile db; falling back to latest backup: {err:#?}");
+
+    // the startup backup is a copy of the broken db
+    if let Some(backup) = startup_backup {
+        let _ = std::fs::remove_file(backup.path(db_path));
+    }
+
+    let failed_path = suffixed_path(db_path, "failed");
+    if let Err(err) = std::fs::rename(db_path, &failed_path) {
+        log::error!("failed to move broken profile db to {failed_path:?}: {err}");
+    }
+
+    for backup in list_backups(db_path).unwrap_or_default() {
+        let restored = std::fs::copy(backup.path(db_path), db_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| open(db_path));
+
+        match restored {
+            Ok(db) => {
+                log::warn!("restored profile db from backup {}", backup.id);
+                return Ok(db);
+            }
+            Err(err) => {
+                log::error!(
+                    "failed to restore profile db backup {}: {err:#?}",
+                    backup.id
+                );
+                let _ = std::fs::remove_file(db_path);
+            }
+        }
+    }
+
+    log::error!("no usable profile db backup found; starting with an empty profile db");
+
+    open(db_path).context("failed to create an empty profile db")
+}
+
+fn ensure_not_in_use(db_path: &Path) -> Result<()> {
+    if db_path.exists() {
+        File::open(db_path)?
+            .try_lock()
+            .with_context(|| format!("profile db {db_path:?} is locked"))?;
+    }
+
+    Ok(())
+}
+
+fn open(db_path: &Path) -> Result<Database<'static>> {
+    let mut db = native_db::Builder::new().create(&MODELS, db_path)?;
+
+    let rw = db.rw_transaction()?;
+    let report = migrate(&rw).context("db migrations should succeed")?;
+    rw.commit()?;
+
+    log::debug!("profile db at schema v{}", report.to_version);
+
+    db.compact()?;
+
+    Ok(db)
+}
+
+/// Replaces the db with a staged restore, if one exists. The db must not be open.
+fn apply_staged_restore(db_path: &Path) -> Result<()> {
+    let staged = staged_restore_path(db_path);
+    if !staged.exists() {
+        return Ok(());
+    }
+
+    if db_path.exists() {
+        backup_file(db_path, BackupReason::BeforeRestore)?;
+    }
+
+    std::fs::rename(staged, db_path)?;
+    log::info!("restored staged profile db backup");
+
+    Ok(())
+}
+
+/// Backs up the db by copying its file. The db must not be open.
+fn backup_file(db_path: &Path, reason: BackupReason) -> Result<ProfileDbBackup> {
+    let backup = copy_to_backup(db_path, reason)?;
+    rotate_backups(db_path)?;
+
+    Ok(backup)
+}
+
+/// Copies the db file to a new backup, without rotating out old backups.
+fn copy_to_backup(db_path: &Path, reason: BackupReason) -> Result<ProfileDbBackup> {
+    let (backup, path) = new_backup(db_path, reason)?;
+    std::fs::copy(db_path, &path)?;
+
+    Ok(backup)
+}
+
+fn new_backup(db_path: &Path, reason: BackupReason) -> Result<(ProfileDbBackup, PathBuf)> {
+    let dir = backup_dir(db_path);
+    if !dir.exists() {
+        create_dir_all(&dir)?;
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+
+    // keep backups taken in quick succession distinct and ordered
+    let created = list_backups(db_path)?
+        .first()
+        .map_or(now, |newest| now.max(newest.created + 1));
+
+    let backup = ProfileDbBackup {
+        id: format!("{created}-{reason}"),
+        created,
+        reason,
+    };
+    let path = backup.path(db_path);
+
+    Ok((backup, path))
+}
+
+/// Removes the oldest backups past [MAX_BACKUPS]. The newest startup and scheduled backups
+/// are always kept, so a run of destructive edits can't push out every periodic snapshot.
+fn rotate_backups(db_path: &Path) -> Result<()> {
+    let mut kept = 0;
+    let mut periodic = vec![];
+
+    for backup in list_backups(db_path)? {
+        let newest_periodic = matches!(
+            backup.reason,
+            BackupReason::Startup | BackupReason::Scheduled
+        ) && !periodic.contains(&backup.reason);
+
+        if newest_periodic {
+            periodic.push(backup.reason);
+        } else if kept >= MAX_BACKUPS {
+            std::fs::remove_file(backup.path(db_path))?;
+            continue;
+        }
+
+        kept += 1;
+    }
+
+    Ok(())
+}
+
+fn backup_dir(db_path: &Path) -> PathBuf {
+    let stem = db_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("profiles");
+
+    db_path.with_file_name(format!("{stem}_backups"))
+}
+
+fn staged_restore_path(db_path: &Path) -> PathBuf {
+    suffixed_path(db_path, "restore")
+}
+
+fn suffixed_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+
+    db_path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pipeline::{
+            action_registar::PipelineActionRegistrar,
+            data::{
+                PipelineActionId, PipelineDefinition, PipelineDefinitionId, TopLevelDefinition,
+                TopLevelId,
+            },
+        },
+        settings::ProfileId,
+    };
+
+    use super::*;
+
+    fn setup_db(name: &str) -> Result<(PathBuf, PipelineActionRegistrar)> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let path: PathBuf = format!("test/out/.config/deck-ds/{name}.db").into();
+
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        Ok((path, registrar))
+    }
+
+    fn cleanup(path: &Path) -> Result<()> {
+        for file in [
+            path.to_path_buf(),
+            staged_restore_path(path),
+            suffixed_path(path, "failed"),
+        ] {
+            if file.exists() {
+                std::fs::remove_file(file)?;
+            }
+        }
+
+        let dir = backup_dir(path);
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)?;
+        }
+
+        Ok(())
+    }
+
+    fn create_profile(db: &ProfileDb, registrar: &PipelineActionRegistrar) -> Result<ProfileId> {
+        let root = PipelineActionId::new("core:citra:platform");
+
+        let profile = db.create_profile(PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "Backup".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        })?;
+
+        Ok(profile.id)
+    }
+
+    #[test]
+    fn test_backup_rotation_and_staged_restore() -> Result<()> {
+        let (path, registrar) = setup_db("backup_restore")?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+        let profile_id = create_profile(&db, &registrar)?;
+
+        let first = db.backup(BackupReason::Manual)?;
+        for _ in 0..MAX_BACKUPS {
+            db.backup(BackupReason::Scheduled)?;
+        }
+
+        let backups = db.list_backups()?;
+        assert_eq!(MAX_BACKUPS, backups.len());
+        assert!(!backups.contains(&first));
+
+        let latest = backups[0].clone();
+
+        db.delete_profile(&profile_id)?;
+        assert!(db.get_profile(&profile_id)?.is_none());
+        assert_eq!(BackupReason::BeforeDelete, db.list_backups()?[0].reason);
+
+        db.stage_restore(&latest.id)?;
+        assert!(db.stage_restore("0-manual").is_err());
+
+        drop(db);
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+        assert!(db.get_profile(&profile_id)?.is_some());
+        assert!(!staged_restore_path(&path).exists());
+        assert!(db
+            .list_backups()?
+            .iter()
+            .any(|backup| backup.reason == BackupReason::BeforeRestore));
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest_periodic_backups() -> Result<()> {
+        let (path, registrar) = setup_db("backup_keep_periodic")?;
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+        let scheduled = db.backup(BackupReason::Scheduled)?;
+        for _ in 0..MAX_BACKUPS {
+            db.backup(BackupReason::BeforeDelete)?;
+        }
+
+        let backups = db.list_backups()?;
+        assert_eq!(MAX_BACKUPS + 1, backups.len());
+        assert!(backups.contains(&scheduled));
+        assert_eq!(
+            MAX_BACKUPS,
+            backups
+                .iter()
+                .filter(|backup| backup.reason == BackupReason::BeforeDelete)
+                .count()
+        );
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_fallback_to_backup_on_broken_db() -> Result<()> {
+        let (path, registrar) = setup_db("backup_fallback")?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+        let profile_id = create_profile(&db, &registrar)?;
+        let good = db.backup(BackupReason::Manual)?;
+
+        drop(db);
+
+        std::fs::write(&path, "not a database")?;
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+        assert!(db.get_profile(&profile_id)?.is_some());
+        assert!(suffixed_path(&path, "failed").exists());
+        assert_eq!(vec![good], db.list_backups()?);
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_broken_db_keeps_full_backup_ring() -> Result<()> {
+        let (path, registrar) = setup_db("backup_full_ring")?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+        let profile_id = create_profile(&db, &registrar)?;
+        for _ in 0..MAX_BACKUPS {
+            db.backup(BackupReason::Manual)?;
+        }
+        let good = db.list_backups()?;
+
+        drop(db);
+
+        std::fs::write(&path, "not a database")?;
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+        assert!(db.get_profile(&profile_id)?.is_some());
+        assert_eq!(good, db.list_backups()?);
+
+        drop(db);
+        cleanup(&path)
+    }
+}
@@ -0,0 +1,112 @@
+//! Lookup benchmarks for the profile db; run with `cargo bench`. App profile lookups
+//! should take the same time regardless of how many apps have overrides.
+
+extern crate test;
+
+use std::path::PathBuf;
+
+use test::Bencher;
+
+use crate::{
+    pipeline::{
+        action_registar::PipelineActionRegistrar,
+        data::{
+            PipelineActionId, PipelineDefinition, PipelineDefinitionId, TopLevelDefinition,
+            TopLevelId,
+        },
+    },
+    settings::{AppId, ProfileId},
+    util::create_dir_all,
+};
+
+use super::ProfileDb;
+
+struct BenchDb {
+    db: ProfileDb,
+    path: PathBuf,
+    profile_id: ProfileId,
+}
+
+impl Drop for BenchDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn definition(registrar: &PipelineActionRegistrar) -> PipelineDefinition {
+    let root = PipelineActionId::new("core:citra:platform");
+
+    PipelineDefinition {
+        id: PipelineDefinitionId::nil(),
+        name: "Bench".into(),
+        primary_target_override: None,
+        platform: TopLevelDefinition {
+            id: TopLevelId::nil(),
+            actions: registrar.make_lookup(&root),
+            root,
+        },
+        toplevel: vec![],
+        desktop_controller_layout_hack: Default::default(),
+    }
+}
+
+/// Creates a db with one profile, overridden for `apps` apps.
+fn setup(name: &str, apps: usize) -> BenchDb {
+    let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+    let path: PathBuf = format!("test/out/.config/deck-ds/{name}.db").into();
+    if path.exists() {
+        std::fs::remove_file(&path).unwrap();
+    }
+    create_dir_all(path.parent().unwrap()).unwrap();
+
+    let db = ProfileDb::new(path.clone(), registrar.clone()).unwrap();
+    db.set_revision_retention(0);
+
+    let profile_id = db.create_profile(definition(&registrar)).unwrap().id;
+
+    for app in 0..apps {
+        db.set_app_profile_override(
+            AppId::new(&app.to_string()),
+            profile_id,
+            definition(&registrar),
+        )
+        .unwrap();
+    }
+
+    BenchDb {
+        db,
+        path,
+        profile_id,
+    }
+}
+
+fn bench_app_profile(b: &mut Bencher, name: &str, apps: usize) {
+    let bench = setup(name, apps);
+    let app_id = AppId::new("0");
+
+    b.iter(|| {
+        let app = bench.db.get_app_profile(&app_id).unwrap();
+        assert!(app.overrides.contains_key(&bench.profile_id));
+    });
+}
+
+#[bench]
+fn bench_get_app_profile_10_overrides(b: &mut Bencher) {
+    bench_app_profile(b, "bench_app_profile_10", 10);
+}
+
+#[bench]
+fn bench_get_app_profile_250_overrides(b: &mut Bencher) {
+    bench_app_profile(b, "bench_app_profile_250", 250);
+}
+
+#[bench]
+fn bench_get_profiles_cached(b: &mut Bencher) {
+    let bench = setup("bench_get_profiles", 0);
+
+    b.iter(|| {
+        let profiles = bench.db.get_profiles().unwrap();
+        assert_eq!(1, profiles.len());
+    });
+}
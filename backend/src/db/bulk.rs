@@ -0,0 +1,360 @@
+//! Batch edits of app overrides and app settings. Each batch is applied in a single
+//! transaction, so a failure for any app leaves every app unchanged.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use native_db::transaction::RwTransaction;
+
+use crate::{
+    pipeline::{
+        action::{ActionId, ErasedPipelineAction},
+        data::{ConfigSelection, PipelineDefinition, PipelineDefinitionId},
+    },
+    settings::{AppId, ProfileId},
+};
+
+use super::{
+    backup::BackupReason,
+    model::{DbAppOverride, DbAppOverrideKey, DbAppSettings, DbCategoryProfile},
+    revision::RevisionSource,
+    ProfileDb,
+};
+
+impl ProfileDb {
+    /// Sets the override of `profile_id` for every app in `app_ids` to a copy of `definition`,
+    /// replacing any existing overrides.
+    pub fn set_app_profile_overrides(
+        &self,
+        app_ids: &[AppId],
+        profile_id: ProfileId,
+        definition: PipelineDefinition,
+    ) -> Result<()> {
+        let app_ids = dedup(app_ids);
+
+        let rw = self.read_write();
+        ensure_profile(&rw, profile_id)?;
+
+        for app_id in app_ids {
+            let definition = detached(definition.clone()).with_new_ids();
+
+            remove_override(&rw, &app_id, profile_id)?;
+            self.record_revision(
+                &rw,
+                profile_id,
+                Some(app_id.clone()),
+                RevisionSource::BulkAppOverrides,
+                &definition,
+            )?;
+            rw.upsert(DbAppOverride::new(
+                app_id,
+                profile_id,
+                definition.save_all_and_transform(&rw)?,
+            ))?;
+        }
+
+        rw.commit()?;
+        self.invalidate_profiles();
+
+        Ok(())
+    }
+
+    /// Copies the override of `profile_id` from `source` to every app in `app_ids`.
+    pub fn copy_app_profile_override(
+        &self,
+        source: &AppId,
+        profile_id: ProfileId,
+        app_ids: &[AppId],
+    ) -> Result<()> {
+        let definition = self
+            .get_app_profile(source)?
+            .overrides
+            .remove(&profile_id)
+            .with_context(|| format!("app {source:?} has no override for {profile_id:?}"))?;
+
+        let app_ids = app_ids
+            .iter()
+            .filter(|id| *id != source)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        self.set_app_profile_overrides(&app_ids, profile_id, definition)
+    }
+
+    /// Removes the overrides of `profile_id`, or of every profile if `None`, for every app
+    /// in `app_ids`, so the apps use the category profile again. Returns the apps that had
+    /// overrides removed.
+    pub fn reset_app_profile_overrides(
+        &self,
+        app_ids: &[AppId],
+        profile_id: Option<ProfileId>,
+    ) -> Result<Vec<AppId>> {
+        let rw = self.read_write();
+
+        let mut removed = vec![];
+
+        for app_id in dedup(app_ids) {
+            let overrides = rw
+                .scan()
+                .secondary::<DbAppOverride>(DbAppOverrideKey::app_id)?
+                .range(app_id.clone()..=app_id.clone())?
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|o| profile_id.is_none_or(|id| o.profile_id == id))
+                .collect::<Vec<_>>();
+
+            if !overrides.is_empty() {
+                removed.push((app_id, overrides));
+            }
+        }
+
+        if removed.is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.backup(BackupReason::BeforeReset)
+            .context("failed to back up profiles before resetting app overrides")?;
+
+        let mut reset = vec![];
+
+        for (app_id, overrides) in removed {
+            for o in overrides {
+                o.remove_all(&rw)?;
+            }
+
+            reset.push(app_id);
+        }
+
+        rw.commit()?;
+        self.invalidate_profiles();
+
+        Ok(reset)
+    }
+
+    /// Sets the default profile of every app in `app_ids`.
+    pub fn set_apps_profile_settings(
+        &self,
+        app_ids: &[AppId],
+        default_profile: Option<ProfileId>,
+    ) -> Result<()> {
+        let rw = self.read_write();
+
+        if let Some(profile_id) = default_profile {
+            ensure_profile(&rw, profile_id)?;
+        }
+
+        for app_id in dedup(app_ids) {
+            rw.upsert(DbAppSettings {
+                app_id,
+                default_profile,
+            })?;
+        }
+
+        Ok(rw.commit()?)
+    }
+}
+
+fn dedup(app_ids: &[AppId]) -> Vec<AppId> {
+    let mut seen = HashSet::new();
+
+    app_ids
+        .iter()
+        .filter(|id| seen.insert(*id))
+        .cloned()
+        .collect()
+}
+
+fn ensure_profile(rw: &RwTransaction, profile_id: ProfileId) -> Result<()> {
+    rw.get()
+        .primary::<DbCategoryProfile>(profile_id)?
+        .with_context(|| format!("Failed to find profile for {profile_id:?}"))?;
+
+    Ok(())
+}
+
+/// Removes the override, along with its actions. Returns `true` if there was one to remove.
+fn remove_override(rw: &RwTransaction, app_id: &AppId, profile_id: ProfileId) -> Result<bool> {
+    let existing = rw
+        .get()
+        .primary::<DbAppOverride>((app_id.clone(), profile_id))?;
+
+    match existing {
+        Some(existing) => {
+            existing.remove_all(rw)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Clears the db ids of `definition`, so saving it creates a new copy of its actions.
+/// Toplevel ids are kept, since overrides are matched to the profile by them.
+pub(super) fn detached(mut definition: PipelineDefinition) -> PipelineDefinition {
+    definition.id = PipelineDefinitionId::nil();
+
+    for tl in definition
+        .toplevel
+        .iter_mut()
+        .chain([&mut definition.platform])
+    {
+        for settings in tl.actions.actions.values_mut() {
+            if let ConfigSelection::Action(action) = &settings.selection {
+                settings.selection =
+                    ConfigSelection::Action(action.cloned_with_id(ActionId::nil()));
+            }
+        }
+    }
+
+    definition
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pipeline::{
+            action_registar::PipelineActionRegistrar,
+            data::{PipelineActionId, TopLevelDefinition, TopLevelId},
+        },
+        util::create_dir_all,
+    };
+
+    use super::*;
+
+    struct TestDb {
+        db: ProfileDb,
+        path: PathBuf,
+    }
+
+    impl Drop for TestDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+            let _ = std::fs::remove_dir_all(self.path.with_file_name(format!(
+                "{}_backups",
+                self.path.file_stem().unwrap().to_string_lossy()
+            )));
+        }
+    }
+
+    fn setup_db(name: &str, registrar: &PipelineActionRegistrar) -> TestDb {
+        let path = PathBuf::from(format!("test/out/.config/deck-ds/{name}.db"));
+        if path.exists() {
+            std::fs::remove_file(&path).unwrap();
+        }
+        create_dir_all(path.parent().unwrap()).unwrap();
+
+        TestDb {
+            db: ProfileDb::new(path.clone(), registrar.clone()).unwrap(),
+            path,
+        }
+    }
+
+    fn make_definition(registrar: &PipelineActionRegistrar) -> PipelineDefinition {
+        let root = PipelineActionId::new("core:citra:platform");
+
+        PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "Citra".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_bulk_overrides() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let test = setup_db("bulk_overrides", &registrar);
+        let db = &test.db;
+
+        let profile_id = db.create_profile(make_definition(&registrar))?.id;
+        let apps = ["1", "2", "3"].map(AppId::new);
+
+        db.set_app_profile_overrides(&apps[..2], profile_id, make_definition(&registrar))?;
+
+        let first = db.get_app_profile(&apps[0])?.overrides[&profile_id].clone();
+        let second = db.get_app_profile(&apps[1])?.overrides[&profile_id].clone();
+
+        // each app has its own copy
+        assert_ne!(first.id, second.id);
+
+        db.copy_app_profile_override(&apps[0], profile_id, &apps)?;
+
+        let third = db.get_app_profile(&apps[2])?.overrides[&profile_id].clone();
+        assert_ne!(first.id, third.id);
+        assert_eq!(first.platform.root, third.platform.root);
+        assert_eq!(
+            first.platform.actions.actions.len(),
+            third.platform.actions.actions.len()
+        );
+        assert_eq!(
+            first.id,
+            db.get_app_profile(&apps[0])?.overrides[&profile_id].id,
+            "source override should be unchanged"
+        );
+
+        let before_reset = || -> Result<usize> {
+            Ok(db
+                .list_backups()?
+                .iter()
+                .filter(|b| b.reason == BackupReason::BeforeReset)
+                .count())
+        };
+
+        // nothing to reset, so nothing is backed up
+        assert!(db
+            .reset_app_profile_overrides(&[AppId::new("4")], None)?
+            .is_empty());
+        assert_eq!(0, before_reset()?);
+
+        let reset = db.reset_app_profile_overrides(&[apps[0].clone(), AppId::new("4")], None)?;
+        assert_eq!(vec![apps[0].clone()], reset);
+        assert_eq!(1, before_reset()?);
+        assert!(db.get_app_profile(&apps[0])?.overrides.is_empty());
+        assert!(db
+            .get_app_profile(&apps[1])?
+            .overrides
+            .contains_key(&profile_id));
+
+        db.set_apps_profile_settings(&apps, Some(profile_id))?;
+        for app in apps.iter() {
+            assert_eq!(Some(profile_id), db.get_app_profile(app)?.default_profile);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_overrides_all_or_nothing() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let test = setup_db("bulk_overrides_atomic", &registrar);
+        let db = &test.db;
+
+        let apps = ["1", "2"].map(AppId::new);
+        let missing = ProfileId::new();
+
+        assert!(db
+            .set_app_profile_overrides(&apps, missing, make_definition(&registrar))
+            .is_err());
+        assert!(db.set_apps_profile_settings(&apps, Some(missing)).is_err());
+        assert!(db
+            .copy_app_profile_override(&apps[0], missing, &apps[1..])
+            .is_err());
+
+        for app in apps.iter() {
+            let app = db.get_app_profile(app)?;
+            assert!(app.overrides.is_empty());
+            assert_eq!(None, app.default_profile);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,518 @@
+//! Portable JSON bundles of profiles and their app overrides, for backing up and
+//! sharing profiles outside of `profiles.db`.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    pipeline::{
+        action::{ActionId, ErasedPipelineAction},
+        action_registar::PipelineActionRegistrar,
+        data::{
+            ConfigSelection, PipelineActionId, PipelineDefinition, PipelineDefinitionId,
+            PipelineTarget,
+        },
+    },
+    settings::{AppId, AppProfile, CategoryProfile, ProfileId},
+};
+
+use super::{
+    backup::BackupReason,
+    check_inheritance,
+    model::{DbAppOverride, DbAppOverrideKey, DbAppSettings, DbCategoryProfile},
+    revision::RevisionSource,
+    ProfileDb,
+};
+
+/// Current bundle format version. Bundles with a newer version are rejected on import.
+pub const PROFILE_BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileBundle {
+    pub version: u32,
+    pub profiles: Vec<CategoryProfile>,
+    /// App overrides for the bundled profiles. Overrides for other profiles are not included.
+    pub app_profiles: Vec<AppProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct DroppedAction {
+    /// The profile, by its id in the bundle
+    pub profile_id: ProfileId,
+    /// Set if the action was dropped from an app override, rather than the profile itself
+    pub app_id: Option<AppId>,
+    pub action_id: PipelineActionId,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ProfileImportReport {
+    /// Imported profiles, mapped from their id in the bundle to their id in the db
+    pub imported: HashMap<ProfileId, ProfileId>,
+    /// Profiles given a new id because their id was already in use
+    pub remapped: Vec<ProfileId>,
+    /// Profiles skipped because their platform is unknown
+    pub skipped: Vec<ProfileId>,
+    /// Unknown actions removed from imported profiles and overrides
+    pub dropped_actions: Vec<DroppedAction>,
+    /// Number of app overrides imported
+    pub app_overrides: usize,
+}
+
+impl ProfileDb {
+    /// Exports the profiles in `ids`, or all profiles if `None`, along with their app overrides.
+    pub fn export_profiles(&self, ids: Option<&[ProfileId]>) -> Result<ProfileBundle> {
+        let profiles = self
+            .get_profiles()?
+            .into_iter()
+            .filter(|p| ids.is_none_or(|ids| ids.contains(&p.id)))
+            .collect::<Vec<_>>();
+
+        for id in ids.unwrap_or_default() {
+            if !profiles.iter().any(|p| p.id == *id) {
+                anyhow::bail!("Failed to find profile for {id:?}");
+            }
+        }
+
+        let exported = profiles.iter().map(|p| p.id).collect::<HashSet<_>>();
+
+        let ro = self.read_only();
+
+        let mut app_ids = exported
+            .iter()
+            .map(|id| {
+                Ok(ro
+                    .scan()
+                    .secondary::<DbAppOverride>(DbAppOverrideKey::profile_id)?
+                    .range(*id..=*id)?
+                    .filter_map(|app| app.ok())
+                    .map(|app| app.app_id)
+                    .collect::<Vec<_>>())
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        app_ids.sort_by(|a, b| a.raw().cmp(b.raw()));
+
+        let app_profiles = app_ids
+            .iter()
+            .map(|app_id| {
+                let mut app = AppProfile::load(app_id, &ro)?;
+                app.overrides.retain(|id, _| exported.contains(id));
+                app.default_profile = app.default_profile.filter(|id| exported.contains(id));
+
+                Ok(app)
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            profiles,
+            app_profiles,
+        })
+    }
+
+    /// Imports a [ProfileBundle] in a single transaction. Profiles keep their ids
+    /// unless already in use, and actions unknown to `registrar` are dropped.
+    pub fn import_profiles(
+        &self,
+        bundle: ProfileBundle,
+        registrar: &PipelineActionRegistrar,
+    ) -> Result<ProfileImportReport> {
+        if bundle.version > PROFILE_BUNDLE_VERSION {
+            anyhow::bail!(
+                "profile bundle version {} is newer than supported version {PROFILE_BUNDLE_VERSION}",
+                bundle.version
+            );
+        }
+
+        self.backup(BackupReason::BeforeImport)
+            .context("failed to back up profiles before import")?;
+
+        let mut report = ProfileImportReport::default();
+        let rw = self.read_write();
+
+        let existing = rw
+            .scan()
+            .primary()?
+            .all()?
+            .filter_map(|p: Result<DbCategoryProfile, _>| p.ok())
+            .map(|p| (p.id, p.parent))
+            .collect::<HashMap<_, _>>();
+
+        let (profiles, skipped): (Vec<_>, Vec<_>) = bundle
+            .profiles
+            .into_iter()
+            .partition(|p| is_known_action(registrar, &p.pipeline.platform.root));
+
+        report.skipped = skipped.iter().map(|p| p.id).collect();
+
+        for profile in profiles.iter() {
+            let id = if profile.id == ProfileId::nil() || existing.contains_key(&profile.id) {
+                report.remapped.push(profile.id);
+                ProfileId::new()
+            } else {
+                profile.id
+            };
+
+            report.imported.insert(profile.id, id);
+        }
+
+        let imported = &report.imported;
+        let map_profile = |id: ProfileId| {
+            imported
+                .get(&id)
+                .copied()
+                .or_else(|| existing.contains_key(&id).then_some(id))
+        };
+
+        let mut dropped_actions = vec![];
+        let mut prepare = |definition, profile_id, app_id: Option<&AppId>| {
+            let (definition, dropped) = prepare_definition(definition, registrar, map_profile)?;
+
+            dropped_actions.extend(dropped.into_iter().map(|action_id| DroppedAction {
+                profile_id,
+                app_id: app_id.cloned(),
+                action_id,
+            }));
+
+            Some(definition)
+        };
+
+        // parents outside the bundle or db are dropped; cycles fail the import
+        let mut parents = existing.clone();
+        for profile in profiles.iter() {
+            parents.insert(imported[&profile.id], profile.parent.and_then(map_profile));
+        }
+        for id in imported.values() {
+            check_inheritance(&parents, *id)?;
+        }
+
+        let mut app_overrides = 0;
+
+        for profile in profiles {
+            let Some(pipeline) = prepare(profile.pipeline, profile.id, None) else {
+                continue;
+            };
+
+            let id = imported[&profile.id];
+            let pipeline = pipeline.with_new_ids();
+
+            self.record_revision(&rw, id, None, RevisionSource::ImportProfiles, &pipeline)?;
+
+            CategoryProfile {
+                id,
+                tags: profile.tags,
+                pipeline,
+                parent: parents[&id],
+            }
+            .save_all(&rw)?;
+        }
+
+        for app in bundle.app_profiles {
+            for (bundle_id, definition) in app.overrides {
+                let Some(profile_id) = imported.get(&bundle_id).copied() else {
+                    continue;
+                };
+
+                if let Some(pipeline) = prepare(definition, bundle_id, Some(&app.id)) {
+                    let pipeline = pipeline.with_new_ids();
+
+                    self.record_revision(
+                        &rw,
+                        profile_id,
+                        Some(app.id.clone()),
+                        RevisionSource::ImportProfiles,
+                        &pipeline,
+                    )?;
+                    rw.upsert(DbAppOverride::new(
+                        app.id.clone(),
+                        profile_id,
+                        pipeline.save_all_and_transform(&rw)?,
+                    ))?;
+                    app_overrides += 1;
+                }
+            }
+
+            let default_profile = app
+                .default_profile
+                .and_then(|id| imported.get(&id).copied());
+
+            // don't replace a default the user already chose
+            let has_default = rw
+                .get()
+                .primary::<DbAppSettings>(app.id.clone())?
+                .is_some_and(|settings| settings.default_profile.is_some());
+
+            if default_profile.is_some() && !has_default {
+                rw.upsert(DbAppSettings {
+                    app_id: app.id,
+                    default_profile,
+                })?;
+            }
+        }
+
+        rw.commit()?;
+        self.invalidate_profiles();
+
+        report.dropped_actions = dropped_actions;
+        report.app_overrides = app_overrides;
+
+        Ok(report)
+    }
+}
+pub(super) fn is_known_action(registrar: &PipelineActionRegistrar, id: &PipelineActionId) -> bool {
+    registrar.all().contains_key(id)
+        || PipelineTarget::iter().any(|t| registrar.get(id, t).is_some())
+}
+
+/// Prepares an imported definition to be saved: clears db ids so new ones are assigned,
+/// remaps profile references with `map_profile`, and drops unknown actions. Returns
+/// `None` if the platform is unknown, along with the ids of any dropped actions.
+fn prepare_definition(
+    mut definition: PipelineDefinition,
+    registrar: &PipelineActionRegistrar,
+    map_profile: impl Fn(ProfileId) -> Option<ProfileId> + Copy,
+) -> Option<(PipelineDefinition, Vec<PipelineActionId>)> {
+    if !is_known_action(registrar, &definition.platform.root) {
+        return None;
+    }
+
+    let mut dropped = vec![];
+
+    definition.id = PipelineDefinitionId::nil();
+    definition.toplevel.retain(|tl| {
+        let known = is_known_action(registrar, &tl.root);
+        if !known {
+            dropped.push(tl.root.clone());
+        }
+        known
+    });
+
+    for tl in definition
+        .toplevel
+        .iter_mut()
+        .chain([&mut definition.platform])
+    {
+        tl.actions.actions.retain(|id, _| {
+            let known = is_known_action(registrar, id);
+            if !known {
+                dropped.push(id.clone());
+            }
+            known
+        });
+
+        for settings in tl.actions.actions.values_mut() {
+            settings.profile_override = settings.profile_override.and_then(map_profile);
+
+            if let ConfigSelection::Action(action) = &settings.selection {
+                settings.selection =
+                    ConfigSelection::Action(action.cloned_with_id(ActionId::nil()));
+            }
+        }
+    }
+
+    dropped.sort_by(|a, b| a.raw().cmp(b.raw()));
+
+    Some((definition, dropped))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pipeline::data::{PipelineActionLookup, TopLevelDefinition, TopLevelId},
+        util::create_dir_all,
+    };
+
+    use super::*;
+
+    fn setup_db(name: &str, registrar: &PipelineActionRegistrar) -> Result<ProfileDb> {
+        let path = PathBuf::from(format!("test/out/.config/deck-ds/{name}.db"));
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+
+        create_dir_all(path.parent().unwrap())?;
+
+        ProfileDb::new(path, registrar.clone())
+    }
+
+    fn make_definition(registrar: &PipelineActionRegistrar, platform: &str) -> PipelineDefinition {
+        let root = PipelineActionId::new(platform);
+
+        PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: platform.to_string(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_export_import_round_trip() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let source = setup_db("bundle_export", &registrar)?;
+
+        let citra = source.create_profile(make_definition(&registrar, "core:citra:platform"))?;
+        let melonds =
+            source.create_profile(make_definition(&registrar, "core:melonds:platform"))?;
+
+        let app_id = AppId::new("bundle_app");
+        source.set_app_profile_override(
+            app_id.clone(),
+            citra.id,
+            PipelineDefinition {
+                id: PipelineDefinitionId::nil(),
+                ..citra.pipeline.clone()
+            },
+        )?;
+        source.set_app_profile_settings(app_id.clone(), Some(citra.id))?;
+
+        let bundle = source.export_profiles(Some(&[citra.id]))?;
+
+        assert_eq!(1, bundle.profiles.len());
+        assert_eq!(1, bundle.app_profiles.len());
+        assert_eq!(Some(citra.id), bundle.app_profiles[0].default_profile);
+        assert!(source.export_profiles(Some(&[ProfileId::new()])).is_err());
+
+        // round trip through json, as a user would
+        let bundle: ProfileBundle = serde_json::from_str(&serde_json::to_string(&bundle)?)?;
+
+        // a fresh db keeps the original ids
+        let target = setup_db("bundle_import", &registrar)?;
+        let report = target.import_profiles(bundle.clone(), &registrar)?;
+        assert_eq!(BackupReason::BeforeImport, target.list_backups()?[0].reason);
+
+        assert_eq!(HashMap::from([(citra.id, citra.id)]), report.imported);
+        assert!(report.remapped.is_empty());
+        assert!(report.dropped_actions.is_empty());
+        assert_eq!(1, report.app_overrides);
+
+        let imported = target
+            .get_profile(&citra.id)?
+            .expect("profile should import");
+        assert_eq!(citra.pipeline.name, imported.pipeline.name);
+        assert_eq!(
+            citra.pipeline.platform.actions.actions.len(),
+            imported.pipeline.platform.actions.actions.len()
+        );
+        assert_eq!(
+            Some(citra.id),
+            target.get_app_profile(&app_id)?.default_profile
+        );
+
+        // importing into the source db conflicts, so the profile is remapped
+        let report = source.import_profiles(bundle, &registrar)?;
+        let remapped = report.imported[&citra.id];
+
+        assert_ne!(citra.id, remapped);
+        assert_eq!(vec![citra.id], report.remapped);
+        assert_eq!(3, source.get_profiles()?.len());
+
+        let app = source.get_app_profile(&app_id)?;
+        assert!(app.overrides.contains_key(&remapped));
+        assert_eq!(Some(citra.id), app.default_profile);
+
+        // the original profiles are untouched
+        assert_eq!(
+            Some(melonds.pipeline.name),
+            source.get_profile(&melonds.id)?.map(|p| p.pipeline.name)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_drops_unknown_actions() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let db = setup_db("bundle_unknown", &registrar)?;
+
+        let mut known = make_definition(&registrar, "core:citra:platform");
+        let unknown_action = PipelineActionId::new("plugin:unknown:action");
+        let unknown_toplevel = PipelineActionId::new("plugin:toplevel:unknown");
+
+        let settings = known
+            .platform
+            .actions
+            .actions
+            .values()
+            .next()
+            .cloned()
+            .expect("citra platform should have actions");
+        known
+            .platform
+            .actions
+            .actions
+            .insert(unknown_action.clone(), settings);
+        known.toplevel.push(TopLevelDefinition {
+            id: TopLevelId::new(),
+            root: unknown_toplevel.clone(),
+            actions: PipelineActionLookup::empty(),
+        });
+
+        let known_profile = CategoryProfile {
+            id: ProfileId::new(),
+            tags: vec![],
+            pipeline: known,
+            parent: None,
+        };
+        let unknown_profile = CategoryProfile {
+            id: ProfileId::new(),
+            tags: vec![],
+            pipeline: make_definition(&registrar, "plugin:unknown:platform"),
+            parent: None,
+        };
+
+        let report = db.import_profiles(
+            ProfileBundle {
+                version: PROFILE_BUNDLE_VERSION,
+                profiles: vec![known_profile.clone(), unknown_profile.clone()],
+                app_profiles: vec![],
+            },
+            &registrar,
+        )?;
+
+        assert_eq!(vec![unknown_profile.id], report.skipped);
+        assert_eq!(
+            vec![
+                DroppedAction {
+                    profile_id: known_profile.id,
+                    app_id: None,
+                    action_id: unknown_toplevel,
+                },
+                DroppedAction {
+                    profile_id: known_profile.id,
+                    app_id: None,
+                    action_id: unknown_action,
+                },
+            ],
+            report.dropped_actions
+        );
+        assert_eq!(1, db.get_profiles()?.len());
+
+        let newer = ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION + 1,
+            profiles: vec![],
+            app_profiles: vec![],
+        };
+        assert!(db.import_profiles(newer, &registrar).is_err());
+
+        Ok(())
+    }
+}
@@ -6,9 +6,16 @@ use anyhow::Result;
 use native_db::transaction::{RTransaction, RwTransaction};
 
 use crate::{
-    db::model::{DbAppOverride, DbCategoryProfile, DbPipelineActionSettings, DbPipelineDefinition},
-    pipeline::data::{
-        PipelineActionLookup, PipelineDefinition, PipelineDefinitionId, TopLevelDefinition,
+    db::model::{
+        DbAppOverride, DbAppOverrideKey, DbCategoryProfile, DbPipelineActionSettings,
+        DbPipelineDefinition, DbProfileRevision, DbTemplate,
+    },
+    pipeline::{
+        action::{ActionId, ErasedPipelineAction},
+        data::{
+            ConfigSelection, PipelineActionLookup, PipelineDefinition, PipelineDefinitionId,
+            TopLevelDefinition, TopLevelId,
+        },
     },
     settings::{AppId, AppProfile, CategoryProfile, ProfileId},
 };
@@ -33,6 +40,7 @@ impl CategoryProfile {
             id: self.id,
             tags: self.tags.clone(),
             pipeline: self.pipeline.save_all_and_transform(rw)?,
+            parent: self.parent,
         };
 
         rw.upsert(db_profile)?;
@@ -43,14 +51,12 @@ impl CategoryProfile {
 
 impl AppProfile {
     pub fn load(app_id: &AppId, ro: &RTransaction) -> Result<Self> {
-        // TODO::figure out if/how native_db supports multiple primary keys, so this can be done more efficiently
         let mut overrides = HashMap::from_iter(
             ro.scan()
-                .primary()?
-                .all()?
-                .filter_map(|app: Result<DbAppOverride, _>| app.ok()) // TODO::log/error on failure
-                .filter(|app| app.id.0 == *app_id)
-                .map(|app| Ok((app.id.1, app.pipeline.transform(ro)?)))
+                .secondary::<DbAppOverride>(DbAppOverrideKey::app_id)?
+                .range(app_id.clone()..=app_id.clone())?
+                .filter_map(|app| app.ok()) // TODO::log/error on failure
+                .map(|app| Ok((app.profile_id, app.pipeline.transform(ro)?)))
                 .collect::<Result<Vec<_>>>()?,
         );
 
@@ -131,6 +137,30 @@ impl AppProfile {
 }
 
 impl PipelineDefinition {
+    /// Sets new ids on the definition, its toplevels, and its actions where they aren't set,
+    /// so the definition matches what `save_all_and_transform` stores.
+    pub fn with_new_ids(mut self) -> Self {
+        if self.id == PipelineDefinitionId::nil() {
+            self.id = PipelineDefinitionId::new();
+        }
+
+        for toplevel in std::iter::once(&mut self.platform).chain(self.toplevel.iter_mut()) {
+            if toplevel.id == TopLevelId::nil() {
+                toplevel.id = TopLevelId::new();
+            }
+
+            for settings in toplevel.actions.actions.values_mut() {
+                if let ConfigSelection::Action(action) = &mut settings.selection {
+                    if action.get_id() == ActionId::nil() {
+                        *action = action.cloned_with_id(ActionId::new());
+                    }
+                }
+            }
+        }
+
+        self
+    }
+
     /// Saves the [PipelineDefinition]. Because it may set new ids internally, `save_all_and_transform` cosumes self.
     pub fn save_all_and_transform(self, rw: &RwTransaction) -> Result<DbPipelineDefinition> {
         let id = if self.id == PipelineDefinitionId::nil() {
@@ -196,21 +226,7 @@ impl PipelineDefinition {
 impl DbCategoryProfile {
     pub fn remove_all(self, rw: &RwTransaction) -> Result<()> {
         self.remove_app_overrides(rw)?;
-
-        let actions = Some(&self.pipeline.platform)
-            .into_iter()
-            .chain(self.pipeline.toplevel.iter());
-
-        for tl in actions {
-            for id in tl.actions.iter() {
-                let action: Option<DbPipelineActionSettings> =
-                    rw.get().primary((self.pipeline.id, tl.id, id.clone()))?;
-                if let Some(action) = action {
-                    action.selection.remove_all(rw)?;
-                    rw.remove(action)?;
-                }
-            }
-        }
+        self.pipeline.remove_actions(rw)?;
 
         rw.remove_blind(self)
     }
@@ -220,8 +236,52 @@ impl DbCategoryProfile {
             id: self.id,
             tags: self.tags.clone(),
             pipeline: self.pipeline.transform(ro)?,
+            parent: self.parent,
         };
 
         Ok(profile)
     }
 }
+
+impl DbAppOverride {
+    pub fn remove_all(self, rw: &RwTransaction) -> Result<()> {
+        self.pipeline.remove_actions(rw)?;
+
+        rw.remove_blind(self)
+    }
+}
+
+impl DbProfileRevision {
+    pub fn remove_all(self, rw: &RwTransaction) -> Result<()> {
+        self.pipeline.remove_actions(rw)?;
+
+        rw.remove_blind(self)
+    }
+}
+
+impl DbTemplate {
+    pub fn remove_all(self, rw: &RwTransaction) -> Result<()> {
+        self.pipeline.remove_actions(rw)?;
+
+        rw.remove_blind(self)
+    }
+}
+
+impl DbPipelineDefinition {
+    fn remove_actions(&self, rw: &RwTransaction) -> Result<()> {
+        let actions = Some(&self.platform).into_iter().chain(self.toplevel.iter());
+
+        for tl in actions {
+            for id in tl.actions.iter() {
+                let action: Option<DbPipelineActionSettings> =
+                    rw.get().primary((self.id, tl.id, id.clone()))?;
+                if let Some(action) = action {
+                    action.selection.remove_all(rw)?;
+                    rw.remove(action)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
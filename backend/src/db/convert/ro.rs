@@ -8,9 +8,10 @@ use crate::{
     db::model::{
         DbAction, DbCemuAudio, DbCemuLayout, DbCitraLayout, DbConfigSelection,
         DbDesktopControllerLayoutHack, DbDesktopSessionHandler, DbDisplayConfig,
-        DbLaunchSecondaryApp, DbLaunchSecondaryAppPreset, DbLime3dsLayout,
-        DbMainAppAutomaticWindowing, DbMelonDSLayout, DbMultiWindow, DbPipelineActionSettings,
-        DbPipelineDefinition, DbSourceFile, DbTopLevelDefinition, DbTouchConfig, DbVirtualScreen,
+        DbLaunchSecondaryApp, DbLaunchSecondaryAppGroup, DbLaunchSecondaryAppPreset,
+        DbLaunchSecondaryFlatpakApp, DbLime3dsLayout, DbMainAppAutomaticWindowing, DbMelonDSLayout,
+        DbMultiWindow, DbPipelineActionSettings, DbPipelineDefinition, DbSourceFile,
+        DbTopLevelDefinition, DbTouchConfig, DbVirtualScreen,
     },
     pipeline::{
         action::{Action, ActionType},
@@ -68,9 +69,17 @@ impl DbAction {
                 action.map(|a| Action::SourceFile(a.into()))
             }
             ActionType::LaunchSecondaryFlatpakApp => {
-                let action = ro.get().primary::<DbLaunchSecondaryApp>(id)?;
+                let action = ro.get().primary::<DbLaunchSecondaryFlatpakApp>(id)?;
                 action.map(|a| Action::LaunchSecondaryFlatpakApp(a.into()))
             }
+            ActionType::LaunchSecondaryApp => {
+                let action = ro.get().primary::<DbLaunchSecondaryApp>(id)?;
+                action.map(|a| Action::LaunchSecondaryApp(a.into()))
+            }
+            ActionType::LaunchSecondaryAppGroup => {
+                let action = ro.get().primary::<DbLaunchSecondaryAppGroup>(id)?;
+                action.map(|a| Action::LaunchSecondaryAppGroup(a.into()))
+            }
             ActionType::LaunchSecondaryAppPreset => {
                 let action = ro.get().primary::<DbLaunchSecondaryAppPreset>(id)?;
                 action.map(|a| Action::LaunchSecondaryAppPreset(a.into()))
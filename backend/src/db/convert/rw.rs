@@ -6,11 +6,13 @@ use crate::{
     db::{
         convert::ext::RwExt,
         model::{
-            DbAction, DbAppOverride, DbCategoryProfile, DbCemuAudio, DbCemuLayout, DbCitraLayout,
-            DbConfigSelection, DbDesktopControllerLayoutHack, DbDesktopSessionHandler,
-            DbDisplayConfig, DbLaunchSecondaryApp, DbLaunchSecondaryAppPreset, DbLime3dsLayout,
-            DbMainAppAutomaticWindowing, DbMelonDSLayout, DbMultiWindow, DbPipelineActionSettings,
-            DbSourceFile, DbTopLevelDefinition, DbTouchConfig, DbVirtualScreen,
+            DbAction, DbAppOverride, DbAppOverrideKey, DbCategoryProfile, DbCemuAudio,
+            DbCemuLayout, DbCitraLayout, DbConfigSelection, DbDesktopControllerLayoutHack,
+            DbDesktopSessionHandler, DbDisplayConfig, DbLaunchSecondaryApp,
+            DbLaunchSecondaryAppGroup, DbLaunchSecondaryAppPreset, DbLaunchSecondaryFlatpakApp,
+            DbLime3dsLayout, DbMainAppAutomaticWindowing, DbMelonDSLayout, DbMultiWindow,
+            DbPipelineActionSettings, DbSourceFile, DbTopLevelDefinition, DbTouchConfig,
+            DbVirtualScreen,
         },
     },
     pipeline::{
@@ -68,8 +70,14 @@ impl Action {
                 rw.upsert::<DbSourceFile>(action.into())?;
             }
             Action::LaunchSecondaryFlatpakApp(action) => {
+                rw.upsert::<DbLaunchSecondaryFlatpakApp>(action.into())?;
+            }
+            Action::LaunchSecondaryApp(action) => {
                 rw.upsert::<DbLaunchSecondaryApp>(action.into())?;
             }
+            Action::LaunchSecondaryAppGroup(action) => {
+                rw.upsert::<DbLaunchSecondaryAppGroup>(action.into())?;
+            }
             Action::LaunchSecondaryAppPreset(action) => {
                 rw.upsert::<DbLaunchSecondaryAppPreset>(action.into())?;
             }
@@ -179,10 +187,9 @@ impl DbCategoryProfile {
     pub fn remove_app_overrides(&self, rw: &RwTransaction) -> Result<()> {
         let overrides = rw
             .scan()
-            .primary()?
-            .all()?
-            .filter_map(|app: Result<DbAppOverride, _>| app.ok()) // TODO::log/error on failure
-            .filter(|app| app.id.1 == self.id)
+            .secondary::<DbAppOverride>(DbAppOverrideKey::profile_id)?
+            .range(self.id..=self.id)?
+            .filter_map(|app| app.ok()) // TODO::log/error on failure
             .collect::<Vec<_>>();
 
         for o in overrides {
@@ -246,9 +253,17 @@ impl DbAction {
                 action.map(|a| rw.remove_blind(a))
             }
             ActionType::LaunchSecondaryFlatpakApp => {
+                let action = rw.get().primary::<DbLaunchSecondaryFlatpakApp>(id)?;
+                action.map(|a| rw.remove_blind(a))
+            }
+            ActionType::LaunchSecondaryApp => {
                 let action = rw.get().primary::<DbLaunchSecondaryApp>(id)?;
                 action.map(|a| rw.remove_blind(a))
             }
+            ActionType::LaunchSecondaryAppGroup => {
+                let action = rw.get().primary::<DbLaunchSecondaryAppGroup>(id)?;
+                action.map(|a| rw.remove_blind(a))
+            }
             ActionType::LaunchSecondaryAppPreset => {
                 let action = rw.get().primary::<DbLaunchSecondaryAppPreset>(id)?;
                 action.map(|a| rw.remove_blind(a))
@@ -1,10 +1,536 @@
 /// Contains migrations between model versions
-use anyhow::Result;
+use std::path::Path;
+
+use anyhow::{Context, Result};
 use native_db::transaction::RwTransaction;
+use serde::Serialize;
+
+use crate::pipeline::data::PipelineDefinition;
+
+use super::{
+    bulk::detached,
+    model::{v1, v2, DbSchemaVersion, MODELS},
+};
+
+/// The schema version of the current models. Databases without a recorded
+/// schema version predate versioning, and are at version 1.
+pub const SCHEMA_VERSION: u32 = 9;
+
+const SCHEMA_VERSION_KEY: u8 = 0;
+
+/// A single migration, upgrading the db from `version - 1` to `version`.
+struct MigrationStep {
+    version: u32,
+    description: &'static str,
+    run: fn(&RwTransaction) -> Result<()>,
+}
+
+/// All migration steps, in the order they are applied.
+const MIGRATIONS: &[MigrationStep] = &[
+    MigrationStep {
+        version: 2,
+        description: "add melonDS options to multi-window actions",
+        run: migrate_v2,
+    },
+    MigrationStep {
+        version: 3,
+        description: "add exit policies to secondary app presets",
+        run: migrate_v3,
+    },
+    MigrationStep {
+        version: 4,
+        description: "index app overrides by app and profile",
+        run: migrate_v4,
+    },
+    MigrationStep {
+        version: 5,
+        description: "store profile parents",
+        run: migrate_v5,
+    },
+    MigrationStep {
+        version: 6,
+        description: "add exit policies to secondary flatpak apps",
+        run: migrate_v6,
+    },
+    MigrationStep {
+        version: 7,
+        description: "store profile rules as typed fields",
+        run: migrate_v7,
+    },
+    MigrationStep {
+        version: 8,
+        description: "save revision pipelines to the action tables",
+        run: migrate_v8,
+    },
+    MigrationStep {
+        version: 9,
+        description: "save user template pipelines to the action tables",
+        run: migrate_v9,
+    },
+];
+
+fn migrate_v2(rw: &RwTransaction) -> Result<()> {
+    Ok(rw.migrate::<v2::DbMultiWindow>()?)
+}
+
+fn migrate_v3(rw: &RwTransaction) -> Result<()> {
+    Ok(rw.migrate::<v2::DbLaunchSecondaryAppPreset>()?)
+}
+
+fn migrate_v4(rw: &RwTransaction) -> Result<()> {
+    Ok(rw.migrate::<v2::DbAppOverride>()?)
+}
+
+fn migrate_v5(rw: &RwTransaction) -> Result<()> {
+    Ok(rw.migrate::<v2::DbCategoryProfile>()?)
+}
+
+fn migrate_v6(rw: &RwTransaction) -> Result<()> {
+    Ok(rw.migrate::<v2::DbLaunchSecondaryFlatpakApp>()?)
+}
+
+fn migrate_v7(rw: &RwTransaction) -> Result<()> {
+    Ok(rw.migrate::<v2::DbProfileRules>()?)
+}
+
+fn migrate_v8(rw: &RwTransaction) -> Result<()> {
+    let revisions = rw
+        .scan()
+        .primary::<v1::DbProfileRevision>()?
+        .all()?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for revision in revisions {
+        match serde_json::from_str::<PipelineDefinition>(&revision.pipeline) {
+            Ok(pipeline) => rw.insert(v2::DbProfileRevision {
+                id: revision.id,
+                profile_id: revision.profile_id,
+                app_id: revision.app_id.clone(),
+                created: revision.created,
+                source: revision.source.clone(),
+                pipeline: detached(pipeline)
+                    .with_new_ids()
+                    .save_all_and_transform(rw)?,
+            })?,
+            Err(err) => log::warn!("dropping unreadable revision {:?}: {err}", revision.id),
+        }
+
+        rw.remove(revision)?;
+    }
+
+    Ok(())
+}
+
+fn migrate_v9(rw: &RwTransaction) -> Result<()> {
+    let templates = rw
+        .scan()
+        .primary::<v1::DbTemplate>()?
+        .all()?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for template in templates {
+        match serde_json::from_str::<PipelineDefinition>(&template.pipeline) {
+            Ok(pipeline) => rw.insert(v2::DbTemplate {
+                id: template.id,
+                tags: template.tags.clone(),
+                description: template.description.clone(),
+                pipeline: detached(pipeline)
+                    .with_new_ids()
+                    .save_all_and_transform(rw)?,
+            })?,
+            Err(err) => log::warn!("dropping unreadable template {:?}: {err}", template.id),
+        }
+
+        rw.remove(template)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// The applied steps, in order
+    pub steps: Vec<String>,
+}
 
 /// Runs migrations using the provided `RwTransaction`.
 /// Committing the migration is handled externally.
-pub fn migrate(_rw: &RwTransaction) -> Result<()> {
-    // no migrations right now
-    Ok(())
+pub fn migrate(rw: &RwTransaction) -> Result<MigrationReport> {
+    let from_version = rw
+        .get()
+        .primary::<DbSchemaVersion>(SCHEMA_VERSION_KEY)?
+        .map_or(1, |v| v.version);
+
+    anyhow::ensure!(
+        from_version <= SCHEMA_VERSION,
+        "profile db schema v{from_version} is newer than the supported v{SCHEMA_VERSION}"
+    );
+
+    let mut steps = vec![];
+
+    for step in MIGRATIONS.iter().filter(|step| step.version > from_version) {
+        log::info!(
+            "migrating profile db to v{}: {}",
+            step.version,
+            step.description
+        );
+
+        (step.run)(rw).with_context(|| format!("migration to v{} failed", step.version))?;
+        steps.push(format!("v{}: {}", step.version, step.description));
+    }
+
+    rw.upsert(DbSchemaVersion {
+        id: SCHEMA_VERSION_KEY,
+        version: SCHEMA_VERSION,
+    })?;
+
+    if !steps.is_empty() {
+        log::info!("migrated profile db from v{from_version} to v{SCHEMA_VERSION}");
+    }
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: SCHEMA_VERSION,
+        steps,
+    })
+}
+
+/// Runs migrations on the db at `db_path` without committing them. The db must not be open.
+pub fn dry_run(db_path: &Path) -> Result<MigrationReport> {
+    anyhow::ensure!(db_path.exists(), "no profile db at {db_path:?}");
+
+    let db = native_db::Builder::new().create(&MODELS, db_path)?;
+    let rw = db.rw_transaction()?;
+
+    // the transaction is aborted when dropped
+    migrate(&rw)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+
+    use crate::{
+        db::{
+            revision::{RevisionId, RevisionSource},
+            rules::{ProfileRule, ProfileRuleId, RuleCondition, PROFILE_RULES_KEY},
+            templates::build_templates,
+            ProfileDb,
+        },
+        pipeline::{
+            action_registar::PipelineActionRegistrar,
+            data::{
+                PipelineActionId, PipelineDefinitionId, TemplateId, TemplateSource,
+                TopLevelDefinition, TopLevelId,
+            },
+        },
+        settings::{AppId, ProfileId},
+        util::create_dir_all,
+    };
+
+    use super::*;
+
+    const FIXTURE_DIR: &str = "test/assets/db/v1";
+
+    /// Copies the v1 fixture db, written by the last release without schema versioning,
+    /// so tests don't modify it. Regenerate it with `test/assets/db/v1/generate.sh`.
+    fn copy_v1_db(name: &str) -> Result<PathBuf> {
+        let path: PathBuf = format!("test/out/.config/deck-ds/{name}.db").into();
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        std::fs::copy(format!("{FIXTURE_DIR}/profiles.db"), &path)
+            .context("v1 fixture db should exist; run test/assets/db/v1/generate.sh")?;
+
+        Ok(path)
+    }
+
+    /// The profiles and app profiles the fixture db was written with, as JSON.
+    fn read_expected() -> Result<Value> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(format!(
+            "{FIXTURE_DIR}/profiles.json"
+        ))?)?)
+    }
+
+    fn cleanup(path: &Path) -> Result<()> {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        let backups = path.with_file_name(format!(
+            "{}_backups",
+            path.file_stem().unwrap().to_string_lossy()
+        ));
+        if backups.exists() {
+            std::fs::remove_dir_all(backups)?;
+        }
+
+        Ok(())
+    }
+
+    /// Asserts every value in `expected` is unchanged in `actual`. Fields added since v1
+    /// are only in `actual`, and are ignored.
+    fn assert_json_subset(expected: &Value, actual: &Value, path: &str) {
+        match (expected, actual) {
+            (Value::Object(expected), Value::Object(actual)) => {
+                for (key, value) in expected {
+                    let path = format!("{path}.{key}");
+                    let actual = actual
+                        .get(key)
+                        .unwrap_or_else(|| panic!("{path} should be kept"));
+
+                    assert_json_subset(value, actual, &path);
+                }
+            }
+            (Value::Array(expected), Value::Array(actual)) => {
+                assert_eq!(expected.len(), actual.len(), "{path} length changed");
+
+                for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    assert_json_subset(expected, actual, &format!("{path}[{i}]"));
+                }
+            }
+            _ => assert_eq!(expected, actual, "{path} changed"),
+        }
+    }
+
+    #[test]
+    fn test_migration_steps_ordered() {
+        let versions = MIGRATIONS
+            .iter()
+            .map(|step| step.version)
+            .collect::<Vec<_>>();
+        let expected = (2..=SCHEMA_VERSION).collect::<Vec<_>>();
+
+        assert_eq!(expected, versions);
+    }
+
+    #[test]
+    fn test_v1_db_upgrade_is_lossless() -> Result<()> {
+        let path = copy_v1_db("migrate_v1")?;
+        let expected = read_expected()?;
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+
+        let version = db
+            .read_only()
+            .get()
+            .primary::<DbSchemaVersion>(SCHEMA_VERSION_KEY)?
+            .map(|v| v.version);
+        assert_eq!(Some(SCHEMA_VERSION), version);
+
+        let expected_profiles = expected["profiles"]
+            .as_array()
+            .context("fixture should list profiles")?;
+        assert!(!expected_profiles.is_empty());
+
+        for expected in expected_profiles {
+            let id = serde_json::from_value(expected["id"].clone())?;
+            let profile = db
+                .get_profile(&id)?
+                .with_context(|| format!("profile {id:?} should be migrated"))?;
+
+            assert_eq!(None, profile.parent);
+            assert_json_subset(expected, &serde_json::to_value(profile)?, "profile");
+        }
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_v1_app_overrides_are_indexed() -> Result<()> {
+        let path = copy_v1_db("migrate_v1_overrides")?;
+        let expected = read_expected()?;
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+
+        let expected_apps = expected["apps"]
+            .as_array()
+            .context("fixture should list app profiles")?;
+        assert!(!expected_apps.is_empty());
+
+        for expected in expected_apps {
+            let app_id: AppId = serde_json::from_value(expected["id"].clone())?;
+            let app = db.get_app_profile(&app_id)?;
+
+            assert_json_subset(expected, &serde_json::to_value(app)?, "app");
+        }
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_v6_profile_rules_are_typed() -> Result<()> {
+        let path: PathBuf = "test/out/.config/deck-ds/migrate_v6_rules.db".into();
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        let rules = vec![ProfileRule {
+            id: ProfileRuleId::new(),
+            name: "Pokemon".into(),
+            profile_id: ProfileId::new(),
+            conditions: vec![RuleCondition::TitleRegex("^Pokemon".into())],
+        }];
+
+        {
+            let db = native_db::Builder::new().create(&MODELS, &path)?;
+            let rw = db.rw_transaction()?;
+            rw.insert(DbSchemaVersion {
+                id: SCHEMA_VERSION_KEY,
+                version: 6,
+            })?;
+            rw.insert(v1::DbProfileRules {
+                id: PROFILE_RULES_KEY,
+                rules: serde_json::to_string(&rules)?,
+            })?;
+            rw.commit()?;
+        }
+
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let db = ProfileDb::new(path.clone(), registrar)?;
+        assert_eq!(rules, db.get_profile_rules()?);
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_v7_revisions_are_saved_to_action_tables() -> Result<()> {
+        let path: PathBuf = "test/out/.config/deck-ds/migrate_v7_revisions.db".into();
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let profile_id = ProfileId::new();
+        let root = PipelineActionId::new("core:citra:platform");
+        let pipeline = PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "Revision".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        }
+        .with_new_ids();
+
+        let revision = |created, pipeline| v1::DbProfileRevision {
+            id: RevisionId::new(),
+            profile_id,
+            app_id: None,
+            created,
+            source: RevisionSource::SetProfile.to_string(),
+            pipeline,
+        };
+
+        {
+            let db = native_db::Builder::new().create(&MODELS, &path)?;
+            let rw = db.rw_transaction()?;
+            rw.insert(DbSchemaVersion {
+                id: SCHEMA_VERSION_KEY,
+                version: 7,
+            })?;
+            rw.insert(revision(1, serde_json::to_string(&pipeline)?))?;
+            rw.insert(revision(2, "not a pipeline".into()))?;
+            rw.commit()?;
+        }
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+
+        // the unreadable revision is dropped
+        let revisions = db.list_revisions(profile_id, None)?;
+        assert_eq!(1, revisions.len());
+        assert_eq!(RevisionSource::SetProfile, revisions[0].source);
+        assert_eq!("Revision", revisions[0].pipeline.name);
+        assert_eq!(
+            pipeline.platform.actions.actions.len(),
+            revisions[0].pipeline.platform.actions.actions.len()
+        );
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_v8_templates_are_saved_to_action_tables() -> Result<()> {
+        let path: PathBuf = "test/out/.config/deck-ds/migrate_v8_templates.db".into();
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let builtin = build_templates(registrar.clone());
+
+        let template = |pipeline| v1::DbTemplate {
+            id: TemplateId::new(),
+            tags: vec!["Custom".into()],
+            description: None,
+            pipeline,
+        };
+        let readable = template(serde_json::to_string(&builtin[0].pipeline)?);
+        let readable_id = readable.id;
+
+        {
+            let db = native_db::Builder::new().create(&MODELS, &path)?;
+            let rw = db.rw_transaction()?;
+            rw.insert(DbSchemaVersion {
+                id: SCHEMA_VERSION_KEY,
+                version: 8,
+            })?;
+            rw.insert(readable)?;
+            rw.insert(template("not a pipeline".into()))?;
+            rw.commit()?;
+        }
+
+        let db = ProfileDb::new(path.clone(), registrar)?;
+
+        // the unreadable template is dropped, and the rest are still listed
+        let templates = db.get_templates()?;
+        assert_eq!(builtin.len() + 1, templates.len());
+
+        let migrated = db
+            .get_template(&readable_id)?
+            .context("readable template should be migrated")?;
+        assert_eq!(TemplateSource::User, migrated.source);
+        assert_eq!(builtin[0].pipeline.name, migrated.pipeline.name);
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_dry_run_does_not_commit() -> Result<()> {
+        let path = copy_v1_db("migrate_dry_run")?;
+
+        let expected = MigrationReport {
+            from_version: 1,
+            to_version: SCHEMA_VERSION,
+            steps: vec![
+                "v2: add melonDS options to multi-window actions".into(),
+                "v3: add exit policies to secondary app presets".into(),
+                "v4: index app overrides by app and profile".into(),
+                "v5: store profile parents".into(),
+                "v6: add exit policies to secondary flatpak apps".into(),
+                "v7: store profile rules as typed fields".into(),
+                "v8: save revision pipelines to the action tables".into(),
+                "v9: save user template pipelines to the action tables".into(),
+            ],
+        };
+
+        assert_eq!(expected, dry_run(&path)?);
+        // nothing was committed, so the same steps run again
+        assert_eq!(expected, dry_run(&path)?);
+
+        cleanup(&path)
+    }
 }
@@ -4,18 +4,26 @@ use strum::IntoEnumIterator;
 
 use crate::pipeline::action::ActionType;
 
-mod v1;
+pub(super) mod v1;
+pub(super) mod v2;
 
 // Core
 
-pub type DbCategoryProfile = v1::DbCategoryProfile;
-pub type DbAppOverride = v1::DbAppOverride;
+pub type DbCategoryProfile = v2::DbCategoryProfile;
+pub type DbAppOverride = v2::DbAppOverride;
+pub type DbAppOverrideKey = v2::DbAppOverrideKey;
 pub type DbAppSettings = v1::DbAppSettings;
 pub type DbPipelineDefinition = v1::DbPipelineDefinition;
 pub type DbTopLevelDefinition = v1::DbTopLevelDefinition;
 pub type DbConfigSelection = v1::DbConfigSelection;
 pub type DbAction = v1::DbAction;
 pub type DbPipelineActionSettings = v1::DbPipelineActionSettings;
+pub type DbLearnedWindowMatch = v1::DbLearnedWindowMatch;
+pub type DbSchemaVersion = v1::DbSchemaVersion;
+pub type DbProfileRevision = v2::DbProfileRevision;
+pub type DbProfileRevisionKey = v2::DbProfileRevisionKey;
+pub type DbProfileRules = v2::DbProfileRules;
+pub type DbTemplate = v2::DbTemplate;
 // pub type DbBtnChord = v1::DbBtnChord;
 
 // Action
@@ -26,13 +34,15 @@ pub type DbCitraLayout = v1::DbCitraLayout;
 pub type DbLime3dsLayout = v1::DbLime3dsLayout;
 pub type DbMelonDSLayout = v1::DbMelonDSLayout;
 pub type DbDesktopSessionHandler = v1::DbDesktopSessionHandler;
-pub type DbMultiWindow = v1::DbMultiWindow;
+pub type DbMultiWindow = v2::DbMultiWindow;
 pub type DbSourceFile = v1::DbSourceFile;
 pub type DbVirtualScreen = v1::DbVirtualScreen;
 pub type DbDisplayConfig = v1::DbDisplayConfig;
 pub type DbTouchConfig = v1::DbTouchConfig;
-pub type DbLaunchSecondaryApp = v1::DbLaunchSecondaryFlatpakApp;
-pub type DbLaunchSecondaryAppPreset = v1::DbLaunchSecondaryAppPreset;
+pub type DbLaunchSecondaryFlatpakApp = v2::DbLaunchSecondaryFlatpakApp;
+pub type DbLaunchSecondaryApp = v1::DbLaunchSecondaryApp;
+pub type DbLaunchSecondaryAppGroup = v1::DbLaunchSecondaryAppGroup;
+pub type DbLaunchSecondaryAppPreset = v2::DbLaunchSecondaryAppPreset;
 pub type DbMainAppAutomaticWindowing = v1::DbMainAppAutomaticWindowing;
 pub type DbDesktopControllerLayoutHack = v1::DbDesktopControllerLayoutHack;
 
@@ -57,6 +67,21 @@ pub static MODELS: Lazy<native_db::Models> = Lazy::new(|| {
         models
             .define::<v1::DbPipelineActionSettings>()
             .expect("failed to define DbPipelineActionSettings v1");
+        models
+            .define::<v1::DbLearnedWindowMatch>()
+            .expect("failed to define DbLearnedWindowMatch v1");
+        models
+            .define::<v1::DbSchemaVersion>()
+            .expect("failed to define DbSchemaVersion v1");
+        models
+            .define::<v1::DbProfileRevision>()
+            .expect("failed to define DbProfileRevision v1");
+        models
+            .define::<v1::DbProfileRules>()
+            .expect("failed to define DbProfileRules v1");
+        models
+            .define::<v1::DbTemplate>()
+            .expect("failed to define DbTemplate v1");
 
         // Actions
 
@@ -93,7 +118,13 @@ pub static MODELS: Lazy<native_db::Models> = Lazy::new(|| {
                 .expect("failed to define DbDisplayConfig v1"),
             models
                 .define::<v1::DbLaunchSecondaryFlatpakApp>()
+                .expect("failed to define LaunchSecondaryFlatpakApp v1"),
+            models
+                .define::<v1::DbLaunchSecondaryApp>()
                 .expect("failed to define LaunchSecondaryApp v1"),
+            models
+                .define::<v1::DbLaunchSecondaryAppGroup>()
+                .expect("failed to define LaunchSecondaryAppGroup v1"),
             models
                 .define::<v1::DbLaunchSecondaryAppPreset>()
                 .expect("failed to define LaunchSecondaryAppPreset v1"),
@@ -110,6 +141,38 @@ pub static MODELS: Lazy<native_db::Models> = Lazy::new(|| {
 
         assert_eq!(ActionType::iter().len(), v1_actions.len());
     }
+    // V2
+    {
+        // Profiles
+
+        models
+            .define::<v2::DbCategoryProfile>()
+            .expect("failed to define CategoryProfile v2");
+        models
+            .define::<v2::DbAppOverride>()
+            .expect("failed to define AppProfile v2");
+        models
+            .define::<v2::DbProfileRules>()
+            .expect("failed to define DbProfileRules v2");
+        models
+            .define::<v2::DbProfileRevision>()
+            .expect("failed to define DbProfileRevision v2");
+        models
+            .define::<v2::DbTemplate>()
+            .expect("failed to define DbTemplate v2");
+
+        // Actions
+
+        models
+            .define::<v2::DbMultiWindow>()
+            .expect("failed to define MultiWindow v2");
+        models
+            .define::<v2::DbLaunchSecondaryAppPreset>()
+            .expect("failed to define LaunchSecondaryAppPreset v2");
+        models
+            .define::<v2::DbLaunchSecondaryFlatpakApp>()
+            .expect("failed to define LaunchSecondaryFlatpakApp v2");
+    }
 
     models
 });
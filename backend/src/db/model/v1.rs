@@ -2,10 +2,13 @@ use native_db::*;
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use crate::{
-    db::codec::rmp_serde_1_3::{RmpSerde, RmpSerdeNamed},
+    db::{
+        codec::rmp_serde_1_3::{RmpSerde, RmpSerdeNamed},
+        revision::RevisionId,
+    },
     pipeline::{
         action::{
             cemu_audio::{CemuAudio, CemuAudioChannels, CemuAudioSetting, CemuAudioState},
@@ -18,25 +21,25 @@ use crate::{
             multi_window::{
                 main_app_automatic_windowing::{
                     GamescopeFilter, GamescopeFullscreenOption, GamescopeOptions, GamescopeScaler,
-                    MainAppAutomaticWindowing,
-                },
-                primary_windowing::{
-                    CemuWindowOptions, CitraWindowOptions, CustomWindowOptions,
-                    DolphinWindowOptions, GeneralOptions,
+                    LearnedWindowMatch, MainAppAutomaticWindowing,
                 },
+                primary_windowing::GeneralOptions,
                 secondary_app::{
-                    LaunchSecondaryAppPreset, LaunchSecondaryFlatpakApp,
-                    SecondaryAppScreenPreference, SecondaryAppWindowingBehavior,
+                    LaunchSecondaryApp, LaunchSecondaryAppGroup, SecondaryAppExitPolicy,
+                    SecondaryAppGroupEntry, SecondaryAppScreenPreference, SecondaryAppTiling,
+                    SecondaryAppWindowingBehavior,
                 },
             },
             session_handler::DesktopSessionHandler,
             touch_config::TouchConfig,
             ActionId,
         },
-        data::{PipelineActionId, PipelineDefinitionId, PipelineTarget, TopLevelId},
+        data::{PipelineActionId, PipelineDefinitionId, PipelineTarget, TemplateId, TopLevelId},
     },
-    secondary_app::{FlatpakApp, SecondaryApp, SecondaryAppPresetId},
-    settings::{AppId, ProfileId},
+    secondary_app::{
+        AppImageApp, FlatpakApp, NativeApp, SecondaryApp, SecondaryAppPresetId, SteamApp, UrlApp,
+    },
+    settings::{AppId, GameId, ProfileId},
     sys::x_display::x_touch::TouchSelectionMode,
 };
 
@@ -46,11 +49,9 @@ use crate::{
     pipeline::action::{
         emu_source::{
             AppImageSource, CustomEmuSource, EmuDeckSource, EmuSettingsSource,
-            EmuSettingsSourceConfig, FlatpakSource,
-        },
-        multi_window::primary_windowing::{
-            LimitedMultiWindowLayout, MultiWindow, MultiWindowLayout,
+            EmuSettingsSourceConfig, FlatpakSource, RetroDeckSource,
         },
+        multi_window::primary_windowing::{LimitedMultiWindowLayout, MultiWindowLayout},
         session_handler::{ExternalDisplaySettings, RelativeLocation},
         virtual_screen::VirtualScreen,
     },
@@ -78,6 +79,78 @@ pub struct DbAppSettings {
 
 #[derive(Serialize, Deserialize)]
 #[native_db]
+#[native_model(id = 6, version = 1, with = RmpSerde)]
+pub struct DbLearnedWindowMatch {
+    #[primary_key]
+    pub app_id: AppId,
+    pub window_matcher: String,
+    pub classes: Vec<String>,
+}
+
+/// The schema version of the db, as a single record. Databases without one predate
+/// schema versioning.
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 7, version = 1, with = RmpSerde)]
+pub struct DbSchemaVersion {
+    #[primary_key]
+    pub id: u8,
+    pub version: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 8, version = 1, with = RmpSerde)]
+pub struct DbProfileRevision {
+    #[primary_key]
+    pub id: RevisionId,
+    #[secondary_key]
+    pub profile_id: ProfileId,
+    pub app_id: Option<AppId>,
+    pub created: u64,
+    pub source: String,
+    /// The saved pipeline, as JSON
+    pub pipeline: String,
+}
+
+/// Singleton holding the ordered automatic profile selection rules.
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 9, version = 1, with = RmpSerde)]
+pub struct DbProfileRules {
+    #[primary_key]
+    pub id: u8,
+    /// The rules, in order, as JSON
+    pub rules: String,
+}
+
+/// A template saved by the user. Built-in and file templates are not stored.
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 10, version = 1, with = RmpSerde)]
+pub struct DbTemplate {
+    #[primary_key]
+    pub id: TemplateId,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    /// The template pipeline, as JSON
+    pub pipeline: String,
+}
+
+impl From<DbLearnedWindowMatch> for (AppId, LearnedWindowMatch) {
+    fn from(value: DbLearnedWindowMatch) -> Self {
+        (
+            value.app_id,
+            LearnedWindowMatch {
+                window_matcher: value.window_matcher,
+                classes: value.classes,
+            },
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
 #[native_model(id = 3, version = 1, with = RmpSerde)]
 pub struct DbAppOverride {
     #[primary_key]
@@ -85,7 +158,7 @@ pub struct DbAppOverride {
     pub pipeline: DbPipelineDefinition,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[native_db]
 #[native_model(id = 4, version = 1, with = RmpSerde)]
 pub struct DbPipelineDefinition {
@@ -132,7 +205,7 @@ pub struct DbPipelineDefinition {
 //     }
 // }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DbTopLevelDefinition {
     pub id: TopLevelId,
     pub root: PipelineActionId,
@@ -294,6 +367,8 @@ impl From<MelonDSLayout> for DbMelonDSLayout {
                 MelonDSSizingOption::EmphasizeTop => DbMelonDSSizingOption::EmphasizeTop,
                 MelonDSSizingOption::EmphasizeBottom => DbMelonDSSizingOption::EmphasizeBottom,
                 MelonDSSizingOption::Auto => DbMelonDSSizingOption::Auto,
+                MelonDSSizingOption::TopOnly => DbMelonDSSizingOption::TopOnly,
+                MelonDSSizingOption::BottomOnly => DbMelonDSSizingOption::BottomOnly,
             },
             book_mode: value.book_mode,
             swap_screens: value.swap_screens,
@@ -318,6 +393,8 @@ impl From<DbMelonDSLayout> for MelonDSLayout {
                 DbMelonDSSizingOption::EmphasizeTop => MelonDSSizingOption::EmphasizeTop,
                 DbMelonDSSizingOption::EmphasizeBottom => MelonDSSizingOption::EmphasizeBottom,
                 DbMelonDSSizingOption::Auto => MelonDSSizingOption::Auto,
+                DbMelonDSSizingOption::TopOnly => MelonDSSizingOption::TopOnly,
+                DbMelonDSSizingOption::BottomOnly => MelonDSSizingOption::BottomOnly,
             },
             book_mode: value.book_mode,
             swap_screens: value.swap_screens,
@@ -332,7 +409,7 @@ pub enum DbMelonDSLayoutOption {
     Vertical,   // Puts screens vertical always,
     Horizontal, // Puts screens horizonal always,
     Hybrid,     // Puts main screen large, with both screens adjacent. Overrides sizing settings.
-    Single,     // Displays only one screen,
+    Single,     // Displays only one screen; bottom if sizing is `BottomOnly`, top otherwise.
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -341,6 +418,8 @@ pub enum DbMelonDSSizingOption {
     EmphasizeTop,
     EmphasizeBottom,
     Auto,
+    TopOnly,
+    BottomOnly,
 }
 
 #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
@@ -582,28 +661,28 @@ pub struct DbMultiWindow {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbMultiWindowGeneralOptions {
-    keep_above: bool,
-    swap_screens: bool,
+    pub keep_above: bool,
+    pub swap_screens: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbMultiWindowCemuOptions {
-    single_screen_layout: DbLimitedMultiWindowLayout,
-    multi_screen_layout: DbMultiWindowLayout,
+    pub single_screen_layout: DbLimitedMultiWindowLayout,
+    pub multi_screen_layout: DbMultiWindowLayout,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbMultiWindowCitraOptions {
-    single_screen_layout: DbLimitedMultiWindowLayout,
-    multi_screen_layout: DbMultiWindowLayout,
+    pub single_screen_layout: DbLimitedMultiWindowLayout,
+    pub multi_screen_layout: DbMultiWindowLayout,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbMultiWindowDolphinOptions {
-    single_screen_layout: DbLimitedMultiWindowLayout,
-    multi_screen_single_secondary_layout: DbMultiWindowLayout,
-    multi_screen_multi_secondary_layout: DbMultiWindowLayout,
-    gba_blacklist: Vec<u8>,
+    pub single_screen_layout: DbLimitedMultiWindowLayout,
+    pub multi_screen_single_secondary_layout: DbMultiWindowLayout,
+    pub multi_screen_multi_secondary_layout: DbMultiWindowLayout,
+    pub gba_blacklist: Vec<u8>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -679,68 +758,6 @@ pub enum DbMultiWindowLayout {
     Separate,
 }
 
-impl From<MultiWindow> for DbMultiWindow {
-    fn from(value: MultiWindow) -> Self {
-        Self {
-            id: value.id,
-            general: value.general.into(),
-            cemu: value.cemu.map(|v| DbMultiWindowCemuOptions {
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_layout: v.multi_screen_layout.into(),
-            }),
-            citra: value.citra.map(|v| DbMultiWindowCitraOptions {
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_layout: v.multi_screen_layout.into(),
-            }),
-            dolphin: value.dolphin.map(|v| DbMultiWindowDolphinOptions {
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
-                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
-                gba_blacklist: v.gba_blacklist,
-            }),
-            custom: value.custom.map(|v| DbMultiWindowCustomOptions {
-                primary_window_matcher: v.primary_window_matcher.clone(),
-                secondary_window_matcher: v.secondary_window_matcher.clone(),
-                classes: v.classes.clone(),
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
-                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
-            }),
-        }
-    }
-}
-
-impl From<DbMultiWindow> for MultiWindow {
-    fn from(value: DbMultiWindow) -> Self {
-        Self {
-            id: value.id,
-            general: value.general.into(),
-            cemu: value.cemu.map(|v| CemuWindowOptions {
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_layout: v.multi_screen_layout.into(),
-            }),
-            citra: value.citra.map(|v| CitraWindowOptions {
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_layout: v.multi_screen_layout.into(),
-            }),
-            dolphin: value.dolphin.map(|v| DolphinWindowOptions {
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
-                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
-                gba_blacklist: v.gba_blacklist,
-            }),
-            custom: value.custom.map(|v| CustomWindowOptions {
-                primary_window_matcher: v.primary_window_matcher.clone(),
-                secondary_window_matcher: v.secondary_window_matcher.clone(),
-                classes: v.classes.clone(),
-                single_screen_layout: v.single_screen_layout.into(),
-                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
-                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
-            }),
-        }
-    }
-}
-
 impl From<GeneralOptions> for DbMultiWindowGeneralOptions {
     fn from(value: GeneralOptions) -> Self {
         Self {
@@ -779,12 +796,26 @@ impl From<EmuSettingsSourceConfig> for DbSourceFile {
                     FlatpakSource::MelonDS => DbFlatpakSource::MelonDS,
                     FlatpakSource::MelonDSPrerelease => DbFlatpakSource::MelonDSPrerelease,
                     FlatpakSource::Lime3ds => DbFlatpakSource::Lime3ds,
+                    FlatpakSource::MelonDSDetected => DbFlatpakSource::MelonDSDetected,
                 }),
                 EmuSettingsSource::AppImage(v) => DbFileSource::AppImage(match v {
                     AppImageSource::Cemu => DbAppImageSource::Cemu,
                 }),
                 EmuSettingsSource::EmuDeck(v) => DbFileSource::EmuDeck(match v {
                     EmuDeckSource::CemuProton => DbEmuDeckSource::CemuProton,
+                    EmuDeckSource::Cemu => DbEmuDeckSource::Cemu,
+                    EmuDeckSource::MelonDS => DbEmuDeckSource::MelonDS,
+                    EmuDeckSource::Citra => DbEmuDeckSource::Citra,
+                    EmuDeckSource::Lime3ds => DbEmuDeckSource::Lime3ds,
+                    EmuDeckSource::Azahar => DbEmuDeckSource::Azahar,
+                    EmuDeckSource::Dolphin => DbEmuDeckSource::Dolphin,
+                }),
+                EmuSettingsSource::RetroDeck(v) => DbFileSource::RetroDeck(match v {
+                    RetroDeckSource::Citra => DbRetroDeckSource::Citra,
+                    RetroDeckSource::Lime3ds => DbRetroDeckSource::Lime3ds,
+                    RetroDeckSource::MelonDS => DbRetroDeckSource::MelonDS,
+                    RetroDeckSource::Cemu => DbRetroDeckSource::Cemu,
+                    RetroDeckSource::Dolphin => DbRetroDeckSource::Dolphin,
                 }),
                 EmuSettingsSource::Custom(v) => DbFileSource::Custom(DbCustomEmuSource {
                     valid_ext: v.valid_ext,
@@ -803,15 +834,29 @@ impl From<DbSourceFile> for EmuSettingsSourceConfig {
                 DbFileSource::Flatpak(v) => EmuSettingsSource::Flatpak(match v {
                     DbFlatpakSource::Cemu => FlatpakSource::Cemu,
                     DbFlatpakSource::Citra => FlatpakSource::Citra,
-                    DbFlatpakSource::MelonDSPrerelease => FlatpakSource::MelonDS,
+                    DbFlatpakSource::MelonDSPrerelease => FlatpakSource::MelonDSPrerelease,
                     DbFlatpakSource::MelonDS => FlatpakSource::MelonDS,
                     DbFlatpakSource::Lime3ds => FlatpakSource::Lime3ds,
+                    DbFlatpakSource::MelonDSDetected => FlatpakSource::MelonDSDetected,
                 }),
                 DbFileSource::AppImage(v) => EmuSettingsSource::AppImage(match v {
                     DbAppImageSource::Cemu => AppImageSource::Cemu,
                 }),
                 DbFileSource::EmuDeck(v) => EmuSettingsSource::EmuDeck(match v {
                     DbEmuDeckSource::CemuProton => EmuDeckSource::CemuProton,
+                    DbEmuDeckSource::Cemu => EmuDeckSource::Cemu,
+                    DbEmuDeckSource::MelonDS => EmuDeckSource::MelonDS,
+                    DbEmuDeckSource::Citra => EmuDeckSource::Citra,
+                    DbEmuDeckSource::Lime3ds => EmuDeckSource::Lime3ds,
+                    DbEmuDeckSource::Azahar => EmuDeckSource::Azahar,
+                    DbEmuDeckSource::Dolphin => EmuDeckSource::Dolphin,
+                }),
+                DbFileSource::RetroDeck(v) => EmuSettingsSource::RetroDeck(match v {
+                    DbRetroDeckSource::Citra => RetroDeckSource::Citra,
+                    DbRetroDeckSource::Lime3ds => RetroDeckSource::Lime3ds,
+                    DbRetroDeckSource::MelonDS => RetroDeckSource::MelonDS,
+                    DbRetroDeckSource::Cemu => RetroDeckSource::Cemu,
+                    DbRetroDeckSource::Dolphin => RetroDeckSource::Dolphin,
                 }),
                 DbFileSource::Custom(v) => EmuSettingsSource::Custom(CustomEmuSource {
                     valid_ext: v.valid_ext,
@@ -829,6 +874,7 @@ pub enum DbFileSource {
     Flatpak(DbFlatpakSource),
     AppImage(DbAppImageSource),
     EmuDeck(DbEmuDeckSource),
+    RetroDeck(DbRetroDeckSource),
     Custom(DbCustomEmuSource),
 }
 
@@ -848,11 +894,27 @@ pub enum DbFlatpakSource {
     MelonDSPrerelease,
     MelonDS,
     Lime3ds,
+    MelonDSDetected,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DbEmuDeckSource {
     CemuProton,
+    Cemu,
+    MelonDS,
+    Citra,
+    Lime3ds,
+    Azahar,
+    Dolphin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DbRetroDeckSource {
+    Citra,
+    Lime3ds,
+    MelonDS,
+    Cemu,
+    Dolphin,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -937,6 +999,10 @@ pub struct DbLaunchSecondaryFlatpakApp {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DbSecondaryApp {
     Flatpak(DbSecondaryFlatpakApp),
+    Native(DbSecondaryNativeApp),
+    AppImage(DbSecondaryAppImageApp),
+    Steam(DbSecondarySteamApp),
+    Url(DbSecondaryUrlApp),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -945,6 +1011,30 @@ pub struct DbSecondaryFlatpakApp {
     args: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSecondaryNativeApp {
+    path: PathBuf,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    working_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSecondaryAppImageApp {
+    path: PathBuf,
+    args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSecondarySteamApp {
+    game_id: GameId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSecondaryUrlApp {
+    url: String,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum DbSecondaryAppWindowingBehavior {
     Fullscreen,
@@ -959,6 +1049,26 @@ pub enum DbSecondaryAppScreenPreference {
     PreferPrimary,
 }
 
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum DbSecondaryAppExitPolicy {
+    #[default]
+    LeaveClosed,
+    Restart {
+        max_restarts: u32,
+    },
+    EndSession,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum DbSecondaryAppTiling {
+    #[default]
+    Auto,
+    SplitHorizontal,
+    SplitVertical,
+    Grid,
+    Stacked,
+}
+
 impl From<FlatpakApp> for DbSecondaryFlatpakApp {
     fn from(value: FlatpakApp) -> Self {
         Self {
@@ -977,32 +1087,24 @@ impl From<DbSecondaryFlatpakApp> for FlatpakApp {
     }
 }
 
-impl From<LaunchSecondaryFlatpakApp> for DbLaunchSecondaryFlatpakApp {
-    fn from(value: LaunchSecondaryFlatpakApp) -> Self {
-        Self {
-            id: value.id,
-            app: value.app.into(),
-            windowing_behavior: value.windowing_behavior.into(),
-            screen_preference: value.screen_preference.into(),
-        }
-    }
-}
-
-impl From<DbLaunchSecondaryFlatpakApp> for LaunchSecondaryFlatpakApp {
-    fn from(value: DbLaunchSecondaryFlatpakApp) -> Self {
-        Self {
-            id: value.id,
-            app: value.app.into(),
-            windowing_behavior: value.windowing_behavior.into(),
-            screen_preference: value.screen_preference.into(),
-        }
-    }
-}
-
 impl From<SecondaryApp> for DbSecondaryApp {
     fn from(value: SecondaryApp) -> Self {
         match value {
             SecondaryApp::Flatpak(app) => DbSecondaryApp::Flatpak(app.into()),
+            SecondaryApp::Native(app) => DbSecondaryApp::Native(DbSecondaryNativeApp {
+                path: app.path,
+                args: app.args,
+                env: app.env,
+                working_dir: app.working_dir,
+            }),
+            SecondaryApp::AppImage(app) => DbSecondaryApp::AppImage(DbSecondaryAppImageApp {
+                path: app.path,
+                args: app.args,
+            }),
+            SecondaryApp::Steam(app) => DbSecondaryApp::Steam(DbSecondarySteamApp {
+                game_id: app.game_id,
+            }),
+            SecondaryApp::Url(app) => DbSecondaryApp::Url(DbSecondaryUrlApp { url: app.url }),
         }
     }
 }
@@ -1011,6 +1113,20 @@ impl From<DbSecondaryApp> for SecondaryApp {
     fn from(value: DbSecondaryApp) -> Self {
         match value {
             DbSecondaryApp::Flatpak(app) => SecondaryApp::Flatpak(app.into()),
+            DbSecondaryApp::Native(app) => SecondaryApp::Native(NativeApp {
+                path: app.path,
+                args: app.args,
+                env: app.env,
+                working_dir: app.working_dir,
+            }),
+            DbSecondaryApp::AppImage(app) => SecondaryApp::AppImage(AppImageApp {
+                path: app.path,
+                args: app.args,
+            }),
+            DbSecondaryApp::Steam(app) => SecondaryApp::Steam(SteamApp {
+                game_id: app.game_id,
+            }),
+            DbSecondaryApp::Url(app) => SecondaryApp::Url(UrlApp { url: app.url }),
         }
     }
 }
@@ -1067,39 +1183,167 @@ impl From<DbSecondaryAppScreenPreference> for SecondaryAppScreenPreference {
     }
 }
 
+impl From<SecondaryAppExitPolicy> for DbSecondaryAppExitPolicy {
+    fn from(value: SecondaryAppExitPolicy) -> Self {
+        match value {
+            SecondaryAppExitPolicy::LeaveClosed => DbSecondaryAppExitPolicy::LeaveClosed,
+            SecondaryAppExitPolicy::Restart { max_restarts } => {
+                DbSecondaryAppExitPolicy::Restart { max_restarts }
+            }
+            SecondaryAppExitPolicy::EndSession => DbSecondaryAppExitPolicy::EndSession,
+        }
+    }
+}
+
+impl From<DbSecondaryAppExitPolicy> for SecondaryAppExitPolicy {
+    fn from(value: DbSecondaryAppExitPolicy) -> Self {
+        match value {
+            DbSecondaryAppExitPolicy::LeaveClosed => SecondaryAppExitPolicy::LeaveClosed,
+            DbSecondaryAppExitPolicy::Restart { max_restarts } => {
+                SecondaryAppExitPolicy::Restart { max_restarts }
+            }
+            DbSecondaryAppExitPolicy::EndSession => SecondaryAppExitPolicy::EndSession,
+        }
+    }
+}
+
+impl From<SecondaryAppTiling> for DbSecondaryAppTiling {
+    fn from(value: SecondaryAppTiling) -> Self {
+        match value {
+            SecondaryAppTiling::Auto => DbSecondaryAppTiling::Auto,
+            SecondaryAppTiling::SplitHorizontal => DbSecondaryAppTiling::SplitHorizontal,
+            SecondaryAppTiling::SplitVertical => DbSecondaryAppTiling::SplitVertical,
+            SecondaryAppTiling::Grid => DbSecondaryAppTiling::Grid,
+            SecondaryAppTiling::Stacked => DbSecondaryAppTiling::Stacked,
+        }
+    }
+}
+
+impl From<DbSecondaryAppTiling> for SecondaryAppTiling {
+    fn from(value: DbSecondaryAppTiling) -> Self {
+        match value {
+            DbSecondaryAppTiling::Auto => SecondaryAppTiling::Auto,
+            DbSecondaryAppTiling::SplitHorizontal => SecondaryAppTiling::SplitHorizontal,
+            DbSecondaryAppTiling::SplitVertical => SecondaryAppTiling::SplitVertical,
+            DbSecondaryAppTiling::Grid => SecondaryAppTiling::Grid,
+            DbSecondaryAppTiling::Stacked => SecondaryAppTiling::Stacked,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[native_db]
-#[native_model(id = 1010, version = 1, with = RmpSerde)]
-pub struct DbLaunchSecondaryAppPreset {
+#[native_model(id = 1016, version = 1, with = RmpSerde)]
+pub struct DbLaunchSecondaryApp {
     #[primary_key]
     pub id: ActionId,
-    pub preset: SecondaryAppPresetId,
+    pub app: DbSecondaryApp,
     pub windowing_behavior: DbSecondaryAppWindowingBehavior,
     pub screen_preference: DbSecondaryAppScreenPreference,
+    pub exit_policy: DbSecondaryAppExitPolicy,
+    pub tiling: DbSecondaryAppTiling,
+    pub z_order: u32,
 }
 
-impl From<LaunchSecondaryAppPreset> for DbLaunchSecondaryAppPreset {
-    fn from(value: LaunchSecondaryAppPreset) -> Self {
+impl From<LaunchSecondaryApp> for DbLaunchSecondaryApp {
+    fn from(value: LaunchSecondaryApp) -> Self {
         Self {
             id: value.id,
-            preset: value.preset,
+            app: value.app.into(),
             windowing_behavior: value.windowing_behavior.into(),
             screen_preference: value.screen_preference.into(),
+            exit_policy: value.exit_policy.into(),
+            tiling: value.tiling.into(),
+            z_order: value.z_order,
         }
     }
 }
 
-impl From<DbLaunchSecondaryAppPreset> for LaunchSecondaryAppPreset {
-    fn from(value: DbLaunchSecondaryAppPreset) -> Self {
+impl From<DbLaunchSecondaryApp> for LaunchSecondaryApp {
+    fn from(value: DbLaunchSecondaryApp) -> Self {
         Self {
             id: value.id,
-            preset: value.preset,
+            app: value.app.into(),
             windowing_behavior: value.windowing_behavior.into(),
             screen_preference: value.screen_preference.into(),
+            exit_policy: value.exit_policy.into(),
+            tiling: value.tiling.into(),
+            z_order: value.z_order,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSecondaryAppGroupEntry {
+    pub app: DbSecondaryApp,
+    pub windowing_behavior: DbSecondaryAppWindowingBehavior,
+    pub exit_policy: DbSecondaryAppExitPolicy,
+}
+
+impl From<SecondaryAppGroupEntry> for DbSecondaryAppGroupEntry {
+    fn from(value: SecondaryAppGroupEntry) -> Self {
+        Self {
+            app: value.app.into(),
+            windowing_behavior: value.windowing_behavior.into(),
+            exit_policy: value.exit_policy.into(),
+        }
+    }
+}
+
+impl From<DbSecondaryAppGroupEntry> for SecondaryAppGroupEntry {
+    fn from(value: DbSecondaryAppGroupEntry) -> Self {
+        Self {
+            app: value.app.into(),
+            windowing_behavior: value.windowing_behavior.into(),
+            exit_policy: value.exit_policy.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 1017, version = 1, with = RmpSerde)]
+pub struct DbLaunchSecondaryAppGroup {
+    #[primary_key]
+    pub id: ActionId,
+    pub apps: Vec<DbSecondaryAppGroupEntry>,
+    pub screen_preference: DbSecondaryAppScreenPreference,
+    pub tiling: DbSecondaryAppTiling,
+}
+
+impl From<LaunchSecondaryAppGroup> for DbLaunchSecondaryAppGroup {
+    fn from(value: LaunchSecondaryAppGroup) -> Self {
+        Self {
+            id: value.id,
+            apps: value.apps.into_iter().map(|v| v.into()).collect(),
+            screen_preference: value.screen_preference.into(),
+            tiling: value.tiling.into(),
+        }
+    }
+}
+
+impl From<DbLaunchSecondaryAppGroup> for LaunchSecondaryAppGroup {
+    fn from(value: DbLaunchSecondaryAppGroup) -> Self {
+        Self {
+            id: value.id,
+            apps: value.apps.into_iter().map(|v| v.into()).collect(),
+            screen_preference: value.screen_preference.into(),
+            tiling: value.tiling.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 1010, version = 1, with = RmpSerde)]
+pub struct DbLaunchSecondaryAppPreset {
+    #[primary_key]
+    pub id: ActionId,
+    pub preset: SecondaryAppPresetId,
+    pub windowing_behavior: DbSecondaryAppWindowingBehavior,
+    pub screen_preference: DbSecondaryAppScreenPreference,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[native_db]
 #[native_model(id = 1011, version = 1, with = RmpSerde)]
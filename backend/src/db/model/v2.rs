@@ -0,0 +1,473 @@
+use native_db::*;
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{
+        codec::rmp_serde_1_3::RmpSerde,
+        revision::RevisionId,
+        rules::{ProfileRule, ProfileRuleId, RuleCondition},
+    },
+    pipeline::{
+        action::{
+            multi_window::{
+                primary_windowing::{
+                    CemuWindowOptions, CitraWindowOptions, CustomWindowOptions,
+                    DolphinWindowOptions, MelonDSWindowOptions, MultiWindow,
+                },
+                secondary_app::{LaunchSecondaryAppPreset, LaunchSecondaryFlatpakApp},
+            },
+            ActionId,
+        },
+        data::TemplateId,
+    },
+    secondary_app::SecondaryAppPresetId,
+    settings::{AppId, ProfileId},
+};
+
+use super::v1::{
+    self, DbLimitedMultiWindowLayout, DbMultiWindowGeneralOptions, DbMultiWindowLayout,
+    DbPipelineDefinition, DbSecondaryAppExitPolicy, DbSecondaryAppScreenPreference,
+    DbSecondaryAppWindowingBehavior, DbSecondaryFlatpakApp,
+};
+
+// Core
+
+#[derive(Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 1, version = 2, with = RmpSerde, from = v1::DbCategoryProfile)]
+pub struct DbCategoryProfile {
+    #[primary_key]
+    pub id: ProfileId,
+    pub tags: Vec<String>,
+    pub pipeline: DbPipelineDefinition,
+    /// The profile unspecified actions are inherited from
+    pub parent: Option<ProfileId>,
+}
+
+impl From<v1::DbCategoryProfile> for DbCategoryProfile {
+    fn from(value: v1::DbCategoryProfile) -> Self {
+        Self {
+            id: value.id,
+            tags: value.tags,
+            pipeline: value.pipeline,
+            parent: None,
+        }
+    }
+}
+
+impl From<DbCategoryProfile> for v1::DbCategoryProfile {
+    fn from(value: DbCategoryProfile) -> Self {
+        Self {
+            id: value.id,
+            tags: value.tags,
+            pipeline: value.pipeline,
+        }
+    }
+}
+
+/// Indexed by app and by profile, so app lookups don't scan every override.
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 3, version = 2, with = RmpSerde, from = v1::DbAppOverride)]
+pub struct DbAppOverride {
+    #[primary_key]
+    pub id: (AppId, ProfileId),
+    #[secondary_key]
+    pub app_id: AppId,
+    #[secondary_key]
+    pub profile_id: ProfileId,
+    pub pipeline: DbPipelineDefinition,
+}
+
+impl DbAppOverride {
+    pub fn new(app_id: AppId, profile_id: ProfileId, pipeline: DbPipelineDefinition) -> Self {
+        Self {
+            id: (app_id.clone(), profile_id),
+            app_id,
+            profile_id,
+            pipeline,
+        }
+    }
+}
+
+impl From<v1::DbAppOverride> for DbAppOverride {
+    fn from(value: v1::DbAppOverride) -> Self {
+        let (app_id, profile_id) = value.id;
+
+        Self::new(app_id, profile_id, value.pipeline)
+    }
+}
+
+impl From<DbAppOverride> for v1::DbAppOverride {
+    fn from(value: DbAppOverride) -> Self {
+        Self {
+            id: value.id,
+            pipeline: value.pipeline,
+        }
+    }
+}
+
+/// Singleton holding the ordered automatic profile selection rules.
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 9, version = 2, with = RmpSerde, from = v1::DbProfileRules)]
+pub struct DbProfileRules {
+    #[primary_key]
+    pub id: u8,
+    /// The rules, in evaluation order
+    pub rules: Vec<DbProfileRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbProfileRule {
+    pub id: ProfileRuleId,
+    pub name: String,
+    pub profile_id: ProfileId,
+    pub conditions: Vec<DbRuleCondition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DbRuleCondition {
+    Collection(String),
+    Tag(String),
+    TitleRegex(String),
+    IsSteamGame(bool),
+    ExecutableRegex(String),
+}
+
+impl From<v1::DbProfileRules> for DbProfileRules {
+    fn from(value: v1::DbProfileRules) -> Self {
+        // v1 stored the API rules as JSON
+        let rules: Vec<ProfileRule> = serde_json::from_str(&value.rules)
+            .inspect_err(|err| log::error!("dropping unreadable profile rules: {err}"))
+            .unwrap_or_default();
+
+        Self {
+            id: value.id,
+            rules: rules.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<DbProfileRules> for v1::DbProfileRules {
+    fn from(value: DbProfileRules) -> Self {
+        let rules: Vec<ProfileRule> = value.rules.into_iter().map(Into::into).collect();
+
+        Self {
+            id: value.id,
+            rules: serde_json::to_string(&rules).expect("profile rules should serialize"),
+        }
+    }
+}
+
+impl From<ProfileRule> for DbProfileRule {
+    fn from(value: ProfileRule) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            profile_id: value.profile_id,
+            conditions: value
+                .conditions
+                .into_iter()
+                .map(|condition| match condition {
+                    RuleCondition::Collection(v) => DbRuleCondition::Collection(v),
+                    RuleCondition::Tag(v) => DbRuleCondition::Tag(v),
+                    RuleCondition::TitleRegex(v) => DbRuleCondition::TitleRegex(v),
+                    RuleCondition::IsSteamGame(v) => DbRuleCondition::IsSteamGame(v),
+                    RuleCondition::ExecutableRegex(v) => DbRuleCondition::ExecutableRegex(v),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<DbProfileRule> for ProfileRule {
+    fn from(value: DbProfileRule) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            profile_id: value.profile_id,
+            conditions: value
+                .conditions
+                .into_iter()
+                .map(|condition| match condition {
+                    DbRuleCondition::Collection(v) => RuleCondition::Collection(v),
+                    DbRuleCondition::Tag(v) => RuleCondition::Tag(v),
+                    DbRuleCondition::TitleRegex(v) => RuleCondition::TitleRegex(v),
+                    DbRuleCondition::IsSteamGame(v) => RuleCondition::IsSteamGame(v),
+                    DbRuleCondition::ExecutableRegex(v) => RuleCondition::ExecutableRegex(v),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A snapshot of a saved pipeline. The pipeline is saved to the action tables under its own ids,
+/// so it is migrated with them.
+///
+/// There is no `from` conversion, since saving the v1 JSON pipeline needs the transaction;
+/// see `migrate_v8`.
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 8, version = 2, with = RmpSerde)]
+pub struct DbProfileRevision {
+    #[primary_key]
+    pub id: RevisionId,
+    #[secondary_key]
+    pub profile_id: ProfileId,
+    pub app_id: Option<AppId>,
+    pub created: u64,
+    pub source: String,
+    pub pipeline: DbPipelineDefinition,
+}
+
+/// A template saved by the user, with its pipeline saved to the action tables like
+/// [DbProfileRevision].
+#[derive(Debug, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 10, version = 2, with = RmpSerde)]
+pub struct DbTemplate {
+    #[primary_key]
+    pub id: TemplateId,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub pipeline: DbPipelineDefinition,
+}
+
+// Actions
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 1005, version = 2, with = RmpSerde, from = v1::DbMultiWindow)]
+pub struct DbMultiWindow {
+    #[primary_key]
+    pub id: ActionId,
+    pub general: DbMultiWindowGeneralOptions,
+    pub cemu: Option<v1::DbMultiWindowCemuOptions>,
+    pub citra: Option<v1::DbMultiWindowCitraOptions>,
+    pub dolphin: Option<v1::DbMultiWindowDolphinOptions>,
+    pub custom: Option<v1::DbMultiWindowCustomOptions>,
+    pub melonds: Option<DbMultiWindowMelonDSOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbMultiWindowMelonDSOptions {
+    single_screen_layout: DbLimitedMultiWindowLayout,
+    multi_screen_layout: DbMultiWindowLayout,
+}
+
+impl From<v1::DbMultiWindow> for DbMultiWindow {
+    fn from(value: v1::DbMultiWindow) -> Self {
+        Self {
+            id: value.id,
+            general: value.general,
+            cemu: value.cemu,
+            citra: value.citra,
+            dolphin: value.dolphin,
+            custom: value.custom,
+            melonds: None,
+        }
+    }
+}
+
+impl From<DbMultiWindow> for v1::DbMultiWindow {
+    fn from(value: DbMultiWindow) -> Self {
+        Self {
+            id: value.id,
+            general: value.general,
+            cemu: value.cemu,
+            citra: value.citra,
+            dolphin: value.dolphin,
+            custom: value.custom,
+        }
+    }
+}
+
+impl From<MultiWindow> for DbMultiWindow {
+    fn from(value: MultiWindow) -> Self {
+        Self {
+            id: value.id,
+            general: value.general.into(),
+            cemu: value.cemu.map(|v| v1::DbMultiWindowCemuOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_layout: v.multi_screen_layout.into(),
+            }),
+            citra: value.citra.map(|v| v1::DbMultiWindowCitraOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_layout: v.multi_screen_layout.into(),
+            }),
+            dolphin: value.dolphin.map(|v| v1::DbMultiWindowDolphinOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
+                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
+                gba_blacklist: v.gba_blacklist,
+            }),
+            custom: value.custom.map(|v| v1::DbMultiWindowCustomOptions {
+                primary_window_matcher: v.primary_window_matcher.clone(),
+                secondary_window_matcher: v.secondary_window_matcher.clone(),
+                classes: v.classes.clone(),
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
+                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
+            }),
+            melonds: value.melonds.map(|v| DbMultiWindowMelonDSOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_layout: v.multi_screen_layout.into(),
+            }),
+        }
+    }
+}
+
+impl From<DbMultiWindow> for MultiWindow {
+    fn from(value: DbMultiWindow) -> Self {
+        Self {
+            id: value.id,
+            general: value.general.into(),
+            cemu: value.cemu.map(|v| CemuWindowOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_layout: v.multi_screen_layout.into(),
+            }),
+            citra: value.citra.map(|v| CitraWindowOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_layout: v.multi_screen_layout.into(),
+            }),
+            dolphin: value.dolphin.map(|v| DolphinWindowOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
+                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
+                gba_blacklist: v.gba_blacklist,
+            }),
+            melonds: value.melonds.map(|v| MelonDSWindowOptions {
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_layout: v.multi_screen_layout.into(),
+            }),
+            custom: value.custom.map(|v| CustomWindowOptions {
+                primary_window_matcher: v.primary_window_matcher.clone(),
+                secondary_window_matcher: v.secondary_window_matcher.clone(),
+                classes: v.classes.clone(),
+                single_screen_layout: v.single_screen_layout.into(),
+                multi_screen_single_secondary_layout: v.multi_screen_single_secondary_layout.into(),
+                multi_screen_multi_secondary_layout: v.multi_screen_multi_secondary_layout.into(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 1010, version = 2, with = RmpSerde, from = v1::DbLaunchSecondaryAppPreset)]
+pub struct DbLaunchSecondaryAppPreset {
+    #[primary_key]
+    pub id: ActionId,
+    pub preset: SecondaryAppPresetId,
+    pub windowing_behavior: DbSecondaryAppWindowingBehavior,
+    pub screen_preference: DbSecondaryAppScreenPreference,
+    pub exit_policy: DbSecondaryAppExitPolicy,
+}
+
+impl From<v1::DbLaunchSecondaryAppPreset> for DbLaunchSecondaryAppPreset {
+    fn from(value: v1::DbLaunchSecondaryAppPreset) -> Self {
+        Self {
+            id: value.id,
+            preset: value.preset,
+            windowing_behavior: value.windowing_behavior,
+            screen_preference: value.screen_preference,
+            exit_policy: Default::default(),
+        }
+    }
+}
+
+impl From<DbLaunchSecondaryAppPreset> for v1::DbLaunchSecondaryAppPreset {
+    fn from(value: DbLaunchSecondaryAppPreset) -> Self {
+        Self {
+            id: value.id,
+            preset: value.preset,
+            windowing_behavior: value.windowing_behavior,
+            screen_preference: value.screen_preference,
+        }
+    }
+}
+
+impl From<LaunchSecondaryAppPreset> for DbLaunchSecondaryAppPreset {
+    fn from(value: LaunchSecondaryAppPreset) -> Self {
+        Self {
+            id: value.id,
+            preset: value.preset,
+            windowing_behavior: value.windowing_behavior.into(),
+            screen_preference: value.screen_preference.into(),
+            exit_policy: value.exit_policy.into(),
+        }
+    }
+}
+
+impl From<DbLaunchSecondaryAppPreset> for LaunchSecondaryAppPreset {
+    fn from(value: DbLaunchSecondaryAppPreset) -> Self {
+        Self {
+            id: value.id,
+            preset: value.preset,
+            windowing_behavior: value.windowing_behavior.into(),
+            screen_preference: value.screen_preference.into(),
+            exit_policy: value.exit_policy.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[native_db]
+#[native_model(id = 1009, version = 2, with = RmpSerde, from = v1::DbLaunchSecondaryFlatpakApp)]
+pub struct DbLaunchSecondaryFlatpakApp {
+    #[primary_key]
+    pub id: ActionId,
+    pub app: DbSecondaryFlatpakApp,
+    pub windowing_behavior: DbSecondaryAppWindowingBehavior,
+    pub screen_preference: DbSecondaryAppScreenPreference,
+    pub exit_policy: DbSecondaryAppExitPolicy,
+}
+
+impl From<v1::DbLaunchSecondaryFlatpakApp> for DbLaunchSecondaryFlatpakApp {
+    fn from(value: v1::DbLaunchSecondaryFlatpakApp) -> Self {
+        Self {
+            id: value.id,
+            app: value.app,
+            windowing_behavior: value.windowing_behavior,
+            screen_preference: value.screen_preference,
+            exit_policy: Default::default(),
+        }
+    }
+}
+
+impl From<DbLaunchSecondaryFlatpakApp> for v1::DbLaunchSecondaryFlatpakApp {
+    fn from(value: DbLaunchSecondaryFlatpakApp) -> Self {
+        Self {
+            id: value.id,
+            app: value.app,
+            windowing_behavior: value.windowing_behavior,
+            screen_preference: value.screen_preference,
+        }
+    }
+}
+
+impl From<LaunchSecondaryFlatpakApp> for DbLaunchSecondaryFlatpakApp {
+    fn from(value: LaunchSecondaryFlatpakApp) -> Self {
+        Self {
+            id: value.id,
+            app: value.app.into(),
+            windowing_behavior: value.windowing_behavior.into(),
+            screen_preference: value.screen_preference.into(),
+            exit_policy: value.exit_policy.into(),
+        }
+    }
+}
+
+impl From<DbLaunchSecondaryFlatpakApp> for LaunchSecondaryFlatpakApp {
+    fn from(value: DbLaunchSecondaryFlatpakApp) -> Self {
+        Self {
+            id: value.id,
+            app: value.app.into(),
+            windowing_behavior: value.windowing_behavior.into(),
+            screen_preference: value.screen_preference.into(),
+            exit_policy: value.exit_policy.into(),
+        }
+    }
+}
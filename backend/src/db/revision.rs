@@ -0,0 +1,552 @@
+//! Revision history of saved profile and app override pipelines, so a bad edit
+//! can be diffed against and undone.
+
+use std::{
+    collections::BTreeSet,
+    sync::atomic::Ordering,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use native_db::transaction::{RTransaction, RwTransaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum::{Display, EnumString};
+
+use crate::{
+    macros::newtype_uuid,
+    pipeline::{
+        action::ErasedPipelineAction,
+        data::{ConfigSelection, PipelineDefinition},
+    },
+    settings::{AppId, CategoryProfile, ProfileId},
+};
+
+use super::{
+    backup::BackupReason,
+    bulk::detached,
+    model::{DbProfileRevision, DbProfileRevisionKey},
+    ProfileDb,
+};
+
+newtype_uuid!(RevisionId);
+
+/// The default number of revisions kept per profile and per app override.
+pub const DEFAULT_REVISION_RETENTION: usize = 20;
+
+/// The API call that saved a revision.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Display, EnumString,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum RevisionSource {
+    CreateProfile,
+    SetProfile,
+    SetAppProfileOverride,
+    ImportProfiles,
+    RestoreRevision,
+    BulkAppOverrides,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ProfileRevision {
+    pub id: RevisionId,
+    pub profile_id: ProfileId,
+    /// Set if the revision is of an app override, rather than the profile itself
+    pub app_id: Option<AppId>,
+    /// Creation time, in milliseconds since the unix epoch
+    pub created: u64,
+    pub source: RevisionSource,
+    /// The saved pipeline, detached from the ids it is stored under
+    pub pipeline: PipelineDefinition,
+}
+
+/// A single changed field between two revisions.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct RevisionChange {
+    /// Path to the changed field. Lists of items with ids, like toplevel definitions,
+    /// are keyed by id rather than position.
+    pub path: Vec<String>,
+    /// The previous value; unset if the field was added
+    pub before: Option<Value>,
+    /// The new value; unset if the field was removed
+    pub after: Option<Value>,
+}
+
+impl ProfileRevision {
+    fn load(value: DbProfileRevision, ro: &RTransaction) -> Result<Self> {
+        Ok(Self {
+            id: value.id,
+            profile_id: value.profile_id,
+            app_id: value.app_id,
+            created: value.created,
+            source: value.source.parse()?,
+            pipeline: detached(value.pipeline.transform(ro)?),
+        })
+    }
+}
+
+impl ProfileDb {
+    /// Sets the number of revisions kept per profile and per app override. Revisions
+    /// are not recorded if 0.
+    pub fn set_revision_retention(&self, retention: usize) {
+        self.revision_retention.store(retention, Ordering::Relaxed);
+    }
+
+    /// Lists the revisions of a profile, or of its override for `app_id` if set, newest first.
+    pub fn list_revisions(
+        &self,
+        profile_id: ProfileId,
+        app_id: Option<&AppId>,
+    ) -> Result<Vec<ProfileRevision>> {
+        let ro = self.read_only();
+
+        let mut revisions = ro
+            .scan()
+            .secondary::<DbProfileRevision>(DbProfileRevisionKey::profile_id)?
+            .range(profile_id..=profile_id)?
+            .filter_map(|revision| revision.ok())
+            .filter(|revision| revision.app_id.as_ref() == app_id)
+            .filter_map(|revision| {
+                let id = revision.id;
+
+                // a broken revision shouldn't hide the rest of the history
+                ProfileRevision::load(revision, &ro)
+                    .inspect_err(|err| log::warn!("skipping unreadable revision {id:?}: {err:#}"))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+
+        revisions.sort_by(|a, b| b.created.cmp(&a.created));
+
+        Ok(revisions)
+    }
+
+    pub fn get_revision(&self, id: RevisionId) -> Result<ProfileRevision> {
+        let ro = self.read_only();
+
+        let revision = ro
+            .get()
+            .primary::<DbProfileRevision>(id)?
+            .with_context(|| format!("revision {id:?} not found"))?;
+
+        ProfileRevision::load(revision, &ro)
+    }
+
+    /// Lists the fields changed from revision `from` to revision `to`.
+    pub fn diff_revisions(&self, from: RevisionId, to: RevisionId) -> Result<Vec<RevisionChange>> {
+        let from = self.get_revision(from)?;
+        let to = self.get_revision(to)?;
+
+        let mut changes = vec![];
+        diff_values(
+            &mut vec![],
+            Some(&keyed(serde_json::to_value(from.pipeline)?)),
+            Some(&keyed(serde_json::to_value(to.pipeline)?)),
+            &mut changes,
+        );
+
+        Ok(changes)
+    }
+
+    /// Saves the pipeline of revision `id` over its profile or app override. The
+    /// restore is recorded as a new revision, so it can be undone in turn.
+    pub fn restore_revision(&self, id: RevisionId) -> Result<()> {
+        let revision = self.get_revision(id)?;
+
+        let profile = self
+            .get_profile(&revision.profile_id)?
+            .with_context(|| format!("profile {:?} no longer exists", revision.profile_id))?;
+
+        self.backup(BackupReason::BeforeRevisionRestore)
+            .context("failed to back up profiles before restoring a revision")?;
+
+        match revision.app_id {
+            Some(app_id) => {
+                let current = self.get_app_profile(&app_id)?.overrides.remove(&profile.id);
+                let pipeline = match current {
+                    Some(current) => with_ids_of(revision.pipeline, &current),
+                    None => revision.pipeline,
+                };
+
+                self.save_app_profile_override(
+                    app_id,
+                    profile.id,
+                    pipeline,
+                    RevisionSource::RestoreRevision,
+                )
+            }
+            None => self.save_profile(
+                CategoryProfile {
+                    pipeline: with_ids_of(revision.pipeline, &profile.pipeline),
+                    ..profile
+                },
+                RevisionSource::RestoreRevision,
+            ),
+        }
+    }
+
+    /// Records a revision of a pipeline saved in `rw`, pruning revisions past the retention
+    /// count. Saves identical to the latest revision are not recorded.
+    pub(super) fn record_revision(
+        &self,
+        rw: &RwTransaction,
+        profile_id: ProfileId,
+        app_id: Option<AppId>,
+        source: RevisionSource,
+        pipeline: &PipelineDefinition,
+    ) -> Result<()> {
+        let retention = self.revision_retention.load(Ordering::Relaxed);
+        if retention == 0 {
+            return Ok(());
+        }
+
+        let mut existing = rw
+            .scan()
+            .secondary::<DbProfileRevision>(DbProfileRevisionKey::profile_id)?
+            .range(profile_id..=profile_id)?
+            .filter_map(|revision| revision.ok())
+            .filter(|revision| revision.app_id == app_id)
+            .collect::<Vec<_>>();
+
+        existing.sort_by(|a, b| b.created.cmp(&a.created));
+
+        // the latest revision was committed by an earlier save
+        let latest = existing
+            .first()
+            .and_then(|latest| latest.pipeline.transform(&self.read_only()).ok());
+
+        // compared as values, since map fields serialize in arbitrary order
+        let value = serde_json::to_value(detached(pipeline.clone()))?;
+        if latest.is_some_and(|latest| {
+            serde_json::to_value(detached(latest)).is_ok_and(|latest| latest == value)
+        }) {
+            return Ok(());
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+
+        // keep revisions saved in quick succession ordered
+        let created = existing
+            .first()
+            .map_or(now, |latest| now.max(latest.created + 1));
+
+        rw.insert(DbProfileRevision {
+            id: RevisionId::new(),
+            profile_id,
+            app_id,
+            created,
+            source: source.to_string(),
+            pipeline: detached(pipeline.clone())
+                .with_new_ids()
+                .save_all_and_transform(rw)?,
+        })?;
+
+        for revision in existing.into_iter().skip(retention - 1) {
+            revision.remove_all(rw)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes all revisions of a profile and its app overrides.
+pub(super) fn remove_revisions(rw: &RwTransaction, profile_id: &ProfileId) -> Result<()> {
+    let revisions = rw
+        .scan()
+        .secondary::<DbProfileRevision>(DbProfileRevisionKey::profile_id)?
+        .range(*profile_id..=*profile_id)?
+        .filter_map(|revision| revision.ok())
+        .collect::<Vec<_>>();
+
+    for revision in revisions {
+        revision.remove_all(rw)?;
+    }
+
+    Ok(())
+}
+
+/// Takes the ids of `current` for the actions `pipeline` shares with it, so restoring a
+/// revision overwrites the saved actions rather than leaving them behind.
+fn with_ids_of(
+    mut pipeline: PipelineDefinition,
+    current: &PipelineDefinition,
+) -> PipelineDefinition {
+    pipeline.id = current.id;
+
+    let current_toplevel = std::iter::once(&current.platform)
+        .chain(current.toplevel.iter())
+        .collect::<Vec<_>>();
+
+    for toplevel in std::iter::once(&mut pipeline.platform).chain(pipeline.toplevel.iter_mut()) {
+        let Some(current) = current_toplevel.iter().find(|tl| tl.id == toplevel.id) else {
+            continue;
+        };
+
+        for (id, settings) in toplevel.actions.actions.iter_mut() {
+            let current = current.actions.actions.get(id).map(|s| &s.selection);
+
+            if let (ConfigSelection::Action(action), Some(ConfigSelection::Action(current))) =
+                (&mut settings.selection, current)
+            {
+                if action.get_type() == current.get_type() {
+                    *action = action.cloned_with_id(current.get_id());
+                }
+            }
+        }
+    }
+
+    pipeline
+}
+
+/// Replaces lists of objects that all have an `id` with objects keyed by that id, so
+/// reordering or removing an item doesn't show up as a change to every item after it.
+fn keyed(value: Value) -> Value {
+    match value {
+        Value::Array(items) => {
+            let ids = items
+                .iter()
+                .map(|item| item.get("id").and_then(Value::as_str).map(str::to_string))
+                .collect::<Option<Vec<_>>>();
+
+            match ids {
+                Some(ids) if !items.is_empty() => {
+                    Value::Object(ids.into_iter().zip(items.into_iter().map(keyed)).collect())
+                }
+                _ => Value::Array(items.into_iter().map(keyed).collect()),
+            }
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, keyed(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+fn diff_values(
+    path: &mut Vec<String>,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<RevisionChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let keys = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+
+            for key in keys {
+                path.push(key.clone());
+                diff_values(path, before.get(key), after.get(key), changes);
+                path.pop();
+            }
+        }
+        (before, after) if before != after => changes.push(RevisionChange {
+            path: path.clone(),
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use serde_json::json;
+
+    use crate::{
+        pipeline::{
+            action_registar::PipelineActionRegistrar,
+            data::{
+                PipelineActionId, PipelineDefinitionId, PipelineTarget, TopLevelDefinition,
+                TopLevelId,
+            },
+        },
+        util::create_dir_all,
+    };
+
+    use super::*;
+
+    fn cleanup(path: &Path) -> Result<()> {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        let backups = path.with_file_name(format!(
+            "{}_backups",
+            path.file_stem().unwrap().to_string_lossy()
+        ));
+        if backups.exists() {
+            std::fs::remove_dir_all(backups)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_keys_lists_by_id() {
+        let before = json!({
+            "name": "Before",
+            "toplevel": [{"id": "a", "root": "x"}, {"id": "b", "root": "y"}],
+        });
+        let after = json!({
+            "name": "After",
+            "toplevel": [{"id": "b", "root": "z"}],
+        });
+
+        let mut changes = vec![];
+        diff_values(
+            &mut vec![],
+            Some(&keyed(before)),
+            Some(&keyed(after)),
+            &mut changes,
+        );
+
+        assert_eq!(
+            vec![
+                RevisionChange {
+                    path: vec!["name".into()],
+                    before: Some(json!("Before")),
+                    after: Some(json!("After")),
+                },
+                RevisionChange {
+                    path: vec!["toplevel".into(), "a".into()],
+                    before: Some(json!({"id": "a", "root": "x"})),
+                    after: None,
+                },
+                RevisionChange {
+                    path: vec!["toplevel".into(), "b".into(), "root".into()],
+                    before: Some(json!("y")),
+                    after: Some(json!("z")),
+                },
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn test_revision_history() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let path: PathBuf = "test/out/.config/deck-ds/revisions.db".into();
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+        db.set_revision_retention(3);
+
+        let root = PipelineActionId::new("core:citra:platform");
+        let mut profile = db.create_profile(PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "Original".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        })?;
+
+        // saving unchanged doesn't add a revision
+        db.set_profile(profile.clone())?;
+
+        let original = db.list_revisions(profile.id, None)?;
+        assert_eq!(1, original.len());
+        assert_eq!(RevisionSource::CreateProfile, original[0].source);
+
+        profile.pipeline.name = "Edited".into();
+        profile.pipeline.primary_target_override = Some(PipelineTarget::Gamemode);
+        db.set_profile(profile.clone())?;
+
+        let revisions = db.list_revisions(profile.id, None)?;
+        assert_eq!(2, revisions.len());
+
+        let changes = db.diff_revisions(original[0].id, revisions[0].id)?;
+        assert_eq!(
+            vec![vec!["name"], vec!["primary_target_override"]],
+            changes
+                .iter()
+                .map(|change| change.path.iter().map(String::as_str).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+
+        db.restore_revision(original[0].id)?;
+        assert_eq!(
+            BackupReason::BeforeRevisionRestore,
+            db.list_backups()?[0].reason
+        );
+
+        let restored = db.get_profile(&profile.id)?.expect("profile should exist");
+        assert_eq!("Original", restored.pipeline.name);
+        assert_eq!(None, restored.pipeline.primary_target_override);
+
+        let revisions = db.list_revisions(profile.id, None)?;
+        assert_eq!(3, revisions.len());
+        assert_eq!(RevisionSource::RestoreRevision, revisions[0].source);
+
+        // the oldest revision is pruned past the retention count
+        profile.pipeline.name = "Pruned".into();
+        db.set_profile(profile.clone())?;
+
+        let revisions = db.list_revisions(profile.id, None)?;
+        assert_eq!(3, revisions.len());
+        assert!(revisions.iter().all(|r| r.id != original[0].id));
+
+        drop(db);
+        cleanup(&path)
+    }
+
+    #[test]
+    fn test_list_skips_unreadable_revisions() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let path: PathBuf = "test/out/.config/deck-ds/revisions_unreadable.db".into();
+        cleanup(&path)?;
+        create_dir_all(path.parent().unwrap())?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+
+        let root = PipelineActionId::new("core:citra:platform");
+        let mut profile = db.create_profile(PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "Original".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        })?;
+
+        profile.pipeline.name = "Edited".into();
+        db.set_profile(profile.clone())?;
+
+        let revisions = db.list_revisions(profile.id, None)?;
+        assert_eq!(2, revisions.len());
+
+        {
+            let rw = db.read_write();
+            let mut broken = rw
+                .get()
+                .primary::<DbProfileRevision>(revisions[1].id)?
+                .expect("revision should exist");
+            broken.source = "unknown_source".into();
+            rw.upsert(broken)?;
+            rw.commit()?;
+        }
+
+        let readable = db.list_revisions(profile.id, None)?;
+        assert_eq!(vec![revisions[0].clone()], readable);
+
+        drop(db);
+        cleanup(&path)
+    }
+}
@@ -0,0 +1,427 @@
+//! Ordered rules that pick the default profile for an app from its metadata, for apps
+//! without a manually chosen default profile.
+
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    macros::newtype_uuid,
+    settings::{AppId, ProfileId},
+};
+
+use super::{
+    model::{DbAppSettings, DbProfileRules},
+    ProfileDb,
+};
+
+newtype_uuid!(ProfileRuleId);
+
+pub(super) const PROFILE_RULES_KEY: u8 = 0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileRule {
+    pub id: ProfileRuleId,
+    pub name: String,
+    /// The profile selected when the rule matches
+    pub profile_id: ProfileId,
+    /// Conditions that must all match; a rule without conditions matches every app
+    pub conditions: Vec<RuleCondition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value")]
+pub enum RuleCondition {
+    /// The app is in the Steam collection with this name
+    Collection(String),
+    /// The app has this Steam store tag
+    Tag(String),
+    /// Regex matched against the app title
+    TitleRegex(String),
+    /// Whether the app is a Steam game, rather than a non-Steam shortcut
+    IsSteamGame(bool),
+    /// Regex matched against the launching executable
+    ExecutableRegex(String),
+}
+
+/// What Steam knows about an app, as sent by the frontend.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct AppMetadata {
+    pub app_id: AppId,
+    pub title: String,
+    pub is_steam_game: bool,
+    #[serde(default)]
+    pub collections: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The launching executable, if known
+    #[serde(default)]
+    pub executable: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct RuleEvaluation {
+    pub rule_id: ProfileRuleId,
+    /// Whether each condition matched, in order
+    pub conditions: Vec<bool>,
+    pub matched: bool,
+    /// Set if the rule matched, but its profile no longer exists, so it was skipped
+    pub missing_profile: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "type", content = "value")]
+pub enum ProfileSelectionSource {
+    /// The default profile set for the app, which takes precedence over rules
+    Manual,
+    Rule(ProfileRuleId),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ProfileSelection {
+    pub profile_id: Option<ProfileId>,
+    pub source: Option<ProfileSelectionSource>,
+    /// The evaluated rules, in order, up to and including the first match
+    pub evaluations: Vec<RuleEvaluation>,
+}
+
+/// A rule with its regexes compiled, so they aren't rebuilt for every evaluation.
+#[derive(Debug, Clone)]
+pub(super) struct CompiledRule {
+    rule: ProfileRule,
+    conditions: Vec<CompiledCondition>,
+}
+
+#[derive(Debug, Clone)]
+enum CompiledCondition {
+    Collection(String),
+    Tag(String),
+    TitleRegex(Regex),
+    IsSteamGame(bool),
+    ExecutableRegex(Regex),
+}
+
+impl TryFrom<ProfileRule> for CompiledRule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: ProfileRule) -> Result<Self> {
+        let conditions = rule
+            .conditions
+            .iter()
+            .map(CompiledCondition::try_from)
+            .collect::<Result<_>>()?;
+
+        Ok(Self { rule, conditions })
+    }
+}
+
+impl TryFrom<&RuleCondition> for CompiledCondition {
+    type Error = anyhow::Error;
+
+    fn try_from(condition: &RuleCondition) -> Result<Self> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern).with_context(|| format!("invalid rule regex {pattern:?}"))
+        };
+
+        Ok(match condition {
+            RuleCondition::Collection(collection) => Self::Collection(collection.to_lowercase()),
+            RuleCondition::Tag(tag) => Self::Tag(tag.to_lowercase()),
+            RuleCondition::TitleRegex(pattern) => Self::TitleRegex(compile(pattern)?),
+            RuleCondition::IsSteamGame(is_steam_game) => Self::IsSteamGame(*is_steam_game),
+            RuleCondition::ExecutableRegex(pattern) => Self::ExecutableRegex(compile(pattern)?),
+        })
+    }
+}
+
+impl CompiledCondition {
+    fn matches(&self, app: &AppMetadata) -> bool {
+        let contains =
+            |values: &[String], value: &str| values.iter().any(|v| v.to_lowercase() == value);
+
+        match self {
+            Self::Collection(collection) => contains(&app.collections, collection),
+            Self::Tag(tag) => contains(&app.tags, tag),
+            Self::TitleRegex(regex) => regex.is_match(&app.title),
+            Self::IsSteamGame(is_steam_game) => app.is_steam_game == *is_steam_game,
+            Self::ExecutableRegex(regex) => app
+                .executable
+                .as_ref()
+                .is_some_and(|exe| regex.is_match(exe)),
+        }
+    }
+}
+
+impl ProfileDb {
+    /// Gets the profile selection rules, in the order they are evaluated.
+    pub fn get_profile_rules(&self) -> Result<Vec<ProfileRule>> {
+        Ok(self
+            .compiled_rules()?
+            .iter()
+            .map(|compiled| compiled.rule.clone())
+            .collect())
+    }
+
+    /// Replaces the profile selection rules. Rules without an id are given one.
+    pub fn set_profile_rules(&self, mut rules: Vec<ProfileRule>) -> Result<Vec<ProfileRule>> {
+        let mut ids = HashSet::new();
+
+        for rule in rules.iter_mut() {
+            if rule.id == ProfileRuleId::nil() {
+                rule.id = ProfileRuleId::new();
+            }
+
+            anyhow::ensure!(ids.insert(rule.id), "duplicate rule id {:?}", rule.id);
+            anyhow::ensure!(
+                self.get_profile(&rule.profile_id)?.is_some(),
+                "Failed to find profile for {:?}",
+                rule.profile_id
+            );
+        }
+
+        let compiled = rules
+            .iter()
+            .cloned()
+            .map(CompiledRule::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        // held across the write, so a concurrent load can't cache the old rules
+        let mut cache = self
+            .rules_cache
+            .lock()
+            .expect("rules cache mutex should not be poisoned");
+
+        let rw = self.read_write();
+        rw.upsert(DbProfileRules {
+            id: PROFILE_RULES_KEY,
+            rules: rules.iter().cloned().map(Into::into).collect(),
+        })?;
+        rw.commit()?;
+
+        *cache = Some(Arc::new(compiled));
+
+        Ok(rules)
+    }
+
+    /// The compiled rules, loaded on first use. Stored rules that no longer compile
+    /// are skipped.
+    fn compiled_rules(&self) -> Result<Arc<Vec<CompiledRule>>> {
+        let mut cache = self
+            .rules_cache
+            .lock()
+            .expect("rules cache mutex should not be poisoned");
+
+        if let Some(rules) = cache.as_ref() {
+            return Ok(rules.clone());
+        }
+
+        let rules = self
+            .read_only()
+            .get()
+            .primary::<DbProfileRules>(PROFILE_RULES_KEY)?
+            .map_or(vec![], |rules| rules.rules)
+            .into_iter()
+            .filter_map(|rule| {
+                CompiledRule::try_from(ProfileRule::from(rule))
+                    .inspect_err(|err| log::warn!("skipping profile rule: {err:#}"))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+
+        Ok(cache.insert(Arc::new(rules)).clone())
+    }
+
+    /// Selects the default profile for an app, explaining which rule matched. The
+    /// manually chosen default profile, if any, takes precedence over the rules.
+    pub fn select_profile(&self, app: &AppMetadata) -> Result<ProfileSelection> {
+        let manual = self
+            .read_only()
+            .get()
+            .primary::<DbAppSettings>(app.app_id.clone())?
+            .and_then(|settings| settings.default_profile);
+
+        let mut evaluations = vec![];
+        let mut matched = None;
+
+        for CompiledRule { rule, conditions } in self.compiled_rules()?.iter() {
+            let conditions = conditions
+                .iter()
+                .map(|c| c.matches(app))
+                .collect::<Vec<_>>();
+            let is_match = conditions.iter().all(|c| *c);
+            let missing_profile = is_match && self.get_profile(&rule.profile_id)?.is_none();
+
+            evaluations.push(RuleEvaluation {
+                rule_id: rule.id,
+                conditions,
+                matched: is_match && !missing_profile,
+                missing_profile,
+            });
+
+            if is_match && !missing_profile {
+                matched = Some(rule.clone());
+                break;
+            }
+        }
+
+        let (profile_id, source) = match (manual, matched) {
+            (Some(profile_id), _) => (Some(profile_id), Some(ProfileSelectionSource::Manual)),
+            (None, Some(rule)) => (
+                Some(rule.profile_id),
+                Some(ProfileSelectionSource::Rule(rule.id)),
+            ),
+            (None, None) => (None, None),
+        };
+
+        Ok(ProfileSelection {
+            profile_id,
+            source,
+            evaluations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        pipeline::{
+            action_registar::PipelineActionRegistrar,
+            data::{
+                PipelineActionId, PipelineDefinition, PipelineDefinitionId, TopLevelDefinition,
+                TopLevelId,
+            },
+        },
+        util::create_dir_all,
+    };
+
+    use super::*;
+
+    fn app(title: &str, is_steam_game: bool) -> AppMetadata {
+        AppMetadata {
+            app_id: AppId::new("12345"),
+            title: title.into(),
+            is_steam_game,
+            collections: vec!["Nintendo DS".into()],
+            tags: vec!["RPG".into()],
+            executable: Some("/usr/bin/melonDS".into()),
+        }
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let app = app("Pokemon Black", false);
+
+        let cases = [
+            (RuleCondition::Collection("nintendo ds".into()), true),
+            (RuleCondition::Collection("3DS".into()), false),
+            (RuleCondition::Tag("rpg".into()), true),
+            (RuleCondition::TitleRegex("^Pokemon".into()), true),
+            (RuleCondition::TitleRegex("Zelda".into()), false),
+            (RuleCondition::IsSteamGame(false), true),
+            (RuleCondition::IsSteamGame(true), false),
+            (RuleCondition::ExecutableRegex("melonDS$".into()), true),
+            (RuleCondition::ExecutableRegex("[".into()), false),
+        ];
+
+        for (condition, expected) in cases {
+            let matches = CompiledCondition::try_from(&condition).is_ok_and(|c| c.matches(&app));
+            assert_eq!(expected, matches, "{condition:?}");
+        }
+    }
+
+    #[test]
+    fn test_select_profile() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let path: PathBuf = "test/out/.config/deck-ds/profile_rules.db".into();
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        create_dir_all(path.parent().unwrap())?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+
+        let root = PipelineActionId::new("core:melonds:platform");
+        let profile_id = db
+            .create_profile(PipelineDefinition {
+                id: PipelineDefinitionId::nil(),
+                name: "melonDS".into(),
+                primary_target_override: None,
+                platform: TopLevelDefinition {
+                    id: TopLevelId::nil(),
+                    actions: registrar.make_lookup(&root),
+                    root,
+                },
+                toplevel: vec![],
+                desktop_controller_layout_hack: Default::default(),
+            })?
+            .id;
+
+        let invalid = ProfileRule {
+            id: ProfileRuleId::nil(),
+            name: "Invalid".into(),
+            profile_id,
+            conditions: vec![RuleCondition::TitleRegex("[".into())],
+        };
+        assert!(db.set_profile_rules(vec![invalid]).is_err());
+
+        let rules = db.set_profile_rules(vec![
+            ProfileRule {
+                id: ProfileRuleId::nil(),
+                name: "Steam games".into(),
+                profile_id,
+                conditions: vec![RuleCondition::IsSteamGame(true)],
+            },
+            ProfileRule {
+                id: ProfileRuleId::nil(),
+                name: "DS collection".into(),
+                profile_id,
+                conditions: vec![
+                    RuleCondition::Collection("Nintendo DS".into()),
+                    RuleCondition::IsSteamGame(false),
+                ],
+            },
+        ])?;
+        assert_eq!(rules, db.get_profile_rules()?);
+
+        let app = app("Pokemon Black", false);
+
+        let selection = db.select_profile(&app)?;
+        assert_eq!(
+            ProfileSelection {
+                profile_id: Some(profile_id),
+                source: Some(ProfileSelectionSource::Rule(rules[1].id)),
+                evaluations: vec![
+                    RuleEvaluation {
+                        rule_id: rules[0].id,
+                        conditions: vec![false],
+                        matched: false,
+                        missing_profile: false,
+                    },
+                    RuleEvaluation {
+                        rule_id: rules[1].id,
+                        conditions: vec![true, true],
+                        matched: true,
+                        missing_profile: false,
+                    },
+                ],
+            },
+            selection
+        );
+
+        db.set_app_profile_settings(app.app_id.clone(), Some(profile_id))?;
+        let selection = db.select_profile(&app)?;
+        assert_eq!(Some(ProfileSelectionSource::Manual), selection.source);
+
+        drop(db);
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
@@ -1,18 +1,28 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, path::Path};
 
 newtype_strid!("Steam App Identifier", AppId);
 
+use anyhow::{Context, Result};
+use native_db::transaction::RTransaction;
+
 use crate::{
+    asset::{AssetDirEntry, AssetManager},
     macros::newtype_strid,
     pipeline::{
         action_registar::PipelineActionRegistrar,
         data::{
             PipelineActionId, PipelineActionLookup, PipelineDefinition, PipelineDefinitionId,
-            PipelineTarget, Template, TemplateId, TopLevelDefinition, TopLevelId,
+            PipelineTarget, Template, TemplateId, TemplateSource, TopLevelDefinition, TopLevelId,
         },
     },
+    settings::ProfileId,
 };
 
+use super::{bulk::detached, bundle::is_known_action, model::DbTemplate, ProfileDb};
+
+/// Dir of template files, in both the asset dir and the settings dir.
+const TEMPLATE_DIR: &str = "templates";
+
 pub fn build_templates(registrar: PipelineActionRegistrar) -> Vec<Template> {
     struct TemplateBuilder {
         id: TemplateId,
@@ -31,6 +41,8 @@ pub fn build_templates(registrar: PipelineActionRegistrar) -> Vec<Template> {
             Template {
                 id: self.id,
                 tags: self.tags,
+                description: None,
+                source: TemplateSource::Builtin,
                 pipeline: PipelineDefinition {
                     id: PipelineDefinitionId::nil(),
                     name: root_action.name.clone(),
@@ -113,3 +125,310 @@ pub fn build_templates(registrar: PipelineActionRegistrar) -> Vec<Template> {
 
     templates.into_iter().map(|t| t.build(&registrar)).collect()
 }
+
+/// Loads the templates in the JSON files of the template asset dir and `settings_dir/templates`.
+/// Each file holds a list of templates. Unparseable files, and templates with an unknown
+/// platform, are skipped.
+pub fn load_template_files(
+    asset_manager: &AssetManager,
+    settings_dir: &Path,
+    registrar: &PipelineActionRegistrar,
+) -> Vec<Template> {
+    let assets = asset_manager
+        .get_dir(TEMPLATE_DIR)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| match entry {
+            AssetDirEntry::Dir(_) => None,
+            AssetDirEntry::File(file) => asset_manager.get_file(file),
+        })
+        .filter_map(|asset| {
+            let path = asset.file_path().ok();
+            let contents = asset
+                .contents_to_string()
+                .inspect_err(|err| log::warn!("failed to read templates at {path:?}: {err}"))
+                .ok()?;
+
+            Some((format!("{path:?}"), contents.into_owned()))
+        });
+
+    let settings_dir = settings_dir.join(TEMPLATE_DIR);
+    let settings = std::fs::read_dir(&settings_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let contents = std::fs::read_to_string(&path)
+                .inspect_err(|err| log::warn!("failed to read templates at {path:?}: {err}"))
+                .ok()?;
+
+            Some((format!("{path:?}"), contents))
+        });
+
+    let mut files = assets.chain(settings).collect::<Vec<_>>();
+    files.sort();
+
+    files
+        .into_iter()
+        .flat_map(|(origin, contents)| {
+            serde_json::from_str::<Vec<Template>>(&contents)
+                .inspect_err(|err| {
+                    log::warn!("failed to parse templates at {origin}; ignoring templates: {err}")
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .filter(move |t| {
+                    let known = is_known_action(registrar, &t.pipeline.platform.root);
+                    if !known {
+                        log::warn!(
+                            "ignoring template {:?} at {origin} with unknown platform {:?}",
+                            t.id,
+                            t.pipeline.platform.root
+                        );
+                    }
+                    known
+                })
+        })
+        .map(|t| Template {
+            source: TemplateSource::File,
+            ..t
+        })
+        .collect()
+}
+
+impl Template {
+    fn load(value: DbTemplate, ro: &RTransaction) -> Result<Self> {
+        Ok(Self {
+            id: value.id,
+            pipeline: detached(value.pipeline.transform(ro)?),
+            tags: value.tags,
+            description: value.description,
+            source: TemplateSource::User,
+        })
+    }
+}
+
+impl ProfileDb {
+    /// Adds templates loaded from files. Templates with the id of an existing template are ignored.
+    pub fn add_file_templates(&mut self, templates: Vec<Template>) {
+        for template in templates {
+            if self.templates.iter().any(|t| t.id == template.id) {
+                log::warn!("ignoring template file with duplicate id {:?}", template.id);
+            } else {
+                self.templates.push(template);
+            }
+        }
+    }
+
+    /// Gets built-in, file, and user templates, in that order.
+    pub fn get_templates(&self) -> Result<Vec<Template>> {
+        let ro = self.read_only();
+
+        // a broken user template shouldn't hide the others
+        let mut user = ro
+            .scan()
+            .primary::<DbTemplate>()?
+            .all()?
+            .filter_map(|t| {
+                t.map_err(anyhow::Error::from)
+                    .and_then(|t| {
+                        let id = t.id;
+                        Template::load(t, &ro).with_context(|| format!("template {id:?}"))
+                    })
+                    .inspect_err(|err| log::warn!("skipping unreadable user template: {err:#}"))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        user.sort_by(|a, b| a.pipeline.name.cmp(&b.pipeline.name));
+
+        Ok(self.templates.iter().cloned().chain(user).collect())
+    }
+
+    pub fn get_template(&self, id: &TemplateId) -> Result<Option<Template>> {
+        if let Some(template) = self.templates.iter().find(|t| t.id == *id) {
+            return Ok(Some(template.clone()));
+        }
+
+        let ro = self.read_only();
+
+        ro.get()
+            .primary::<DbTemplate>(*id)?
+            .map(|t| Template::load(t, &ro))
+            .transpose()
+    }
+
+    /// Saves the pipeline of a profile as a new user template.
+    pub fn create_template(
+        &self,
+        profile_id: ProfileId,
+        description: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<Template> {
+        let profile = self
+            .get_profile(&profile_id)?
+            .with_context(|| format!("Failed to find profile for {profile_id:?}"))?;
+
+        let template = Template {
+            id: TemplateId::new(),
+            pipeline: detached(profile.pipeline),
+            tags,
+            description,
+            source: TemplateSource::User,
+        };
+
+        self.save_template(&template)?;
+
+        Ok(template)
+    }
+
+    /// Updates the description and tags of a user template.
+    pub fn update_template(
+        &self,
+        id: &TemplateId,
+        description: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<Template> {
+        let template = self.get_user_template(id)?;
+
+        let template = Template {
+            description,
+            tags,
+            ..template
+        };
+
+        self.save_template(&template)?;
+
+        Ok(template)
+    }
+
+    /// Deletes a user template. Built-in and file templates can't be deleted.
+    pub fn delete_template(&self, id: &TemplateId) -> Result<()> {
+        self.get_user_template(id)?;
+
+        let rw = self.read_write();
+        if let Some(template) = rw.get().primary::<DbTemplate>(*id)? {
+            template.remove_all(&rw)?;
+        }
+
+        Ok(rw.commit()?)
+    }
+
+    fn get_user_template(&self, id: &TemplateId) -> Result<Template> {
+        let template = self
+            .get_template(id)?
+            .with_context(|| format!("Failed to find template for {id:?}"))?;
+
+        anyhow::ensure!(
+            template.source == TemplateSource::User,
+            "{:?} template {id:?} can't be changed",
+            template.source
+        );
+
+        Ok(template)
+    }
+
+    fn save_template(&self, template: &Template) -> Result<()> {
+        let rw = self.read_write();
+
+        if let Some(existing) = rw.get().primary::<DbTemplate>(template.id)? {
+            existing.remove_all(&rw)?;
+        }
+
+        rw.insert(DbTemplate {
+            id: template.id,
+            tags: template.tags.clone(),
+            description: template.description.clone(),
+            pipeline: detached(template.pipeline.clone())
+                .with_new_ids()
+                .save_all_and_transform(&rw)?,
+        })?;
+
+        Ok(rw.commit()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{decky_env::DeckyEnv, util::create_dir_all};
+
+    use super::*;
+
+    #[test]
+    fn test_user_templates() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+        let path: PathBuf = "test/out/.config/deck-ds/user_templates.db".into();
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        create_dir_all(path.parent().unwrap())?;
+
+        let db = ProfileDb::new(path.clone(), registrar.clone())?;
+        let builtin = db.get_templates()?;
+
+        let profile = db.create_profile(builtin[0].pipeline.clone())?;
+        let template =
+            db.create_template(profile.id, Some("Mine".into()), vec!["Custom".into()])?;
+
+        assert_eq!(TemplateSource::User, template.source);
+        assert_eq!(PipelineDefinitionId::nil(), template.pipeline.id);
+        assert_eq!(Some(&template), db.get_templates()?.last());
+
+        let updated = db.update_template(&template.id, None, vec![])?;
+        assert_eq!(Some(updated), db.get_template(&template.id)?);
+
+        assert!(db.delete_template(&builtin[0].id).is_err());
+        assert!(db.update_template(&builtin[0].id, None, vec![]).is_err());
+
+        db.delete_template(&template.id)?;
+        assert_eq!(None, db.get_template(&template.id)?);
+        assert_eq!(builtin.len(), db.get_templates()?.len());
+
+        drop(db);
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_template_files() -> Result<()> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let decky_env = DeckyEnv::new_test("template_files");
+
+        let builtin = build_templates(registrar.clone());
+        let mut known = builtin[0].clone();
+        known.id = TemplateId::new();
+        let mut unknown = builtin[0].clone();
+        unknown.id = TemplateId::new();
+        unknown.pipeline.platform.root = PipelineActionId::new("plugin:unknown:platform");
+
+        let dir = decky_env.decky_plugin_settings_dir.join(TEMPLATE_DIR);
+        create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("templates.json"),
+            serde_json::to_string(&vec![known.clone(), unknown])?,
+        )?;
+        std::fs::write(dir.join("invalid.json"), "not json")?;
+
+        let loaded = load_template_files(
+            &decky_env.asset_manager(),
+            &decky_env.decky_plugin_settings_dir,
+            &registrar,
+        );
+
+        assert_eq!(
+            vec![known.id],
+            loaded.iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+        assert_eq!(TemplateSource::File, loaded[0].source);
+
+        std::fs::remove_dir_all(decky_env.deck_user_home)?;
+
+        Ok(())
+    }
+}
@@ -46,7 +46,12 @@ impl DeckyEnv {
                     .map(|v| v.env)
                     .unwrap_or(env)
             }
-            AppModes::Serve => Self {
+            AppModes::Serve
+            | AppModes::ExportProfiles { .. }
+            | AppModes::ImportProfiles { .. }
+            | AppModes::ListProfileBackups
+            | AppModes::RestoreProfileBackup { .. }
+            | AppModes::MigrateProfiles { .. } => Self {
                 decky_user: decky::user().unwrap_or(default.decky_user),
                 deck_user_home: std::env::var("DECKY_USER_HOME")
                     .map(PathBuf::from)
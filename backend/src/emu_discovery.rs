@@ -0,0 +1,444 @@
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    pipeline::{
+        action::emu_source::{
+            AppImageSource, CustomEmuSource, EmuDeckSettings, EmuDeckSource, EmuSettingsSource,
+            FlatpakSource, RetroDeckSource,
+        },
+        executor::PipelineContext,
+    },
+    sys::flatpak::{list_installed_flatpaks, FlatpakInfo},
+};
+
+pub mod version;
+
+/// Emulators with settings sources that can be discovered.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Display, EnumIter,
+)]
+pub enum Emulator {
+    Cemu,
+    Citra,
+    Lime3ds,
+    Azahar,
+    MelonDS,
+    Dolphin,
+}
+
+impl Emulator {
+    fn settings_ext(&self) -> &'static str {
+        match self {
+            Emulator::Cemu => "xml",
+            Emulator::MelonDS => "toml",
+            Emulator::Citra | Emulator::Lime3ds | Emulator::Azahar | Emulator::Dolphin => "ini",
+        }
+    }
+
+    /// Settings file, relative to the XDG config dir used by the emulator.
+    fn relative_settings_file(&self) -> &'static str {
+        match self {
+            Emulator::Cemu => "Cemu/settings.xml",
+            Emulator::Citra => "citra-emu/qt-config.ini",
+            Emulator::Lime3ds => "lime3ds-emu/qt-config.ini",
+            Emulator::Azahar => "azahar-emu/qt-config.ini",
+            Emulator::MelonDS => "melonDS/melonDS.toml",
+            Emulator::Dolphin => "dolphin-emu/Dolphin.ini",
+        }
+    }
+
+    /// Resolves the settings file in `config_dir`, falling back to the
+    /// pre-1.0 melonDS INI file if the TOML file doesn't exist.
+    fn settings_file_in(&self, config_dir: &Path) -> PathBuf {
+        let file = config_dir.join(self.relative_settings_file());
+        match self {
+            Emulator::MelonDS if !file.is_file() => {
+                let ini = config_dir.join("melonDS/melonDS.ini");
+                if ini.is_file() {
+                    ini
+                } else {
+                    file
+                }
+            }
+            _ => file,
+        }
+    }
+
+    /// Flatpak settings sources for the emulator, in order of preference.
+    /// Emulators without a `FlatpakSource` fall back to a custom source.
+    fn flatpak_sources(&self) -> &'static [FlatpakSource] {
+        match self {
+            Emulator::Cemu => &[FlatpakSource::Cemu],
+            Emulator::Citra => &[FlatpakSource::Citra],
+            Emulator::Lime3ds => &[FlatpakSource::Lime3ds],
+            Emulator::MelonDS => &[FlatpakSource::MelonDS, FlatpakSource::MelonDSPrerelease],
+            Emulator::Azahar | Emulator::Dolphin => &[],
+        }
+    }
+
+    fn flatpak_app_id(&self) -> &'static str {
+        match self {
+            Emulator::Azahar => "org.azahar_emu.Azahar",
+            Emulator::Dolphin => "org.DolphinEmu.dolphin-emu",
+            _ => self.flatpak_sources()[0].org(),
+        }
+    }
+
+    /// Matches an AppImage file name (e.g. `Cemu-2.0-x86_64.AppImage`) to an emulator.
+    fn from_appimage_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if !name.ends_with(".appimage") {
+            return None;
+        }
+
+        Emulator::iter().find(|emu| name.contains(&emu.to_string().to_lowercase()))
+    }
+
+    fn custom_source(&self, settings_path: PathBuf) -> EmuSettingsSource {
+        EmuSettingsSource::Custom(CustomEmuSource {
+            valid_ext: vec![self.settings_ext().to_string()],
+            settings_path: Some(settings_path),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum EmuInstallKind {
+    Flatpak,
+    AppImage,
+    EmuDeck,
+    RetroDeck,
+    /// Settings found in the XDG config dir without a known install method.
+    Native,
+}
+
+/// A detected emulator install, with a settings source that can be used to prefill
+/// an `EmuSettingsSourceConfig`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmuInstall {
+    pub emulator: Emulator,
+    pub kind: EmuInstallKind,
+    pub source: EmuSettingsSource,
+    pub settings_path: PathBuf,
+    pub settings_exists: bool,
+    pub version: Option<String>,
+    /// Path to the executable, if known (AppImages only)
+    pub executable: Option<PathBuf>,
+}
+
+/// Directories commonly used to store AppImages, relative to the user home.
+const APPIMAGE_DIRS: [&str; 4] = ["Applications", "AppImages", ".local/bin", "Desktop"];
+
+/// Scans installed flatpaks, common AppImage locations, EmuDeck/RetroDECK installs,
+/// and XDG config dirs for supported emulators.
+///
+/// The installs are only listed for the user to pick from; settings source defaults
+/// are still set by the action registrar.
+pub fn discover_emulators(ctx: &PipelineContext) -> Vec<EmuInstall> {
+    let flatpaks = list_installed_flatpaks()
+        .inspect_err(|err| log::warn!("Unable to fetch installed flatpaks: {err}"))
+        .unwrap_or_default();
+
+    discover_installed(ctx, &flatpaks)
+}
+
+fn discover_installed(ctx: &PipelineContext, flatpaks: &[FlatpakInfo]) -> Vec<EmuInstall> {
+    let home = &ctx.decky_env.deck_user_home;
+
+    let mut installs = discover_flatpaks(ctx, flatpaks);
+    installs.extend(discover_appimages(home));
+    installs.extend(discover_emudeck(ctx));
+    installs.extend(discover_retrodeck(ctx));
+
+    // Native installs share the XDG config dir with AppImages and EmuDeck, so only
+    // report settings that weren't already claimed by one of those.
+    let config_dir = home.join(".config");
+    for emulator in Emulator::iter() {
+        let settings_path = emulator.settings_file_in(&config_dir);
+        if settings_path.is_file() && !installs.iter().any(|i| i.settings_path == settings_path) {
+            installs.push(EmuInstall {
+                emulator,
+                kind: EmuInstallKind::Native,
+                source: emulator.custom_source(settings_path.clone()),
+                settings_path,
+                settings_exists: true,
+                version: None,
+                executable: None,
+            });
+        }
+    }
+
+    for install in installs.iter_mut().filter(|i| i.version.is_none()) {
+        install.version = version::detect_version(install.emulator, &install.settings_path, home)
+            .map(|v| v.to_string());
+    }
+
+    installs
+}
+
+fn discover_flatpaks(ctx: &PipelineContext, installed: &[FlatpakInfo]) -> Vec<EmuInstall> {
+    Emulator::iter()
+        .filter_map(|emulator| {
+            let app_id = emulator.flatpak_app_id();
+            let version = installed
+                .iter()
+                .find(|f| f.app_id == app_id)?
+                .version
+                .clone();
+
+            // prefer the first source with existing settings, e.g. pre-1.0 melonDS INI files
+            let sources = emulator
+                .flatpak_sources()
+                .iter()
+                .filter_map(|source| {
+                    let source = EmuSettingsSource::Flatpak(*source);
+                    let settings_path = source.settings_path(ctx).ok()?;
+                    Some((source, settings_path))
+                })
+                .collect::<Vec<_>>();
+
+            let (source, settings_path) = match sources
+                .iter()
+                .find(|(_, path)| path.is_file())
+                .or(sources.first())
+            {
+                Some(found) => found.clone(),
+                None => {
+                    let config_dir = ctx
+                        .decky_env
+                        .deck_user_home
+                        .join(".var/app")
+                        .join(app_id)
+                        .join("config");
+                    let settings_path = emulator.settings_file_in(&config_dir);
+                    (emulator.custom_source(settings_path.clone()), settings_path)
+                }
+            };
+
+            Some(EmuInstall {
+                emulator,
+                kind: EmuInstallKind::Flatpak,
+                source,
+                settings_exists: settings_path.is_file(),
+                settings_path,
+                version,
+                executable: None,
+            })
+        })
+        .collect()
+}
+
+/// Lists AppImages for supported emulators in the common AppImage dirs.
+fn list_appimages(home: &Path) -> Vec<(Emulator, PathBuf)> {
+    APPIMAGE_DIRS
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(home.join(dir)).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_string_lossy().to_string();
+            let emulator = Emulator::from_appimage_name(&name)?;
+
+            Some((emulator, path))
+        })
+        .collect()
+}
+
+fn discover_appimages(home: &Path) -> Vec<EmuInstall> {
+    let config_dir = home.join(".config");
+
+    list_appimages(home)
+        .into_iter()
+        .map(|(emulator, path)| {
+            let settings_path = emulator.settings_file_in(&config_dir);
+            let source = match emulator {
+                Emulator::Cemu => EmuSettingsSource::AppImage(AppImageSource::Cemu),
+                _ => emulator.custom_source(settings_path.clone()),
+            };
+
+            EmuInstall {
+                emulator,
+                kind: EmuInstallKind::AppImage,
+                source,
+                settings_exists: settings_path.is_file(),
+                settings_path,
+                version: version::appimage_version(&path).map(|v| v.to_string()),
+                executable: Some(path),
+            }
+        })
+        .collect()
+}
+
+fn discover_emudeck(ctx: &PipelineContext) -> Vec<EmuInstall> {
+    if EmuDeckSettings::load(&ctx.decky_env.deck_user_home).is_err() {
+        return vec![];
+    }
+
+    [
+        (Emulator::Cemu, EmuDeckSource::CemuProton),
+        (Emulator::Cemu, EmuDeckSource::Cemu),
+        (Emulator::Citra, EmuDeckSource::Citra),
+        (Emulator::Lime3ds, EmuDeckSource::Lime3ds),
+        (Emulator::Azahar, EmuDeckSource::Azahar),
+        (Emulator::MelonDS, EmuDeckSource::MelonDS),
+        (Emulator::Dolphin, EmuDeckSource::Dolphin),
+    ]
+    .into_iter()
+    .filter_map(|(emulator, source)| {
+        let source = EmuSettingsSource::EmuDeck(source);
+        let settings_path = source.settings_path(ctx).ok()?;
+
+        settings_path.is_file().then_some(EmuInstall {
+            emulator,
+            kind: EmuInstallKind::EmuDeck,
+            source,
+            settings_path,
+            settings_exists: true,
+            version: None,
+            executable: None,
+        })
+    })
+    .collect()
+}
+
+fn discover_retrodeck(ctx: &PipelineContext) -> Vec<EmuInstall> {
+    if !RetroDeckSource::retrodeck_cfg(ctx).is_file() {
+        return vec![];
+    }
+
+    [
+        (Emulator::Cemu, RetroDeckSource::Cemu),
+        (Emulator::Citra, RetroDeckSource::Citra),
+        (Emulator::Lime3ds, RetroDeckSource::Lime3ds),
+        (Emulator::MelonDS, RetroDeckSource::MelonDS),
+        (Emulator::Dolphin, RetroDeckSource::Dolphin),
+    ]
+    .into_iter()
+    .filter_map(|(emulator, source)| {
+        let source = EmuSettingsSource::RetroDeck(source);
+        let settings_path = source.settings_path(ctx).ok()?;
+
+        settings_path.is_file().then_some(EmuInstall {
+            emulator,
+            kind: EmuInstallKind::RetroDeck,
+            source,
+            settings_path,
+            settings_exists: true,
+            version: None,
+            executable: None,
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+
+    use crate::{decky_env::DeckyEnv, util::create_dir_all};
+
+    use super::*;
+
+    fn touch(path: &Path) -> Result<()> {
+        create_dir_all(path.parent().unwrap())?;
+        Ok(std::fs::write(path, "")?)
+    }
+
+    #[test]
+    fn test_discover_flatpaks_and_appimages() -> Result<()> {
+        let decky_env = Arc::new(DeckyEnv::new_test("emu_discovery"));
+        let home = decky_env.deck_user_home.clone();
+        let ctx = PipelineContext::new(None, Default::default(), decky_env);
+
+        let flatpaks = [
+            FlatpakInfo {
+                name: "Cemu".into(),
+                app_id: "info.cemu.Cemu".into(),
+                version: Some("2.6".into()),
+            },
+            FlatpakInfo {
+                name: "Dolphin".into(),
+                app_id: "org.DolphinEmu.dolphin-emu".into(),
+                version: Some("2409".into()),
+            },
+            FlatpakInfo {
+                name: "Firefox".into(),
+                app_id: "org.mozilla.firefox".into(),
+                version: None,
+            },
+        ];
+
+        let cemu_settings = home.join(".var/app/info.cemu.Cemu/config/Cemu/settings.xml");
+        touch(&cemu_settings)?;
+
+        let melonds = home.join("Applications/melonDS-1.0-x86_64.AppImage");
+        touch(&melonds)?;
+        touch(&home.join("Applications/Firefox.AppImage"))?;
+
+        let native_dolphin = home.join(".config/dolphin-emu/Dolphin.ini");
+        touch(&native_dolphin)?;
+
+        let installs = discover_installed(&ctx, &flatpaks);
+        let find = |emulator: Emulator, kind: EmuInstallKind| {
+            installs
+                .iter()
+                .find(|i| i.emulator == emulator && i.kind == kind)
+                .unwrap_or_else(|| panic!("{emulator} {kind:?} install should be found"))
+        };
+
+        assert_eq!(4, installs.len());
+
+        let cemu = find(Emulator::Cemu, EmuInstallKind::Flatpak);
+        assert_eq!(EmuSettingsSource::Flatpak(FlatpakSource::Cemu), cemu.source);
+        assert_eq!(cemu_settings, cemu.settings_path);
+        assert!(cemu.settings_exists);
+        assert_eq!(Some("2.6"), cemu.version.as_deref());
+
+        // emulators without a flatpak source get a custom source in the flatpak config dir
+        let dolphin = find(Emulator::Dolphin, EmuInstallKind::Flatpak);
+        assert_eq!(
+            home.join(".var/app/org.DolphinEmu.dolphin-emu/config/dolphin-emu/Dolphin.ini"),
+            dolphin.settings_path
+        );
+        assert!(!dolphin.settings_exists);
+
+        let appimage = find(Emulator::MelonDS, EmuInstallKind::AppImage);
+        assert_eq!(Some(&melonds), appimage.executable.as_ref());
+        assert_eq!(
+            home.join(".config/melonDS/melonDS.toml"),
+            appimage.settings_path
+        );
+        assert_eq!(Some("1.0.0"), appimage.version.as_deref());
+
+        let native = find(Emulator::Dolphin, EmuInstallKind::Native);
+        assert_eq!(native_dolphin, native.settings_path);
+
+        std::fs::remove_dir_all(home)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_emulator_from_appimage_name() {
+        assert_eq!(
+            Some(Emulator::Cemu),
+            Emulator::from_appimage_name("Cemu-2.0-86-x86_64.AppImage")
+        );
+        assert_eq!(
+            Some(Emulator::Azahar),
+            Emulator::from_appimage_name("azahar.AppImage")
+        );
+        assert_eq!(
+            Some(Emulator::MelonDS),
+            Emulator::from_appimage_name("melonDS-x86_64.AppImage")
+        );
+        assert_eq!(None, Emulator::from_appimage_name("Cemu.tar.gz"));
+        assert_eq!(None, Emulator::from_appimage_name("Firefox.AppImage"));
+    }
+}
@@ -0,0 +1,571 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pipeline::{
+        action::emu_source::EmuSettingsSourceConfig, data::VersionMatcher,
+        executor::PipelineContext,
+    },
+    sys::flatpak::get_flatpak_version,
+};
+
+use super::{list_appimages, Emulator};
+
+/// Loosely semver-shaped emulator version. Missing components default to 0, and a
+/// trailing numeric component (e.g. the `86` in Cemu `2.0-86`, or a nightly build
+/// number) is kept as `build` and compared after `patch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct EmuVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub build: Option<u64>,
+}
+
+impl EmuVersion {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            build: None,
+        }
+    }
+}
+
+impl FromStr for EmuVersion {
+    type Err = anyhow::Error;
+
+    /// Finds the first version-shaped token in `s`, e.g. `2.0-86` in
+    /// `Cemu-2.0-86-x86_64.AppImage`. Tokens must start the string or follow a
+    /// separator or `v`, so names (`Lime3DS`) and architectures (`x86_64`) are skipped.
+    fn from_str(s: &str) -> Result<Self> {
+        let arch_rxp = Regex::new(r"(?i)x86[_-]64|i[3-6]86|amd64|aarch64|arm64|armhf|x86")
+            .expect("arch regex should be valid");
+        let rxp =
+            Regex::new(r"(?:^|[-_ ./(])v?(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:[-.+](\d+))?(?:$|[^0-9])")
+                .expect("emu version regex should be valid");
+
+        let stripped = arch_rxp.replace_all(s, "-");
+        let captures = rxp
+            .captures(&stripped)
+            .with_context(|| format!("no version found in `{s}`"))?;
+
+        let part = |i| -> Result<Option<u64>> {
+            captures
+                .get(i)
+                .map(|m| m.as_str().parse::<u64>())
+                .transpose()
+                .with_context(|| format!("invalid version component in `{s}`"))
+        };
+
+        Ok(Self {
+            major: part(1)?.unwrap_or_default(),
+            minor: part(2)?.unwrap_or_default(),
+            patch: part(3)?.unwrap_or_default(),
+            build: part(4)?,
+        })
+    }
+}
+
+impl Display for EmuVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(build) = self.build {
+            write!(f, "-{build}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PartialOrd for EmuVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EmuVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            self.major,
+            self.minor,
+            self.patch,
+            self.build.unwrap_or_default(),
+        )
+            .cmp(&(
+                other.major,
+                other.minor,
+                other.patch,
+                other.build.unwrap_or_default(),
+            ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    /// `^1.2` => `>=1.2.0, <2.0.0`; `^0.2` => `>=0.2.0, <0.3.0`
+    Caret,
+    /// `~1.2` => `>=1.2.0, <1.3.0`; `~1` => `>=1.0.0, <2.0.0`
+    Tilde,
+}
+
+/// A version in a requirement, where trailing components may be omitted
+/// (or `*`/`x`) to match any value, as in cargo's semver requirements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct PartialVersion {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    build: Option<u64>,
+}
+
+impl PartialVersion {
+    /// Compares only the components present in `self`.
+    fn cmp_prefix(&self, v: &EmuVersion) -> Ordering {
+        v.major
+            .cmp(&self.major)
+            .then_with(|| self.minor.map_or(Ordering::Equal, |m| v.minor.cmp(&m)))
+            .then_with(|| self.patch.map_or(Ordering::Equal, |p| v.patch.cmp(&p)))
+            .then_with(|| {
+                self.build
+                    .map_or(Ordering::Equal, |b| v.build.unwrap_or_default().cmp(&b))
+            })
+    }
+}
+
+impl FromStr for PartialVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rxp = Regex::new(r"^v?(\d+)(?:\.(\d+|[*xX]))?(?:\.(\d+|[*xX]))?(?:[-+](\d+))?$")
+            .expect("partial version regex should be valid");
+
+        let captures = rxp
+            .captures(s)
+            .with_context(|| format!("invalid version requirement `{s}`"))?;
+
+        let part = |i| -> Result<Option<u64>> {
+            captures
+                .get(i)
+                .filter(|m| m.as_str().chars().all(|c| c.is_ascii_digit()))
+                .map(|m| m.as_str().parse::<u64>())
+                .transpose()
+                .with_context(|| format!("invalid version component in `{s}`"))
+        };
+
+        let major = part(1)?.context("major version expected")?;
+        let mut minor = part(2)?;
+        let mut patch = minor.and(part(3)?);
+        let build = part(4)?;
+
+        // a build number pins the version before it, e.g. `2.0-86` => `2.0.0-86`
+        if build.is_some() {
+            minor = minor.or(Some(0));
+            patch = patch.or(Some(0));
+        }
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            build,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Comparator {
+    op: Op,
+    version: PartialVersion,
+}
+
+impl Comparator {
+    fn matches(&self, v: &EmuVersion) -> bool {
+        let req = &self.version;
+        let ord = req.cmp_prefix(v);
+        match self.op {
+            Op::Exact => ord == Ordering::Equal,
+            Op::Greater => ord == Ordering::Greater,
+            Op::GreaterEq => ord != Ordering::Less,
+            Op::Less => ord == Ordering::Less,
+            Op::LessEq => ord != Ordering::Greater,
+            Op::Tilde => {
+                ord != Ordering::Less
+                    && v.major == req.major
+                    && req.minor.is_none_or(|m| v.minor == m)
+            }
+            Op::Caret => {
+                // everything up to the leftmost non-zero component must match exactly;
+                // if every given component is zero, all of them must match
+                let given = [Some(req.major), req.minor, req.patch];
+                let actual = [v.major, v.minor, v.patch];
+                let pinned = given
+                    .iter()
+                    .position(|c| c.is_some_and(|c| c != 0))
+                    .unwrap_or(2);
+
+                ord != Ordering::Less
+                    && given
+                        .iter()
+                        .zip(actual)
+                        .take(pinned + 1)
+                        .all(|(given, actual)| given.is_none_or(|g| g == actual))
+            }
+        }
+    }
+}
+
+/// A set of comparators (e.g. `>=2.0, <3.0`) that must all match. Bare versions
+/// are caret requirements, as in cargo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionReq(Vec<Comparator>);
+
+impl VersionReq {
+    pub fn matches(&self, version: &EmuVersion) -> bool {
+        self.0.iter().all(|c| c.matches(version))
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let comparators = s
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty() && *c != "*")
+            .map(|c| {
+                let (op, version) = [
+                    (">=", Op::GreaterEq),
+                    ("<=", Op::LessEq),
+                    (">", Op::Greater),
+                    ("<", Op::Less),
+                    ("=", Op::Exact),
+                    ("^", Op::Caret),
+                    ("~", Op::Tilde),
+                ]
+                .into_iter()
+                .find_map(|(prefix, op)| c.strip_prefix(prefix).map(|v| (op, v)))
+                .unwrap_or((Op::Caret, c));
+
+                Ok(Comparator {
+                    op,
+                    version: version.trim().parse()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self(comparators))
+    }
+}
+
+/// Detects the version of `emulator` whose settings live at `settings_path`, trying
+/// flatpak metadata, then AppImage metadata, then markers in the settings file itself.
+pub fn detect_version(emulator: Emulator, settings_path: &Path, home: &Path) -> Option<EmuVersion> {
+    flatpak_version(emulator, settings_path, home)
+        .or_else(|| {
+            settings_path
+                .starts_with(home.join(".config"))
+                .then(|| {
+                    list_appimages(home)
+                        .into_iter()
+                        .filter(|(emu, _)| *emu == emulator)
+                        .find_map(|(_, path)| appimage_version(&path))
+                })
+                .flatten()
+        })
+        .or_else(|| config_marker_version(emulator, settings_path))
+}
+
+fn flatpak_version(emulator: Emulator, settings_path: &Path, home: &Path) -> Option<EmuVersion> {
+    let app_id = settings_path
+        .strip_prefix(home.join(".var/app"))
+        .ok()?
+        .components()
+        .next()?
+        .as_os_str()
+        .to_string_lossy()
+        .to_string();
+
+    if app_id != emulator.flatpak_app_id() {
+        return None;
+    }
+
+    get_flatpak_version(&app_id)
+        .inspect_err(|err| log::warn!("failed to get flatpak version for {app_id}: {err}"))
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+}
+
+/// Reads `X-AppImage-Version` from the desktop file embedded in the AppImage,
+/// falling back to the version in the file name.
+pub fn appimage_version(appimage: &Path) -> Option<EmuVersion> {
+    embedded_appimage_version(appimage)
+        .inspect_err(|err| log::debug!("no embedded version in {appimage:?}: {err}"))
+        .ok()
+        .or_else(|| appimage.file_name()?.to_string_lossy().parse().ok())
+}
+
+/// Offset of the squashfs image in a type 2 AppImage, which starts right after the
+/// ELF runtime (the end of its section header table).
+fn appimage_squashfs_offset(appimage: &Path) -> Result<u64> {
+    let mut header = [0; 64];
+    std::fs::File::open(appimage)?.read_exact(&mut header)?;
+
+    anyhow::ensure!(&header[..4] == b"\x7fELF", "not an ELF file");
+    anyhow::ensure!(&header[8..11] == b"AI\x02", "not a type 2 AppImage");
+
+    let le = header[5] == 1;
+    let uint_at = |i: usize, len: usize| {
+        header[i..i + len]
+            .iter()
+            .enumerate()
+            .map(|(n, b)| {
+                let shift = if le { n } else { len - 1 - n };
+                u64::from(*b) << (8 * shift)
+            })
+            .sum::<u64>()
+    };
+
+    let (sh_off, sh_entsize, sh_num) = match header[4] {
+        1 => (uint_at(0x20, 4), uint_at(0x2E, 2), uint_at(0x30, 2)),
+        2 => (uint_at(0x28, 8), uint_at(0x3A, 2), uint_at(0x3C, 2)),
+        class => anyhow::bail!("unknown ELF class {class}"),
+    };
+
+    Ok(sh_off + sh_entsize * sh_num)
+}
+
+/// Extracts the embedded desktop files with `unsquashfs`, so the AppImage
+/// itself is never executed.
+fn embedded_appimage_version(appimage: &Path) -> Result<EmuVersion> {
+    let offset = appimage_squashfs_offset(appimage)?;
+    let dir = tempfile::tempdir()?;
+    let out = dir.path().join("squashfs-root");
+
+    Command::new("unsquashfs")
+        .arg("-offset")
+        .arg(offset.to_string())
+        .arg("-dest")
+        .arg(&out)
+        .arg("-no-xattrs")
+        .arg(appimage)
+        .arg("*.desktop")
+        .output()?
+        .status
+        .exit_ok()?;
+
+    let desktop_files = std::fs::read_dir(&out)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "desktop"))
+        .collect::<Vec<PathBuf>>();
+
+    desktop_files
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .flat_map(|contents| {
+            contents
+                .lines()
+                .filter_map(|l| l.trim().strip_prefix("X-AppImage-Version="))
+                .map(|v| v.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .find_map(|v| v.parse().ok())
+        .context("embedded desktop file has no X-AppImage-Version")
+}
+
+/// Infers a minimum version from the settings file format, for emulators that
+/// changed formats between releases.
+fn config_marker_version(emulator: Emulator, settings_path: &Path) -> Option<EmuVersion> {
+    match emulator {
+        Emulator::MelonDS => match settings_path.extension()?.to_str()? {
+            "toml" => Some(EmuVersion::new(1, 0, 0)),
+            "ini" => Some(EmuVersion::new(0, 9, 5)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Matches the version of `emulator` found via the settings source in the pipeline
+/// against a version requirement. Fails to match if no version can be detected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmuVersionMatcher {
+    emulator: Emulator,
+    req: VersionReq,
+}
+
+impl EmuVersionMatcher {
+    pub fn new(emulator: Emulator, req: &str) -> Result<Self> {
+        Ok(Self {
+            emulator,
+            req: req.parse()?,
+        })
+    }
+}
+
+#[typetag::serde]
+impl VersionMatcher for EmuVersionMatcher {
+    fn matches_version(&self, ctx: &PipelineContext) -> Result<bool> {
+        let settings_path = ctx
+            .get_state::<EmuSettingsSourceConfig>()
+            .context("emulator version matching requires settings source in pipeline")?;
+
+        let installed = detect_version(self.emulator, settings_path, &ctx.decky_env.deck_user_home);
+
+        // The settings format wins where it identifies the version, so the selected
+        // action always edits the file the source points to.
+        let version = config_marker_version(self.emulator, settings_path).or(installed);
+
+        log::debug!(
+            "detected {} version {version:?} from {settings_path:?}",
+            self.emulator
+        );
+
+        let matches = version.is_some_and(|v| self.req.matches(&v));
+        if installed.is_some_and(|v| self.req.matches(&v) != matches) {
+            log::warn!(
+                "{} settings at {settings_path:?} don't match the installed version {installed:?}; check the selected settings source",
+                self.emulator
+            );
+        }
+
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() -> Result<()> {
+        assert_eq!(
+            EmuVersion {
+                major: 2,
+                minor: 0,
+                patch: 0,
+                build: Some(86)
+            },
+            "2.0-86".parse()?
+        );
+        assert_eq!(EmuVersion::new(1, 0, 0), "v1.0".parse()?);
+        assert_eq!(EmuVersion::new(2119, 0, 0), "2119".parse()?);
+        assert_eq!(
+            EmuVersion::new(2119, 0, 0),
+            "Lime3DS-2119-x86_64.AppImage".parse()?
+        );
+        assert_eq!(
+            EmuVersion {
+                major: 2,
+                minor: 0,
+                patch: 0,
+                build: Some(86)
+            },
+            "Cemu-2.0-86-x86_64.AppImage".parse()?
+        );
+        assert!("nightly".parse::<EmuVersion>().is_err());
+        assert!("melonDS-x86_64.AppImage".parse::<EmuVersion>().is_err());
+        assert!("melonDS-aarch64.AppImage".parse::<EmuVersion>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_req() -> Result<()> {
+        let cemu_2: VersionReq = ">=2.0, <3".parse()?;
+        assert!(cemu_2.matches(&"2.0-86".parse()?));
+        assert!(!cemu_2.matches(&"1.27.1".parse()?));
+
+        let caret: VersionReq = "^1.2".parse()?;
+        assert!(caret.matches(&"1.9.0".parse()?));
+        assert!(!caret.matches(&"1.1.0".parse()?));
+        assert!(!caret.matches(&"2.0.0".parse()?));
+
+        let caret_zero: VersionReq = "^0.9".parse()?;
+        assert!(caret_zero.matches(&"0.9.5".parse()?));
+        assert!(!caret_zero.matches(&"0.10.0".parse()?));
+
+        let caret_patch: VersionReq = "^0.0.3".parse()?;
+        assert!(caret_patch.matches(&"0.0.3".parse()?));
+        assert!(!caret_patch.matches(&"0.0.4".parse()?));
+
+        let tilde: VersionReq = "~0.9".parse()?;
+        assert!(tilde.matches(&"0.9.5".parse()?));
+        assert!(!tilde.matches(&"0.10.0".parse()?));
+
+        let tilde_major: VersionReq = "~1".parse()?;
+        assert!(tilde_major.matches(&"1.9.0".parse()?));
+        assert!(!tilde_major.matches(&"2.0.0".parse()?));
+
+        // partial versions match any omitted component
+        let greater: VersionReq = ">1.2".parse()?;
+        assert!(!greater.matches(&"1.2.5".parse()?));
+        assert!(greater.matches(&"1.3.0".parse()?));
+        assert!("<=1.2".parse::<VersionReq>()?.matches(&"1.2.5".parse()?));
+        assert!("=2.x".parse::<VersionReq>()?.matches(&"2.1.0".parse()?));
+
+        let build: VersionReq = ">=2.0-86".parse()?;
+        assert!(build.matches(&"2.0-86".parse()?));
+        assert!(!build.matches(&"2.0-85".parse()?));
+
+        assert!("*"
+            .parse::<VersionReq>()?
+            .matches(&EmuVersion::new(0, 0, 1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_appimage_squashfs_offset() -> Result<()> {
+        let mut header = [0u8; 64];
+        header[..4].copy_from_slice(b"\x7fELF");
+        header[4] = 2; // 64-bit
+        header[5] = 1; // little endian
+        header[8..11].copy_from_slice(b"AI\x02");
+        header[0x28..0x30].copy_from_slice(&1000u64.to_le_bytes());
+        header[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+        header[0x3C..0x3E].copy_from_slice(&10u16.to_le_bytes());
+
+        let file = tempfile::NamedTempFile::new()?;
+        std::fs::write(file.path(), header)?;
+        assert_eq!(1640, appimage_squashfs_offset(file.path())?);
+
+        header[8..11].copy_from_slice(b"\0\0\0");
+        std::fs::write(file.path(), header)?;
+        assert!(appimage_squashfs_offset(file.path()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_marker_version() {
+        assert_eq!(
+            Some(EmuVersion::new(1, 0, 0)),
+            config_marker_version(Emulator::MelonDS, Path::new("melonDS/melonDS.toml"))
+        );
+        assert_eq!(
+            None,
+            config_marker_version(Emulator::Cemu, Path::new("Cemu/settings.xml"))
+        );
+    }
+}
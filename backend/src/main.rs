@@ -1,11 +1,12 @@
 #![feature(exit_status_error)]
+#![cfg_attr(test, feature(test))]
 
 use anyhow::Result;
 use client_pipeline::ClientPipelineHandler;
 
 use std::{
     env,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     thread::sleep,
     time::Duration,
@@ -19,11 +20,16 @@ use crate::{
     api::{request_handler::RequestHandler, Api},
     autostart::AutoStart,
     consts::{PACKAGE_NAME, PACKAGE_VERSION, PORT},
-    db::ProfileDb,
+    db::{
+        backup::{BackupReason, BACKUP_INTERVAL},
+        templates::load_template_files,
+        ProfileDb,
+    },
     decky_env::DeckyEnv,
+    option_source::OptionSources,
     pipeline::{action_registar::PipelineActionRegistrar, executor::PipelineContext},
     secondary_app::SecondaryAppManager,
-    settings::Settings,
+    settings::{ProfileId, Settings},
     util::create_dir_all,
 };
 use clap::{Parser, Subcommand};
@@ -34,7 +40,9 @@ pub mod asset;
 pub mod consts;
 pub mod db;
 pub mod decky_env;
+pub mod emu_discovery;
 mod macros;
+pub mod option_source;
 pub mod pipeline;
 pub mod secondary_app;
 pub mod sys;
@@ -69,6 +77,32 @@ pub enum AppModes {
         /// The folder in which to store the schema
         output: String,
     },
+    /// exports profiles to a JSON bundle. The plugin server must not be running.
+    ExportProfiles {
+        /// The file in which to store the bundle
+        output: String,
+        /// Ids of the profiles to export; exports all profiles if none are given
+        #[arg(long = "profile")]
+        profiles: Vec<String>,
+    },
+    /// imports profiles from a JSON bundle. The plugin server must not be running.
+    ImportProfiles {
+        /// The bundle file to import
+        input: String,
+    },
+    /// lists the profile db backups, newest first.
+    ListProfileBackups,
+    /// restores a profile db backup. The plugin server must not be running.
+    RestoreProfileBackup {
+        /// The id of the backup to restore
+        backup_id: String,
+    },
+    /// migrates the profile db to the current schema. The plugin server must not be running.
+    MigrateProfiles {
+        /// Reports the migration steps without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 fn main() -> Result<()> {
@@ -89,6 +123,11 @@ fn main() -> Result<()> {
             AppModes::Autostart { .. } => "autostart",
             AppModes::Serve => "server",
             AppModes::Schema { .. } => "schema",
+            AppModes::ExportProfiles { .. }
+            | AppModes::ImportProfiles { .. }
+            | AppModes::ListProfileBackups
+            | AppModes::RestoreProfileBackup { .. }
+            | AppModes::MigrateProfiles { .. } => "profiles",
         }
     );
 
@@ -150,14 +189,17 @@ fn main() -> Result<()> {
         log::error!("Error setting up config dir: {err}");
     }
 
-    let registrar = PipelineActionRegistrar::builder().with_core().build();
+    let registrar = PipelineActionRegistrar::builder()
+        .with_core()
+        .with_registry_files(&decky_env.asset_manager())
+        .build();
 
     let global_config = settings.get_global_cfg();
 
     let settings = Arc::new(Mutex::new(settings));
 
     let request_handler = Arc::new(Mutex::new(RequestHandler::new()));
-    let secondary_app_manager = SecondaryAppManager::new(decky_env.asset_manager());
+    let secondary_app_manager = SecondaryAppManager::new(&decky_env);
     let client_pipeline_handler =
         Arc::new(Mutex::new(ClientPipelineHandler::new(decky_env.clone())));
 
@@ -244,9 +286,24 @@ fn main() -> Result<()> {
         AppModes::Serve => {
             decky_env.write()?;
 
-            let db_path = decky_env.decky_plugin_runtime_dir.join("profiles.db");
             let profiles_db: &'static ProfileDb =
-                Box::leak(Box::new(ProfileDb::new(db_path, registrar.clone())));
+                Box::leak(Box::new(open_profile_db(&decky_env, registrar.clone())?));
+
+            profiles_db.set_revision_retention(
+                settings
+                    .lock()
+                    .expect("settings mutex should not be poisoned")
+                    .get_global_cfg()
+                    .profile_revision_retention,
+            );
+
+            std::thread::spawn(move || loop {
+                sleep(BACKUP_INTERVAL);
+
+                if let Err(err) = profiles_db.backup(BackupReason::Scheduled) {
+                    log::error!("Failed to back up profile db: {err:#?}");
+                }
+            });
 
             let instance = Instance::new(PORT)
                 // logging
@@ -298,6 +355,34 @@ fn main() -> Result<()> {
                         profiles_db,
                     ),
                 )
+                .register(
+                    "set_app_profile_overrides",
+                    crate::api::profile::set_app_profile_overrides(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
+                .register(
+                    "copy_app_profile_override",
+                    crate::api::profile::copy_app_profile_override(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
+                .register(
+                    "reset_app_profile_overrides",
+                    crate::api::profile::reset_app_profile_overrides(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
+                .register(
+                    "set_apps_profile_settings",
+                    crate::api::profile::set_apps_profile_settings(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
                 .register(
                     "get_default_app_override_for_profile_request",
                     crate::api::profile::get_default_app_override_pipeline_for_profile(
@@ -326,10 +411,93 @@ fn main() -> Result<()> {
                     "get_toplevel",
                     crate::api::profile::get_toplevel(registrar.clone()),
                 )
+                .register(
+                    "get_action_ui_schemas",
+                    crate::api::action::get_action_ui_schemas(),
+                )
+                .register(
+                    "get_options",
+                    crate::api::action::get_options(
+                        request_handler.clone(),
+                        OptionSources::new(decky_env.clone(), secondary_app_manager.clone()),
+                    ),
+                )
                 .register(
                     "get_templates",
                     crate::api::profile::get_templates(profiles_db),
                 )
+                .register(
+                    "create_template",
+                    crate::api::profile::create_template(request_handler.clone(), profiles_db),
+                )
+                .register(
+                    "update_template",
+                    crate::api::profile::update_template(request_handler.clone(), profiles_db),
+                )
+                .register(
+                    "delete_template",
+                    crate::api::profile::delete_template(request_handler.clone(), profiles_db),
+                )
+                .register(
+                    "export_profiles",
+                    crate::api::profile::export_profiles(request_handler.clone(), profiles_db),
+                )
+                .register(
+                    "import_profiles",
+                    crate::api::profile::import_profiles(
+                        request_handler.clone(),
+                        profiles_db,
+                        registrar.clone(),
+                    ),
+                )
+                .register(
+                    "list_profile_revisions",
+                    crate::api::profile::list_profile_revisions(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
+                .register(
+                    "diff_profile_revisions",
+                    crate::api::profile::diff_profile_revisions(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
+                .register(
+                    "restore_profile_revision",
+                    crate::api::profile::restore_profile_revision(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
+                .register(
+                    "get_profile_rules",
+                    crate::api::profile::get_profile_rules(profiles_db),
+                )
+                .register(
+                    "set_profile_rules",
+                    crate::api::profile::set_profile_rules(request_handler.clone(), profiles_db),
+                )
+                .register(
+                    "select_app_profile",
+                    crate::api::profile::select_app_profile(request_handler.clone(), profiles_db),
+                )
+                .register(
+                    "list_profile_backups",
+                    crate::api::profile::list_profile_backups(profiles_db),
+                )
+                .register(
+                    "create_profile_backup",
+                    crate::api::profile::create_profile_backup(profiles_db),
+                )
+                .register(
+                    "restore_profile_backup",
+                    crate::api::profile::restore_profile_backup(
+                        request_handler.clone(),
+                        profiles_db,
+                    ),
+                )
                 // client pipeline
                 .register(
                     "add_client_teardown_action",
@@ -351,10 +519,65 @@ fn main() -> Result<()> {
                         client_pipeline_handler.clone(),
                     ),
                 )
+                .register(
+                    "apply_gamescope_launch_options",
+                    crate::api::client_pipeline::apply_gamescope_launch_options(
+                        request_handler.clone(),
+                        client_pipeline_handler.clone(),
+                    ),
+                )
                 // secondary app
                 .register(
-                    "get_secondary_app_info",
-                    crate::api::secondary_app::get_secondary_app_info(secondary_app_manager),
+                    "create_secondary_app_preset",
+                    crate::api::secondary_app::create_secondary_app_preset(
+                        request_handler.clone(),
+                        secondary_app_manager.clone(),
+                    ),
+                )
+                .register(
+                    "update_secondary_app_preset",
+                    crate::api::secondary_app::update_secondary_app_preset(
+                        request_handler.clone(),
+                        secondary_app_manager.clone(),
+                    ),
+                )
+                .register(
+                    "delete_secondary_app_preset",
+                    crate::api::secondary_app::delete_secondary_app_preset(
+                        request_handler.clone(),
+                        secondary_app_manager,
+                    ),
+                )
+                // windows
+                .register("list_windows", crate::api::window::list_windows())
+                .register(
+                    "test_window_matcher",
+                    crate::api::window::test_window_matcher(request_handler.clone()),
+                )
+                .register(
+                    "get_learned_windows",
+                    crate::api::window::get_learned_windows(profiles_db, decky_env.clone()),
+                )
+                .register(
+                    "set_learned_window",
+                    crate::api::window::set_learned_window(
+                        request_handler.clone(),
+                        profiles_db,
+                        decky_env.clone(),
+                    ),
+                )
+                .register(
+                    "reset_learned_windows",
+                    crate::api::window::reset_learned_windows(
+                        request_handler.clone(),
+                        profiles_db,
+                        decky_env.clone(),
+                    ),
+                )
+                // emulators
+                .register(
+                    "discover_emulators",
+                    crate::api::emulator::discover_emulators(decky_env.clone()),
                 )
                 // settings
                 .register(
@@ -363,14 +586,14 @@ fn main() -> Result<()> {
                 )
                 .register(
                     "set_settings",
-                    crate::api::general::set_settings(request_handler.clone(), settings.clone()),
+                    crate::api::general::set_settings(
+                        request_handler.clone(),
+                        settings.clone(),
+                        profiles_db,
+                    ),
                 )
                 // system info
                 .register("get_display_info", api::general::get_display_info())
-                .register(
-                    "get_audio_device_info",
-                    api::general::get_audio_device_info(decky_env.clone()),
-                )
                 // autostart
                 .register(
                     "autostart",
@@ -389,6 +612,62 @@ fn main() -> Result<()> {
                 .run_blocking()
                 .map_err(|_| anyhow::anyhow!("server stopped unexpectedly"))
         }
+        AppModes::ExportProfiles { output, profiles } => {
+            let profiles_db = open_profile_db(&decky_env, registrar)?;
+
+            let ids = profiles
+                .iter()
+                .map(|id| uuid::Uuid::parse_str(id).map(ProfileId::from_uuid))
+                .collect::<Result<Vec<_>, _>>()?;
+            let bundle =
+                profiles_db.export_profiles((!ids.is_empty()).then_some(ids.as_slice()))?;
+
+            println!("exporting {} profile(s) to {output}", bundle.profiles.len());
+
+            Ok(std::fs::write(
+                output,
+                serde_json::to_string_pretty(&bundle)?,
+            )?)
+        }
+        AppModes::ImportProfiles { input } => {
+            let profiles_db = open_profile_db(&decky_env, registrar.clone())?;
+
+            let bundle = serde_json::from_str(&std::fs::read_to_string(input)?)?;
+            let report = profiles_db.import_profiles(bundle, &registrar)?;
+
+            println!("{}", serde_json::to_string_pretty(&report)?);
+
+            Ok(())
+        }
+        AppModes::ListProfileBackups => {
+            let backups = db::backup::list_backups(&profile_db_path(&decky_env))?;
+
+            println!("{}", serde_json::to_string_pretty(&backups)?);
+
+            Ok(())
+        }
+        AppModes::RestoreProfileBackup { backup_id } => {
+            db::backup::stage_restore(&profile_db_path(&decky_env), &backup_id)?;
+
+            // opening the db applies the staged restore
+            open_profile_db(&decky_env, registrar)?;
+
+            println!("restored profile backup {backup_id}");
+
+            Ok(())
+        }
+        AppModes::MigrateProfiles { dry_run } => {
+            let report = db::migrate::dry_run(&profile_db_path(&decky_env))?;
+
+            println!("{}", serde_json::to_string_pretty(&report)?);
+
+            if !dry_run {
+                // opening the db applies the migrations
+                open_profile_db(&decky_env, registrar)?;
+            }
+
+            Ok(())
+        }
         AppModes::Schema { output } => {
             let path = Path::new(&output);
             if path.is_file() {
@@ -410,6 +689,23 @@ fn main() -> Result<()> {
     }
 }
 
+fn profile_db_path(decky_env: &DeckyEnv) -> PathBuf {
+    decky_env.decky_plugin_runtime_dir.join("profiles.db")
+}
+
+fn open_profile_db(decky_env: &DeckyEnv, registrar: PipelineActionRegistrar) -> Result<ProfileDb> {
+    let templates = load_template_files(
+        &decky_env.asset_manager(),
+        &decky_env.decky_plugin_settings_dir,
+        &registrar,
+    );
+
+    let mut db = ProfileDb::new(profile_db_path(decky_env), registrar)?;
+    db.add_file_templates(templates);
+
+    Ok(db)
+}
+
 fn set_env_vars() {
     // TODO::consider XDG_RUNDIME_DIR and XDG_DATA_DIRS
 
@@ -0,0 +1,271 @@
+//! Options for dropdown fields that only the backend can list, such as audio devices or
+//! installed flatpaks. Action UI schemas reference them by [OptionSource].
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decky_env::DeckyEnv,
+    pipeline::action::{display_config::ExternalDisplaySettings, ui_schema::UiOption},
+    secondary_app::{SecondaryApp, SecondaryAppManager, SecondaryAppPreset, SecondaryAppPresetId},
+    sys::{
+        audio::{get_audio_sinks, get_audio_sources, AudioDeviceInfo},
+        display_info::{get_display_info, DisplayValues},
+        flatpak::{list_installed_flatpaks, FlatpakInfo},
+        x_display::{AspectRatioOption, ModeOption, ModePreference},
+    },
+};
+
+/// Device name that makes emulators follow the system default device.
+const DEFAULT_AUDIO_DEVICE: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value")]
+pub enum OptionSource {
+    /// Audio devices, along with the system default device
+    AudioDevices { kind: AudioDeviceKind },
+    /// Built-in and user secondary app presets, by id. With `installed_only`, presets
+    /// for flatpaks that aren't installed are left out.
+    SecondaryAppPresets { installed_only: bool },
+    /// Installed flatpak apps, by app id
+    InstalledFlatpaks,
+    /// External display settings for the modes the external display supports,
+    /// along with its previous and native resolution
+    DisplayResolutions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum AudioDeviceKind {
+    Sink,
+    Source,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct OptionList {
+    pub options: Vec<UiOption>,
+    /// The value to use when none is selected, if any
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OptionSources {
+    decky_env: Arc<DeckyEnv>,
+    secondary_app_manager: SecondaryAppManager,
+}
+
+impl OptionSources {
+    pub fn new(decky_env: Arc<DeckyEnv>, secondary_app_manager: SecondaryAppManager) -> Self {
+        Self {
+            decky_env,
+            secondary_app_manager,
+        }
+    }
+
+    pub fn get(&self, source: OptionSource) -> Result<OptionList> {
+        match source {
+            OptionSource::AudioDevices { kind } => Ok(audio_options(match kind {
+                AudioDeviceKind::Sink => get_audio_sinks(&self.decky_env),
+                AudioDeviceKind::Source => get_audio_sources(&self.decky_env),
+            })),
+            OptionSource::SecondaryAppPresets { installed_only } => {
+                let mut presets = self.secondary_app_manager.get_presets();
+
+                if installed_only {
+                    let installed = list_installed_flatpaks()?;
+
+                    presets.retain(|_, preset| match &preset.app {
+                        SecondaryApp::Flatpak(app) => {
+                            installed.iter().any(|f| f.app_id == app.app_id)
+                        }
+                        _ => true,
+                    });
+                }
+
+                Ok(preset_options(presets))
+            }
+            OptionSource::InstalledFlatpaks => Ok(flatpak_options(list_installed_flatpaks()?)),
+            OptionSource::DisplayResolutions => Ok(display_settings_options(
+                get_display_info().unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+fn audio_options(devices: Vec<AudioDeviceInfo>) -> OptionList {
+    let default = UiOption {
+        value: DEFAULT_AUDIO_DEVICE.into(),
+        label: "Default".to_string(),
+    };
+
+    OptionList {
+        default: Some(default.value.clone()),
+        options: [default]
+            .into_iter()
+            .chain(devices.into_iter().map(|d| UiOption {
+                value: d.name.into(),
+                label: d.description,
+            }))
+            .collect(),
+    }
+}
+
+fn preset_options(presets: HashMap<SecondaryAppPresetId, SecondaryAppPreset>) -> OptionList {
+    let mut options = presets
+        .into_iter()
+        .map(|(id, preset)| UiOption {
+            value: serde_json::to_value(id).unwrap_or_default(),
+            label: preset.name,
+        })
+        .collect::<Vec<_>>();
+    options.sort_by(|a, b| a.label.cmp(&b.label));
+
+    OptionList {
+        options,
+        default: None,
+    }
+}
+
+fn flatpak_options(flatpaks: Vec<FlatpakInfo>) -> OptionList {
+    let mut options = flatpaks
+        .into_iter()
+        .map(|f| UiOption {
+            value: f.app_id.into(),
+            label: f.name,
+        })
+        .collect::<Vec<_>>();
+    options.sort_by(|a, b| a.label.cmp(&b.label));
+
+    OptionList {
+        options,
+        default: None,
+    }
+}
+
+/// Modes are ordered greatest to least. Mode preferences are "at most" the mode, in case
+/// the display changes without the settings changing.
+fn display_settings_options(modes: Vec<DisplayValues>) -> OptionList {
+    let option = |label: String, settings: ExternalDisplaySettings| UiOption {
+        value: serde_json::to_value(settings).unwrap_or_default(),
+        label,
+    };
+
+    let fixed = [
+        option("Previous".to_string(), ExternalDisplaySettings::Previous),
+        option("Native".to_string(), ExternalDisplaySettings::Native),
+    ];
+
+    let modes = modes.iter().map(|mode| {
+        let resolution = mode.resolution();
+        let label = match mode.refresh() {
+            Some(refresh) => format!("{}x{} @ {refresh:.2}", resolution.w, resolution.h),
+            None => format!("{}x{}", resolution.w, resolution.h),
+        };
+
+        let preference = ModePreference {
+            aspect_ratio: AspectRatioOption::Exact(resolution.w as f32 / resolution.h as f32),
+            // without a known refresh rate, let the display go as high as possible
+            refresh: ModeOption::AtMost(mode.refresh().map_or(2000.0, f64::from)),
+            resolution: ModeOption::AtMost(resolution),
+        };
+
+        option(label, ExternalDisplaySettings::Preference(preference))
+    });
+
+    OptionList {
+        default: serde_json::to_value(ExternalDisplaySettings::default()).ok(),
+        options: fixed.into_iter().chain(modes).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_audio_options() {
+        let list = audio_options(vec![AudioDeviceInfo {
+            name: "alsa_output.hdmi".into(),
+            description: "HDMI Output".into(),
+            channels: Some(2),
+        }]);
+
+        assert_eq!(
+            OptionList {
+                options: vec![
+                    UiOption {
+                        value: "default".into(),
+                        label: "Default".into(),
+                    },
+                    UiOption {
+                        value: "alsa_output.hdmi".into(),
+                        label: "HDMI Output".into(),
+                    },
+                ],
+                default: Some("default".into()),
+            },
+            list
+        );
+    }
+
+    #[test]
+    fn test_display_settings_options() {
+        let modes: Vec<DisplayValues> = serde_json::from_value(serde_json::json!([
+            {"width": 1920, "height": 1080, "refresh": 60.0},
+            {"width": 1280, "height": 720, "refresh": null},
+        ]))
+        .unwrap();
+
+        let list = display_settings_options(modes);
+
+        assert_eq!(
+            vec!["Previous", "Native", "1920x1080 @ 60.00", "1280x720"],
+            list.options
+                .iter()
+                .map(|o| o.label.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(serde_json::json!({"type": "Previous"})), list.default);
+
+        let preference = &list.options[3].value;
+        assert_eq!("Preference", preference["type"]);
+        assert_eq!(
+            serde_json::json!({"type": "AtMost", "value": {"w": 1280, "h": 720}}),
+            preference["value"]["resolution"]
+        );
+        assert_eq!(
+            serde_json::json!({"type": "AtMost", "value": 2000.0}),
+            preference["value"]["refresh"]
+        );
+    }
+
+    #[test]
+    fn test_option_source_format() {
+        let source = OptionSource::AudioDevices {
+            kind: AudioDeviceKind::Sink,
+        };
+
+        assert_eq!(
+            serde_json::json!({"type": "AudioDevices", "value": {"kind": "Sink"}}),
+            serde_json::to_value(source).unwrap()
+        );
+        assert_eq!(
+            OptionSource::InstalledFlatpaks,
+            serde_json::from_value(serde_json::json!({"type": "InstalledFlatpaks"})).unwrap()
+        );
+        assert_eq!(
+            OptionSource::SecondaryAppPresets {
+                installed_only: true
+            },
+            serde_json::from_value(serde_json::json!({
+                "type": "SecondaryAppPresets",
+                "value": {"installed_only": true}
+            }))
+            .unwrap()
+        );
+    }
+}
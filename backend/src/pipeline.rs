@@ -3,3 +3,4 @@ pub mod action_registar;
 pub mod data;
 pub mod dependency;
 pub mod executor;
+pub mod registry_file;
@@ -8,6 +8,8 @@ use strum::{Display, EnumIter, EnumString};
 
 use crate::macros::newtype_uuid;
 
+use self::ui_schema::ActionUiSchema;
+
 use self::cemu_layout::CemuLayout;
 use self::citra_layout::CitraLayout;
 use self::display_config::DisplayConfig;
@@ -16,7 +18,10 @@ use self::emu_source::EmuSettingsSourceConfig;
 use self::lime_3ds_layout::Lime3dsLayout;
 use self::melonds_layout::MelonDSLayout;
 use self::multi_window::main_app_automatic_windowing::MainAppAutomaticWindowing;
-use self::multi_window::secondary_app::{LaunchSecondaryAppPreset, LaunchSecondaryFlatpakApp};
+use self::multi_window::secondary_app::{
+    LaunchSecondaryApp, LaunchSecondaryAppGroup, LaunchSecondaryAppPreset,
+    LaunchSecondaryFlatpakApp,
+};
 use self::{
     multi_window::primary_windowing::MultiWindow, session_handler::DesktopSessionHandler,
     virtual_screen::VirtualScreen,
@@ -30,7 +35,7 @@ mod desktop;
 mod emu;
 
 pub mod multi_window;
-pub mod version_matchers;
+pub mod ui_schema;
 pub mod virtual_screen;
 
 pub use desktop::desktop_controller_layout_hack;
@@ -73,6 +78,15 @@ pub trait ActionImpl: DeserializeOwned + Serialize {
         Self::TYPE
     }
 
+    /// Describes the editor for the action's settings. Defaults to the fields of its JSON
+    /// schema; override to adjust widgets, labels, and placement.
+    fn ui_schema() -> ActionUiSchema
+    where
+        Self: JsonSchema + Sized,
+    {
+        ActionUiSchema::generate::<Self>()
+    }
+
     /// Whether the "setup" code should be run during the "reify" step;
     /// Generally used on "read-only" actions that define configuration
     /// used in later predicate-style actions (determining emulator version based on config files, etc).
@@ -152,6 +166,8 @@ pub enum Action {
     SourceFile(EmuSettingsSourceConfig),
     LaunchSecondaryFlatpakApp(LaunchSecondaryFlatpakApp),
     LaunchSecondaryAppPreset(LaunchSecondaryAppPreset),
+    LaunchSecondaryApp(LaunchSecondaryApp),
+    LaunchSecondaryAppGroup(LaunchSecondaryAppGroup),
     MainAppAutomaticWindowing(MainAppAutomaticWindowing),
     DesktopControllerLayoutHack(DesktopControllerLayoutHack),
 }
@@ -197,6 +213,12 @@ impl Action {
             Action::LaunchSecondaryAppPreset(a) => {
                 Action::LaunchSecondaryAppPreset(LaunchSecondaryAppPreset { id, ..a.clone() })
             }
+            Action::LaunchSecondaryApp(a) => {
+                Action::LaunchSecondaryApp(LaunchSecondaryApp { id, ..a.clone() })
+            }
+            Action::LaunchSecondaryAppGroup(a) => {
+                Action::LaunchSecondaryAppGroup(LaunchSecondaryAppGroup { id, ..a.clone() })
+            }
             Action::MainAppAutomaticWindowing(a) => {
                 Action::MainAppAutomaticWindowing(MainAppAutomaticWindowing { id, ..a.clone() })
             }
@@ -212,7 +234,18 @@ impl Action {
 
 /// This effectively acts as a typename for the action, and thus variants CANNOT be renamed without breaking things
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, Display, EnumString, EnumIter,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Deserialize,
+    Serialize,
+    Display,
+    EnumString,
+    EnumIter,
+    JsonSchema,
 )]
 pub enum ActionType {
     CemuAudio,
@@ -230,4 +263,6 @@ pub enum ActionType {
     VirtualScreen,
     LaunchSecondaryFlatpakApp,
     LaunchSecondaryAppPreset,
+    LaunchSecondaryApp,
+    LaunchSecondaryAppGroup,
 }
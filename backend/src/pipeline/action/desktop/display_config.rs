@@ -2,9 +2,12 @@ use anyhow::Context;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::pipeline::{
-    action::{ActionId, ActionImpl, ActionType},
-    dependency::Dependency,
+use crate::{
+    option_source::OptionSource,
+    pipeline::{
+        action::{ui_schema::ActionUiSchema, ActionId, ActionImpl, ActionType},
+        dependency::Dependency,
+    },
 };
 
 pub use super::common::{ExternalDisplaySettings, RelativeLocation};
@@ -93,4 +96,11 @@ impl ActionImpl for DisplayConfig {
     ) -> Vec<crate::pipeline::dependency::Dependency> {
         vec![Dependency::Display]
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        ActionUiSchema::generate::<Self>().options_from(
+            "external_display_settings",
+            OptionSource::DisplayResolutions,
+        )
+    }
 }
@@ -9,8 +9,14 @@ use serde::{Deserialize, Serialize};
 use steamdeck_controller_hidraw::SteamDeckGamepadButton;
 use xrandr::XId;
 
-use crate::pipeline::{
-    action::ActionType, data::BtnChord, dependency::Dependency, executor::PipelineContext,
+use crate::{
+    option_source::OptionSource,
+    pipeline::{
+        action::{ui_schema::ActionUiSchema, ActionType},
+        data::BtnChord,
+        dependency::Dependency,
+        executor::PipelineContext,
+    },
 };
 
 use self::ui::DeckDsUi;
@@ -379,4 +385,11 @@ impl ActionImpl for DesktopSessionHandler {
     fn get_id(&self) -> ActionId {
         self.id
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        ActionUiSchema::generate::<Self>().options_from(
+            "teardown_external_settings",
+            OptionSource::DisplayResolutions,
+        )
+    }
 }
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     pipeline::{
-        action::{ActionId, ActionImpl, ActionType},
+        action::{ui_schema::ActionUiSchema, ActionId, ActionImpl, ActionType},
         dependency::Dependency,
     },
     sys::{
@@ -72,6 +72,12 @@ impl ActionImpl for TouchConfig {
     fn get_id(&self) -> ActionId {
         self.id
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        ActionUiSchema::generate::<Self>()
+            .label("touch_mode", "Touch Display")
+            .on_qam(&["touch_mode"])
+    }
 }
 
 fn update_touch(touch_mode: TouchSelectionMode) {
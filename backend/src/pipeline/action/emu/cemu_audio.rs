@@ -1,11 +1,15 @@
 use std::path::Path;
 
 use crate::{
+    option_source::{AudioDeviceKind, OptionSource},
     pipeline::{dependency::Dependency, executor::PipelineContext},
     sys::audio::{get_audio_sinks, get_audio_sources},
 };
 
-use super::super::{emu_source::EmuSettingsSourceConfig, ActionId, ActionImpl, ActionType};
+use super::super::{
+    emu_source::EmuSettingsSourceConfig, ui_schema::ActionUiSchema, ActionId, ActionImpl,
+    ActionType,
+};
 use anyhow::{Context, Result};
 use regex::Regex;
 use schemars::JsonSchema;
@@ -272,6 +276,30 @@ impl ActionImpl for CemuAudio {
     fn get_id(&self) -> ActionId {
         self.id
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        [
+            ("tv_out", "TV Output", AudioDeviceKind::Sink),
+            ("pad_out", "Gamepad Output", AudioDeviceKind::Sink),
+            ("mic_in", "Microphone Input", AudioDeviceKind::Source),
+        ]
+        .into_iter()
+        .fold(
+            ActionUiSchema::generate::<Self>(),
+            |schema, (setting, label, kind)| {
+                let volume = format!("state.{setting}.volume");
+
+                schema
+                    .group(&format!("state.{setting}"), label)
+                    .options_from(
+                        &format!("state.{setting}.device"),
+                        OptionSource::AudioDevices { kind },
+                    )
+                    .slider(&volume, 0.0, 100.0, 1.0)
+                    .on_qam(&[volume.as_str()])
+            },
+        )
+    }
 }
 
 #[cfg(test)]
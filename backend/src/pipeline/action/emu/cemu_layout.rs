@@ -2,7 +2,10 @@ use std::path::Path;
 
 use crate::pipeline::executor::PipelineContext;
 
-use super::super::{emu_source::EmuSettingsSourceConfig, ActionId, ActionImpl, ActionType};
+use super::super::{
+    emu_source::EmuSettingsSourceConfig, ui_schema::ActionUiSchema, ActionId, ActionImpl,
+    ActionType,
+};
 use anyhow::{Context, Result};
 use regex::Regex;
 use schemars::JsonSchema;
@@ -98,6 +101,11 @@ impl ActionImpl for CemuLayout {
     fn get_id(&self) -> ActionId {
         self.id
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        ActionUiSchema::generate::<Self>()
+            .on_qam(&["layout.separate_gamepad_view", "layout.fullscreen"])
+    }
 }
 
 #[cfg(test)]
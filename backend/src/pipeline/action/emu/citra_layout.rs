@@ -1,7 +1,11 @@
 use std::path::Path;
 
 use crate::pipeline::{
-    action::{emu_source::EmuSettingsSourceConfig, ActionId, ActionImpl, ActionType},
+    action::{
+        emu_source::EmuSettingsSourceConfig,
+        ui_schema::{ActionUiSchema, UiOption, UiWidget},
+        ActionId, ActionImpl, ActionType,
+    },
     executor::PipelineContext,
 };
 
@@ -201,6 +205,31 @@ mod internal {
     }
 }
 
+impl CitraLayout {
+    /// Shared with [Lime3dsLayout](super::lime_3ds_layout::Lime3dsLayout), which has the same settings.
+    pub(super) fn customize_ui_schema(schema: ActionUiSchema) -> ActionUiSchema {
+        let options = [
+            (CitraLayoutOption::Default, "Default"),
+            (CitraLayoutOption::SingleScreen, "Single Screen"),
+            (CitraLayoutOption::LargeScreen, "Large Screen"),
+            (CitraLayoutOption::SideBySide, "Side by Side"),
+            (CitraLayoutOption::SeparateWindows, "Separate Windows"),
+            (CitraLayoutOption::HybridScreen, "Hybrid Screen"),
+        ]
+        .into_iter()
+        .map(|(option, label)| UiOption {
+            value: serde_json::to_value(option).unwrap_or_default(),
+            label: label.to_string(),
+        })
+        .collect();
+
+        schema
+            .label("layout.layout_option", "Layout")
+            .widget("layout.layout_option", UiWidget::Dropdown(options))
+            .on_qam(&["layout.layout_option", "layout.swap_screens"])
+    }
+}
+
 impl ActionImpl for CitraLayout {
     type State = internal::CitraState;
 
@@ -233,6 +262,10 @@ impl ActionImpl for CitraLayout {
     fn get_id(&self) -> ActionId {
         self.id
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        Self::customize_ui_schema(ActionUiSchema::generate::<Self>())
+    }
 }
 
 #[cfg(test)]
@@ -1,12 +1,18 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
-use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::pipeline::{dependency::Dependency, executor::PipelineContext};
+use crate::{
+    emu_discovery::version::EmuVersion,
+    pipeline::{dependency::Dependency, executor::PipelineContext},
+    sys::flatpak::get_flatpak_version,
+};
 
 use super::super::{ActionId, ActionImpl, ActionType};
 
@@ -23,9 +29,20 @@ pub enum EmuSettingsSource {
     Flatpak(FlatpakSource),
     AppImage(AppImageSource),
     EmuDeck(EmuDeckSource),
+    RetroDeck(RetroDeckSource),
     Custom(CustomEmuSource),
 }
 
+impl EmuSettingsSource {
+    /// Resolves the settings file this source points to, without checking that it exists.
+    pub fn settings_path(
+        &self,
+        ctx: &PipelineContext,
+    ) -> Result<PathBuf, EmuSettingsSourceFileError> {
+        self.settings_file(ctx)
+    }
+}
+
 #[enum_delegate::register]
 trait EmuSettingsSourceFile {
     fn settings_file(&self, ctx: &PipelineContext) -> Result<PathBuf, EmuSettingsSourceFileError>;
@@ -35,6 +52,8 @@ trait EmuSettingsSourceFile {
 pub enum EmuSettingsSourceFileError {
     #[error("Emudeck settings not found at {0}")]
     MissingEmudeckSettings(PathBuf),
+    #[error("RetroDECK settings not found at {0}")]
+    MissingRetroDeckSettings(PathBuf),
     #[error("Custom File not set at field {0}")]
     NotSet(String),
 }
@@ -66,18 +85,50 @@ pub enum FlatpakSource {
     MelonDSPrerelease,
     MelonDS,
     Lime3ds,
+    /// melonDS 1.0 or prerelease settings, picked by the installed flatpak version.
+    MelonDSDetected,
 }
 
 impl FlatpakSource {
-    fn org(&self) -> &'static str {
+    pub fn org(&self) -> &'static str {
         match self {
             FlatpakSource::Cemu => "info.cemu.Cemu",
             FlatpakSource::Citra => "org.citra_emu.citra",
             FlatpakSource::MelonDSPrerelease => "net.kuribo64.melonDS",
             FlatpakSource::MelonDS => "net.kuribo64.melonDS",
             FlatpakSource::Lime3ds => "io.github.lime3ds.Lime3DS",
+            FlatpakSource::MelonDSDetected => "net.kuribo64.melonDS",
         }
     }
+
+    /// Picks the 1.0 source if the installed flatpak is 1.0 or later. If the version
+    /// is unknown, the prerelease source is only used if its INI file is all that exists.
+    fn detect_melonds(
+        &self,
+        ctx: &PipelineContext,
+    ) -> Result<FlatpakSource, EmuSettingsSourceFileError> {
+        let installed = get_flatpak_version(self.org())
+            .inspect_err(|err| log::debug!("unable to get melonDS flatpak version: {err}"))
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<EmuVersion>().ok());
+
+        let is_v1 = match installed {
+            Some(version) => version >= EmuVersion::new(1, 0, 0),
+            None => {
+                FlatpakSource::MelonDS.settings_file(ctx)?.is_file()
+                    || !FlatpakSource::MelonDSPrerelease
+                        .settings_file(ctx)?
+                        .is_file()
+            }
+        };
+
+        Ok(if is_v1 {
+            FlatpakSource::MelonDS
+        } else {
+            FlatpakSource::MelonDSPrerelease
+        })
+    }
 }
 
 impl EmuSettingsSourceFile for FlatpakSource {
@@ -91,8 +142,11 @@ impl EmuSettingsSourceFile for FlatpakSource {
             FlatpakSource::Cemu => dir.join("config/Cemu/settings.xml"),
             FlatpakSource::Citra => dir.join("config/citra-emu/qt-config.ini"),
             FlatpakSource::MelonDSPrerelease => dir.join("config/melonDS/melonDS.ini"),
-            FlatpakSource::MelonDS => dir.join("config/melonDS/melonDS.toml"), // untested (unreleased)
+            FlatpakSource::MelonDS => dir.join("config/melonDS/melonDS.toml"),
             FlatpakSource::Lime3ds => dir.join("config/citra-emu/qt-config.ini"),
+            FlatpakSource::MelonDSDetected => {
+                return self.detect_melonds(ctx)?.settings_file(ctx);
+            }
         };
 
         Ok(res)
@@ -102,51 +156,247 @@ impl EmuSettingsSourceFile for FlatpakSource {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum EmuDeckSource {
     CemuProton,
-    // Don't really expect any other EmuDeck sources to depend on it, but hey.
+    Cemu,
+    MelonDS,
+    Citra,
+    Lime3ds,
+    Azahar,
+    Dolphin,
 }
 
-impl EmuSettingsSourceFile for EmuDeckSource {
-    fn settings_file(&self, ctx: &PipelineContext) -> Result<PathBuf, EmuSettingsSourceFileError> {
-        let emudeck_settings_file = ctx.decky_env.deck_user_home.join("emudeck/settings.sh");
+/// Typed view of `~/emudeck/settings.sh`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmuDeckSettings {
+    pub expert: bool,
+    pub emulation_path: Option<PathBuf>,
+    pub roms_path: Option<PathBuf>,
+    pub tools_path: Option<PathBuf>,
+    pub bios_path: Option<PathBuf>,
+    pub saves_path: Option<PathBuf>,
+    pub storage_path: Option<PathBuf>,
+    /// `doSetup<Emu>` toggles, keyed by emulator name (e.g. `Cemu`, `MelonDS`)
+    pub setup: HashMap<String, bool>,
+    /// `doInstall<Emu>` toggles, keyed by emulator name (e.g. `Cemu`, `MelonDS`)
+    pub install: HashMap<String, bool>,
+}
 
-        log::debug!("found emudeck settings file");
+impl EmuDeckSettings {
+    pub fn settings_file(home: &Path) -> PathBuf {
+        home.join("emudeck/settings.sh")
+    }
 
-        let emudeck_settings = std::fs::read_to_string(&emudeck_settings_file);
+    pub fn load(home: &Path) -> Result<Self, EmuSettingsSourceFileError> {
+        let settings_file = Self::settings_file(home);
 
-        let err = Err(EmuSettingsSourceFileError::MissingEmudeckSettings(
-            emudeck_settings_file,
-        ));
+        std::fs::read_to_string(&settings_file)
+            .map(|settings| Self::parse(&settings, home))
+            .map_err(|_| EmuSettingsSourceFileError::MissingEmudeckSettings(settings_file))
+    }
+
+    /// Parses the `key=value` assignments of `settings.sh`. Relative paths are resolved against `home`.
+    pub fn parse(settings: &str, home: &Path) -> Self {
+        let resolve = |value: &str| {
+            let value = value
+                .replace("$HOME", &home.to_string_lossy())
+                .replace("${HOME}", &home.to_string_lossy());
+            let value = value.strip_prefix("~/").unwrap_or(&value);
+
+            // absolute paths replace `home` when joined
+            home.join(value)
+        };
+
+        let mut res = Self::default();
 
-        match emudeck_settings {
-            Ok(emudeck_settings) => {
-                let rxp = Regex::new(r"emulationPath=(.*)")
-                    .expect("emudeck emulation path regex should be valid");
-                let found = rxp.captures(&emudeck_settings);
-
-                log::debug!("found emudeck captures {found:?}");
-
-                match found {
-                    Some(c) => {
-                        let path = Path::new(c.get(1).unwrap().as_str().trim());
-                        let resolved =
-                            if !path.is_dir() && ctx.decky_env.deck_user_home.join(path).is_dir() {
-                                ctx.decky_env.deck_user_home.join(path)
-                            } else {
-                                path.to_path_buf()
-                            };
-
-                        let cemu_proton_path = resolved.join("roms/wiiu/settings.xml");
-                        log::debug!("cemu_proton_path at {cemu_proton_path:?}",);
-
-                        match self {
-                            EmuDeckSource::CemuProton => Ok(cemu_proton_path),
-                        }
+        for line in settings.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches(|c: char| c == '"' || c == '\'');
+
+            match key.trim() {
+                "expert" => res.expert = value == "true",
+                "emulationPath" => res.emulation_path = Some(resolve(value)),
+                "romsPath" => res.roms_path = Some(resolve(value)),
+                "toolsPath" => res.tools_path = Some(resolve(value)),
+                "biosPath" => res.bios_path = Some(resolve(value)),
+                "savesPath" => res.saves_path = Some(resolve(value)),
+                "storagePath" => res.storage_path = Some(resolve(value)),
+                key => {
+                    if let Some(emu) = key.strip_prefix("doSetup") {
+                        res.setup.insert(emu.to_string(), value == "true");
+                    } else if let Some(emu) = key.strip_prefix("doInstall") {
+                        res.install.insert(emu.to_string(), value == "true");
                     }
-                    None => err,
                 }
             }
-            Err(_) => err,
         }
+
+        res
+    }
+
+    /// Whether EmuDeck installed or set up `emu`. Emulators without toggles,
+    /// e.g. from older EmuDeck versions, are assumed to be enabled.
+    pub fn is_enabled(&self, emu: &str) -> bool {
+        match (self.setup.get(emu), self.install.get(emu)) {
+            (None, None) => true,
+            (setup, install) => setup.is_some_and(|v| *v) || install.is_some_and(|v| *v),
+        }
+    }
+
+    pub fn roms_dir(&self) -> Option<PathBuf> {
+        self.roms_path
+            .clone()
+            .or_else(|| self.emulation_path.as_ref().map(|p| p.join("roms")))
+    }
+}
+
+impl EmuDeckSource {
+    /// Candidate settings files, in order of preference. Only installs enabled by the
+    /// `doSetup`/`doInstall` toggles are candidates, unless none are enabled. The first
+    /// existing candidate wins; if none exist, the first is used.
+    fn candidates(&self, settings: &EmuDeckSettings, ctx: &PipelineContext) -> Vec<PathBuf> {
+        let home = &ctx.decky_env.deck_user_home;
+        let config = home.join(".config");
+        let flatpak = |source: FlatpakSource| source.settings_file(ctx).ok();
+        let proton = settings
+            .roms_dir()
+            .map(|roms| roms.join("wiiu/settings.xml"));
+
+        // (EmuDeck toggle name, settings file) for each way EmuDeck installs the emulator;
+        // Cemu is the Proton build, and CemuNative the AppImage
+        let installs = match self {
+            EmuDeckSource::CemuProton => vec![("Cemu", proton)],
+            EmuDeckSource::Cemu => vec![
+                ("CemuNative", Some(config.join("Cemu/settings.xml"))),
+                ("Cemu", proton),
+            ],
+            EmuDeckSource::MelonDS => vec![
+                ("MelonDS", flatpak(FlatpakSource::MelonDS)),
+                ("MelonDS", flatpak(FlatpakSource::MelonDSPrerelease)),
+                ("MelonDS", Some(config.join("melonDS/melonDS.toml"))),
+            ],
+            EmuDeckSource::Citra => vec![
+                ("Citra", Some(config.join("citra-emu/qt-config.ini"))),
+                ("Citra", flatpak(FlatpakSource::Citra)),
+            ],
+            EmuDeckSource::Lime3ds => vec![
+                ("Lime3DS", Some(config.join("lime3ds-emu/qt-config.ini"))),
+                ("Lime3DS", flatpak(FlatpakSource::Lime3ds)),
+            ],
+            EmuDeckSource::Azahar => {
+                vec![("Azahar", Some(config.join("azahar-emu/qt-config.ini")))]
+            }
+            EmuDeckSource::Dolphin => vec![
+                (
+                    "Dolphin",
+                    Some(home.join(
+                        ".var/app/org.DolphinEmu.dolphin-emu/config/dolphin-emu/Dolphin.ini",
+                    )),
+                ),
+                ("Dolphin", Some(config.join("dolphin-emu/Dolphin.ini"))),
+            ],
+        };
+
+        let (enabled, disabled): (Vec<_>, Vec<_>) = installs
+            .into_iter()
+            .filter_map(|(toggle, path)| Some((settings.is_enabled(toggle), path?)))
+            .partition(|(enabled, _)| *enabled);
+
+        let installs = if enabled.is_empty() {
+            disabled
+        } else {
+            enabled
+        };
+
+        installs.into_iter().map(|(_, path)| path).collect()
+    }
+}
+
+impl EmuSettingsSourceFile for EmuDeckSource {
+    fn settings_file(&self, ctx: &PipelineContext) -> Result<PathBuf, EmuSettingsSourceFileError> {
+        let home = &ctx.decky_env.deck_user_home;
+        let settings = EmuDeckSettings::load(home)?;
+
+        log::debug!("found emudeck settings {settings:?}");
+
+        let candidates = self.candidates(&settings, ctx);
+        let resolved = candidates
+            .iter()
+            .find(|p| p.is_file())
+            .or(candidates.first())
+            .cloned()
+            .ok_or(EmuSettingsSourceFileError::MissingEmudeckSettings(
+                EmuDeckSettings::settings_file(home),
+            ))?;
+
+        log::debug!("emudeck {self:?} settings at {resolved:?}");
+
+        Ok(resolved)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum RetroDeckSource {
+    Citra,
+    Lime3ds,
+    MelonDS,
+    Cemu,
+    Dolphin,
+}
+
+const RETRODECK_FLATPAK: &str = "net.retrodeck.retrodeck";
+
+impl RetroDeckSource {
+    /// Path to `retrodeck.cfg`, which RetroDECK writes on first run.
+    pub fn retrodeck_cfg(ctx: &PipelineContext) -> PathBuf {
+        ctx.decky_env
+            .deck_user_home
+            .join(".var/app")
+            .join(RETRODECK_FLATPAK)
+            .join("config/retrodeck/retrodeck.cfg")
+    }
+}
+
+impl EmuSettingsSourceFile for RetroDeckSource {
+    fn settings_file(&self, ctx: &PipelineContext) -> Result<PathBuf, EmuSettingsSourceFileError> {
+        // `retrodeck.cfg` only tells if RetroDECK was set up; the `rdhome` it points to holds
+        // user data, while emulator configs are kept inside the RetroDECK flatpak.
+        let cfg_file = Self::retrodeck_cfg(ctx);
+        if !cfg_file.is_file() {
+            return Err(EmuSettingsSourceFileError::MissingRetroDeckSettings(
+                cfg_file,
+            ));
+        }
+
+        let config_dir = ctx
+            .decky_env
+            .deck_user_home
+            .join(".var/app")
+            .join(RETRODECK_FLATPAK)
+            .join("config");
+
+        let res = match self {
+            RetroDeckSource::Citra => config_dir.join("citra-emu/qt-config.ini"),
+            RetroDeckSource::Lime3ds => config_dir.join("lime3ds-emu/qt-config.ini"),
+            RetroDeckSource::MelonDS => {
+                let toml = config_dir.join("melonDS/melonDS.toml");
+                if toml.is_file() {
+                    toml
+                } else {
+                    config_dir.join("melonDS/melonDS.ini")
+                }
+            }
+            RetroDeckSource::Cemu => config_dir.join("Cemu/settings.xml"),
+            RetroDeckSource::Dolphin => config_dir.join("dolphin-emu/Dolphin.ini"),
+        };
+
+        Ok(res)
     }
 }
 
@@ -174,7 +424,7 @@ impl ActionImpl for EmuSettingsSourceConfig {
     const TYPE: ActionType = ActionType::SourceFile;
 
     fn should_setup_during_reify(&self) -> bool {
-        true // needed for MelonDsVersionMatcher to determine version
+        true // needed for EmuVersionMatcher to determine version
     }
 
     fn setup(&self, ctx: &mut PipelineContext) -> anyhow::Result<()> {
@@ -194,6 +444,11 @@ impl ActionImpl for EmuSettingsSourceConfig {
 
                 Ok(())
             }
+            EmuSettingsSource::RetroDeck(retrodeck) => {
+                ctx.set_state::<Self>(retrodeck.settings_file(ctx)?);
+
+                Ok(())
+            }
             EmuSettingsSource::Custom(CustomEmuSource {
                 settings_path: Some(file),
                 ..
@@ -230,6 +485,10 @@ impl ActionImpl for EmuSettingsSourceConfig {
                     path: emudeck.settings_file(ctx)?,
                     is_file: true,
                 },
+                EmuSettingsSource::RetroDeck(retrodeck) => Dependency::Path {
+                    path: retrodeck.settings_file(ctx)?,
+                    is_file: true,
+                },
                 EmuSettingsSource::Custom(CustomEmuSource {
                     settings_path: Some(file),
                     ..
@@ -246,11 +505,23 @@ impl ActionImpl for EmuSettingsSourceConfig {
 
         let dep = get_settings_file_path(self, ctx);
         match dep {
-            Ok(dep) => vec![dep],
+            Ok(dep) => {
+                if let EmuSettingsSource::RetroDeck(_) = &self.source {
+                    vec![
+                        Dependency::RetroDeckSettings(RetroDeckSource::retrodeck_cfg(ctx)),
+                        dep,
+                    ]
+                } else {
+                    vec![dep]
+                }
+            }
             Err(err) => match err {
                 EmuSettingsSourceFileError::MissingEmudeckSettings(err) => {
                     vec![Dependency::EmuDeckSettings(err)]
                 }
+                EmuSettingsSourceFileError::MissingRetroDeckSettings(err) => {
+                    vec![Dependency::RetroDeckSettings(err)]
+                }
                 EmuSettingsSourceFileError::NotSet(field) => vec![Dependency::ConfigField(field)],
             },
         }
@@ -266,7 +537,9 @@ impl ActionImpl for EmuSettingsSourceConfig {
 mod tests {
 
     use super::*;
+    use crate::{decky_env::DeckyEnv, util::create_dir_all};
     use anyhow::Result;
+    use std::sync::Arc;
 
     #[test]
     fn test_custom_serde() -> Result<()> {
@@ -281,6 +554,94 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_retrodeck_settings_file() -> Result<()> {
+        let decky_env = Arc::new(DeckyEnv::new_test("retrodeck_source"));
+        let ctx = PipelineContext::new(None, Default::default(), decky_env.clone());
+
+        let cfg = RetroDeckSource::retrodeck_cfg(&ctx);
+        assert!(matches!(
+            RetroDeckSource::MelonDS.settings_file(&ctx),
+            Err(EmuSettingsSourceFileError::MissingRetroDeckSettings(path)) if path == cfg
+        ));
+
+        create_dir_all(cfg.parent().unwrap())?;
+        std::fs::copy("test/assets/retrodeck/retrodeck.cfg", &cfg)?;
+
+        let config_dir = decky_env
+            .deck_user_home
+            .join(".var/app/net.retrodeck.retrodeck/config");
+        assert_eq!(
+            config_dir.join("melonDS/melonDS.ini"),
+            RetroDeckSource::MelonDS.settings_file(&ctx)?
+        );
+
+        create_dir_all(config_dir.join("melonDS"))?;
+        std::fs::write(config_dir.join("melonDS/melonDS.toml"), "")?;
+        assert_eq!(
+            config_dir.join("melonDS/melonDS.toml"),
+            RetroDeckSource::MelonDS.settings_file(&ctx)?
+        );
+        assert_eq!(
+            config_dir.join("Cemu/settings.xml"),
+            RetroDeckSource::Cemu.settings_file(&ctx)?
+        );
+
+        std::fs::remove_dir_all(&decky_env.deck_user_home)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_emudeck_settings() -> Result<()> {
+        let home = Path::new("/home/deck");
+        let settings = std::fs::read_to_string("test/assets/emudeck/settings.sh")?;
+        let settings = EmuDeckSettings::parse(&settings, home);
+
+        assert!(!settings.expert);
+        assert_eq!(
+            Some(PathBuf::from("/run/media/mmcblk0p1/Emulation")),
+            settings.emulation_path
+        );
+        assert_eq!(
+            Some(PathBuf::from("/run/media/mmcblk0p1/Emulation/storage")),
+            settings.storage_path
+        );
+        assert_eq!(Some(&true), settings.setup.get("MelonDS"));
+        assert_eq!(Some(&false), settings.install.get("Azahar"));
+        assert_eq!(
+            None,
+            EmuDeckSettings::parse("expert=true", home).emulation_path
+        );
+
+        assert!(settings.is_enabled("Cemu"));
+        assert!(!settings.is_enabled("CemuNative"));
+        assert!(settings.is_enabled("Ryujinx"));
+
+        let ctx = PipelineContext::new(
+            None,
+            Default::default(),
+            Arc::new(DeckyEnv {
+                deck_user_home: home.to_path_buf(),
+                ..Default::default()
+            }),
+        );
+        let proton = vec![PathBuf::from(
+            "/run/media/mmcblk0p1/Emulation/roms/wiiu/settings.xml",
+        )];
+
+        assert_eq!(
+            proton,
+            EmuDeckSource::CemuProton.candidates(&settings, &ctx)
+        );
+        // CemuNative is disabled, so the Proton build is used
+        assert_eq!(proton, EmuDeckSource::Cemu.candidates(&settings, &ctx));
+        // Lime3DS is disabled, so every install is a candidate
+        assert_eq!(2, EmuDeckSource::Lime3ds.candidates(&settings, &ctx).len());
+
+        Ok(())
+    }
 }
 
 // If "Versioned" is a Selection, how does it determine the source app to query?
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use crate::pipeline::{action::ActionId, executor::PipelineContext};
 
 use super::{
-    super::{Action, ActionImpl, ActionType, ErasedPipelineAction},
+    super::{ui_schema::ActionUiSchema, Action, ActionImpl, ActionType, ErasedPipelineAction},
     citra_layout::{CitraLayout, CitraLayoutState},
 };
 
@@ -35,4 +35,8 @@ impl ActionImpl for Lime3dsLayout {
     ) -> Vec<crate::pipeline::dependency::Dependency> {
         Action::from(self.0).get_dependencies(ctx)
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        CitraLayout::customize_ui_schema(ActionUiSchema::generate::<Self>())
+    }
 }
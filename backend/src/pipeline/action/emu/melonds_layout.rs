@@ -1,7 +1,10 @@
 use std::path::Path;
 
 use crate::pipeline::{
-    action::{emu_source::EmuSettingsSourceConfig, ActionId, ActionImpl, ActionType},
+    action::{
+        emu_source::EmuSettingsSourceConfig, ui_schema::ActionUiSchema, ActionId, ActionImpl,
+        ActionType,
+    },
     executor::PipelineContext,
 };
 
@@ -11,6 +14,10 @@ use anyhow::{anyhow, Context, Result};
 use configparser::ini::Ini;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+/// melonDS 1.0 stores per-window settings under `[Instance0.Window<index>]`.
+const TOML_INSTANCE: &str = "Instance0";
 
 #[cfg(test)]
 pub use internal::MelonDSLayoutState;
@@ -23,7 +30,7 @@ pub enum MelonDSLayoutOption {
     Vertical,   // Puts screens vertical always,
     Horizontal, // Puts screens horizonal always,
     Hybrid,     // Puts main screen large, with both screens adjacent. Overrides sizing settings.
-    Single,     // Displays only one screen, // TODO::topscreen 4, bottomscreen 5
+    Single, // Displays only one screen; the bottom screen if sizing is `BottomOnly`, the top screen otherwise.
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -32,6 +39,8 @@ pub enum MelonDSSizingOption {
     EmphasizeTop,
     EmphasizeBottom,
     Auto,
+    TopOnly,
+    BottomOnly,
 }
 
 impl MelonDSSizingOption {
@@ -41,6 +50,8 @@ impl MelonDSSizingOption {
             MelonDSSizingOption::EmphasizeTop => 1,
             MelonDSSizingOption::EmphasizeBottom => 2,
             MelonDSSizingOption::Auto => 3,
+            MelonDSSizingOption::TopOnly => 4,
+            MelonDSSizingOption::BottomOnly => 5,
         }
     }
 }
@@ -65,7 +76,13 @@ mod internal {
     #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
     pub struct MelonDSLayoutState {
         pub layout: RawMelonDSState,
+        /// Path to the settings file; INI for prerelease builds, TOML for 1.0+
         pub ini_path: PathBuf,
+        #[serde(default)]
+        pub window_index: Option<u8>,
+        /// Previous `Enabled` value of the window (TOML only)
+        #[serde(default)]
+        pub window_enabled: Option<bool>,
     }
 
     #[cfg_attr(test, derive(Default))]
@@ -118,10 +135,13 @@ impl From<MelonDSLayout> for RawMelonDSState {
                     swap_screens: if swap_screens { 1 } else { 0 },
                 }
             }
-            (MelonDSLayoutOption::Single, _, book_mode, swap_screens) => {
+            (MelonDSLayoutOption::Single, sizing, book_mode, swap_screens) => {
                 internal::RawMelonDSState {
                     layout_option: 0,
-                    sizing_option: 4,
+                    sizing_option: match sizing {
+                        MelonDSSizingOption::BottomOnly => MelonDSSizingOption::BottomOnly.raw(),
+                        _ => MelonDSSizingOption::TopOnly.raw(),
+                    },
                     rotation: if book_mode { 3 } else { 0 },
                     swap_screens: if swap_screens { 1 } else { 0 },
                 }
@@ -131,7 +151,122 @@ impl From<MelonDSLayout> for RawMelonDSState {
 }
 
 impl internal::RawMelonDSState {
-    fn read<P: AsRef<Path>>(ini_path: P) -> Result<Self> {
+    fn is_toml<P: AsRef<Path>>(path: P) -> bool {
+        path.as_ref().extension().is_some_and(|ext| ext == "toml")
+    }
+
+    /// Reads the layout for `window_index` (the main window if `None`), along with
+    /// whether that window is enabled (TOML only).
+    fn read<P: AsRef<Path>>(path: P, window_index: Option<u8>) -> Result<(Self, Option<bool>)> {
+        if Self::is_toml(&path) {
+            Self::read_toml(path, window_index.unwrap_or_default())
+        } else {
+            Self::ensure_single_window(window_index)?;
+            Ok((Self::read_ini(path)?, None))
+        }
+    }
+
+    /// Writes the layout for `window_index` (the main window if `None`), optionally
+    /// setting whether the window is enabled (TOML only).
+    fn write<P: AsRef<Path>>(
+        &self,
+        path: P,
+        window_index: Option<u8>,
+        window_enabled: Option<bool>,
+    ) -> Result<()> {
+        if Self::is_toml(&path) {
+            self.write_toml(path, window_index.unwrap_or_default(), window_enabled)
+        } else {
+            Self::ensure_single_window(window_index)?;
+            self.write_ini(path)
+        }
+    }
+
+    fn ensure_single_window(window_index: Option<u8>) -> Result<()> {
+        match window_index {
+            None | Some(0) => Ok(()),
+            Some(index) => Err(anyhow!(
+                "melonDS window {index} requires melonDS 1.0 or later (TOML settings)"
+            )),
+        }
+    }
+
+    fn window_key(window_index: u8) -> String {
+        format!("Window{window_index}")
+    }
+
+    fn read_toml<P: AsRef<Path>>(toml_path: P, window_index: u8) -> Result<(Self, Option<bool>)> {
+        let doc = Self::load_toml(&toml_path)?;
+        let window = doc
+            .get(TOML_INSTANCE)
+            .and_then(|i| i.get(Self::window_key(window_index)));
+
+        let int = |key: &str| -> u64 {
+            window
+                .and_then(|w| w.get(key))
+                .and_then(|v| v.as_integer())
+                .map(|v| v.max(0) as u64)
+                .unwrap_or_default()
+        };
+        let swap = window
+            .and_then(|w| w.get("ScreenSwap"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+        let enabled = window
+            .and_then(|w| w.get("Enabled"))
+            .and_then(|v| v.as_bool());
+
+        Ok((
+            Self {
+                layout_option: int("ScreenLayout"),
+                sizing_option: int("ScreenSizing"),
+                rotation: int("ScreenRotation"),
+                swap_screens: swap as u64,
+            },
+            enabled,
+        ))
+    }
+
+    fn write_toml<P: AsRef<Path>>(
+        &self,
+        toml_path: P,
+        window_index: u8,
+        window_enabled: Option<bool>,
+    ) -> Result<()> {
+        let mut doc = Self::load_toml(&toml_path)?;
+        let window_key = Self::window_key(window_index);
+
+        // Create missing tables as standard tables, rather than the inline tables `IndexMut` would create.
+        let window = doc
+            .entry(TOML_INSTANCE)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .with_context(|| format!("melonDS `{TOML_INSTANCE}` should be a table"))?
+            .entry(&window_key)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .with_context(|| format!("melonDS `{TOML_INSTANCE}.{window_key}` should be a table"))?;
+
+        window.insert("ScreenLayout", toml_edit::value(self.layout_option as i64));
+        window.insert("ScreenSizing", toml_edit::value(self.sizing_option as i64));
+        window.insert("ScreenRotation", toml_edit::value(self.rotation as i64));
+        window.insert("ScreenSwap", toml_edit::value(self.swap_screens != 0));
+
+        if let Some(enabled) = window_enabled {
+            window.insert("Enabled", toml_edit::value(enabled));
+        }
+
+        Ok(std::fs::write(toml_path, doc.to_string())?)
+    }
+
+    fn load_toml<P: AsRef<Path>>(toml_path: P) -> Result<DocumentMut> {
+        std::fs::read_to_string(&toml_path)
+            .with_context(|| format!("failed to load toml at {}", toml_path.as_ref().display()))?
+            .parse::<DocumentMut>()
+            .with_context(|| format!("failed to parse toml at {}", toml_path.as_ref().display()))
+    }
+
+    fn read_ini<P: AsRef<Path>>(ini_path: P) -> Result<Self> {
         let mut ini = Ini::new();
         let map = ini.load(&ini_path).map_err(|err| {
             anyhow!(
@@ -168,7 +303,7 @@ impl internal::RawMelonDSState {
         })
     }
 
-    fn write<P: AsRef<Path>>(&self, ini_path: P) -> Result<()> {
+    fn write_ini<P: AsRef<Path>>(&self, ini_path: P) -> Result<()> {
         let mut ini = Ini::new_cs();
         ini.load(&ini_path).map_err(|err| {
             anyhow!(
@@ -206,23 +341,36 @@ impl ActionImpl for MelonDSLayout {
             .get_state::<EmuSettingsSourceConfig>()
             .with_context(|| "No source file set for melonDS settings")?;
 
+        let (layout, window_enabled) =
+            internal::RawMelonDSState::read(ini_path, self.window_index)?;
+
+        // Secondary windows are disabled by default, so they need to be enabled to open on launch.
+        let enable_window = self.window_index.is_some_and(|i| i > 0).then_some(true);
+
         let current = internal::MelonDSLayoutState {
-            layout: internal::RawMelonDSState::read(ini_path)?,
+            layout,
             ini_path: ini_path.clone(),
+            window_index: self.window_index,
+            window_enabled: window_enabled.or(enable_window.map(|_| false)),
         };
 
         let raw: RawMelonDSState = (*self).into();
 
-        raw.write(ini_path).map(|_| {
-            ctx.set_state::<Self>(current);
-        })
+        raw.write(ini_path, self.window_index, enable_window)
+            .map(|_| {
+                ctx.set_state::<Self>(current);
+            })
     }
 
     fn teardown(&self, ctx: &mut PipelineContext) -> Result<()> {
         let state = ctx.get_state::<Self>();
 
         match state {
-            Some(state) => state.layout.write(&state.ini_path),
+            Some(state) => {
+                state
+                    .layout
+                    .write(&state.ini_path, state.window_index, state.window_enabled)
+            }
             None => Ok(()),
         }
     }
@@ -231,6 +379,26 @@ impl ActionImpl for MelonDSLayout {
     fn get_id(&self) -> ActionId {
         self.id
     }
+
+    fn ui_schema() -> ActionUiSchema {
+        use MelonDSLayoutOption::*;
+
+        ActionUiSchema::generate::<Self>()
+            .label("layout_option", "Layout")
+            .label("sizing_option", "Sizing")
+            // hybrid layouts ignore the sizing option
+            .visible_if(
+                "sizing_option",
+                "layout_option",
+                &[Natural, Vertical, Horizontal, Single],
+            )
+            .on_qam(&[
+                "layout_option",
+                "sizing_option",
+                "book_mode",
+                "swap_screens",
+            ])
+    }
 }
 
 #[cfg(test)]
@@ -260,11 +428,11 @@ mod tests {
         };
 
         let expected = initial;
-        let actual = internal::RawMelonDSState::read(&path)?;
+        let (actual, _) = internal::RawMelonDSState::read(&path, None)?;
 
         assert_eq!(expected, actual);
 
-        expected.write(&path)?;
+        expected.write(&path, None, None)?;
         let actual_str = std::fs::read_to_string(&path)?;
         assert_eq!(source, actual_str);
 
@@ -277,12 +445,83 @@ mod tests {
             window_index: None,
         }
         .into();
-        expected.write(&path)?;
+        expected.write(&path, None, None)?;
 
-        let actual = internal::RawMelonDSState::read(&path)?;
+        let (actual, _) = internal::RawMelonDSState::read(&path, None)?;
 
         assert_eq!(expected, actual);
 
+        assert!(expected.write(&path, Some(1), None).is_err());
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_write_melonds_toml_layout() -> Result<()> {
+        let source_path = "test/assets/melonds/melonDS.toml";
+        let source = std::fs::read_to_string(source_path)?;
+        let path = PathBuf::from("test/out/melonds/melonDS.toml");
+        create_dir_all(path.parent().unwrap())?;
+
+        std::fs::write(&path, &source)?;
+
+        let initial = internal::RawMelonDSState {
+            layout_option: 2,
+            sizing_option: 0,
+            rotation: 0,
+            swap_screens: 0,
+        };
+
+        let (actual, enabled) = internal::RawMelonDSState::read(&path, None)?;
+        assert_eq!(initial, actual);
+        assert_eq!(None, enabled);
+
+        initial.write(&path, None, None)?;
+        let actual_str = std::fs::read_to_string(&path)?;
+        assert_eq!(source, actual_str);
+
+        let top: internal::RawMelonDSState = MelonDSLayout {
+            id: ActionId::new(),
+            layout_option: MelonDSLayoutOption::Single,
+            sizing_option: MelonDSSizingOption::Even,
+            book_mode: false,
+            swap_screens: false,
+            window_index: Some(0),
+        }
+        .into();
+        let bottom: internal::RawMelonDSState = MelonDSLayout {
+            id: ActionId::new(),
+            layout_option: MelonDSLayoutOption::Single,
+            sizing_option: MelonDSSizingOption::BottomOnly,
+            book_mode: false,
+            swap_screens: false,
+            window_index: Some(1),
+        }
+        .into();
+
+        assert_eq!(4, top.sizing_option);
+        assert_eq!(5, bottom.sizing_option);
+
+        top.write(&path, Some(0), None)?;
+        bottom.write(&path, Some(1), Some(true))?;
+
+        assert_eq!(
+            (top, None),
+            internal::RawMelonDSState::read(&path, Some(0))?
+        );
+        assert_eq!(
+            (bottom, Some(true)),
+            internal::RawMelonDSState::read(&path, Some(1))?
+        );
+
+        // window not present in the file yet
+        bottom.write(&path, Some(2), Some(true))?;
+        assert_eq!(
+            (bottom, Some(true)),
+            internal::RawMelonDSState::read(&path, Some(2))?
+        );
+
         std::fs::remove_file(path)?;
         Ok(())
     }
@@ -1,14 +1,22 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use anyhow::Context;
+use anyhow::{Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
 
 use crate::{
+    decky_env::DeckyEnv,
     pipeline::action::{Action, ActionId, ActionImpl, ActionType, ErasedPipelineAction},
-    settings::SteamLaunchInfo,
-    sys::{kwin::KWinClientMatcher, x_display::Resolution},
+    settings::{AppId, SteamLaunchInfo},
+    sys::{
+        kwin::{KWinClientMatcher, KWinWindowMatcher},
+        x_display::Resolution,
+    },
     util::{escape_string_for_regex, get_maybe_window_names_classes_from_title},
 };
 
@@ -21,6 +29,15 @@ pub struct MainAppAutomaticWindowing {
     pub gamescope: GamescopeOptions,
 }
 
+/// The main window selected for an app, remembered so later launches can use it
+/// instead of guessing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LearnedWindowMatch {
+    /// Regex matched against the window caption
+    pub window_matcher: String,
+    pub classes: Vec<String>,
+}
+
 #[derive(Debug, Clone, SmartDefault, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct GamescopeOptions {
     #[default(true)]
@@ -63,6 +80,161 @@ pub enum GamescopeFilter {
     Pixel,
 }
 
+/// Separates the gamescope invocation from the wrapped command in launch options.
+const GAMESCOPE_COMMAND_SEPARATOR: &str = " -- ";
+const GAMESCOPE_MAX_SHARPNESS: u8 = 20;
+
+impl GamescopeScaler {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            GamescopeScaler::Auto => "auto",
+            GamescopeScaler::Integer => "integer",
+            GamescopeScaler::Fit => "fit",
+            GamescopeScaler::Fill => "fill",
+            GamescopeScaler::Stretch => "stretch",
+        }
+    }
+}
+
+impl GamescopeFilter {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            GamescopeFilter::Linear => "linear",
+            GamescopeFilter::Fsr => "fsr",
+            GamescopeFilter::Nis => "nis",
+            GamescopeFilter::Pixel => "pixel",
+        }
+    }
+}
+
+impl GamescopeOptions {
+    /// Checks that the options make sense together when output at `output`.
+    pub fn validate(&self, output: Resolution) -> Result<()> {
+        if let Some(game) = self.game_resolution {
+            if game.w == 0 || game.h == 0 {
+                anyhow::bail!("game resolution {}x{} is invalid", game.w, game.h);
+            }
+        }
+
+        if self.game_refresh == Some(0) {
+            anyhow::bail!("game refresh rate must be greater than 0");
+        }
+
+        let sharpness = match self.filter {
+            GamescopeFilter::Fsr => Some(("FSR", self.fsr_sharpness)),
+            GamescopeFilter::Nis => Some(("NIS", self.nis_sharpness)),
+            GamescopeFilter::Linear | GamescopeFilter::Pixel => None,
+        };
+
+        if let Some((filter, sharpness)) = sharpness {
+            if sharpness > GAMESCOPE_MAX_SHARPNESS {
+                anyhow::bail!(
+                    "{filter} sharpness must be between 0 and {GAMESCOPE_MAX_SHARPNESS}, got {sharpness}"
+                );
+            }
+
+            let is_upscaling = self.game_resolution.is_some_and(|game| {
+                game.w <= output.w && game.h <= output.h && (game.w, game.h) != (output.w, output.h)
+            });
+
+            if !is_upscaling {
+                anyhow::bail!(
+                    "{filter} requires upscaling; set a game resolution smaller than {}x{}",
+                    output.w,
+                    output.h
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds the gamescope invocation, excluding the wrapped command.
+    pub fn to_args(&self, output: Resolution) -> Result<Vec<String>> {
+        self.validate(output)?;
+
+        let mut args = vec![
+            "gamescope".to_string(),
+            "-e".to_string(),
+            "-W".to_string(),
+            output.w.to_string(),
+            "-H".to_string(),
+            output.h.to_string(),
+            "-F".to_string(),
+            self.filter.as_arg().to_string(),
+            "-S".to_string(),
+            self.scaler.as_arg().to_string(),
+        ];
+
+        match self.filter {
+            GamescopeFilter::Fsr => {
+                args.extend(["--sharpness".to_string(), self.fsr_sharpness.to_string()])
+            }
+            GamescopeFilter::Nis => {
+                args.extend(["--sharpness".to_string(), self.nis_sharpness.to_string()])
+            }
+            GamescopeFilter::Linear | GamescopeFilter::Pixel => (),
+        }
+
+        args.push(
+            match self.fullscreen_option {
+                GamescopeFullscreenOption::Borderless => "-b",
+                GamescopeFullscreenOption::Fullscreen => "-f",
+            }
+            .to_string(),
+        );
+
+        if let Some(game) = self.game_resolution {
+            args.extend([
+                "-w".to_string(),
+                game.w.to_string(),
+                "-h".to_string(),
+                game.h.to_string(),
+            ]);
+        }
+
+        if let Some(refresh) = self.game_refresh {
+            args.extend(["-r".to_string(), refresh.to_string()]);
+        }
+
+        Ok(args)
+    }
+
+    /// Wraps the command in `launch_options` with gamescope, replacing any gamescope
+    /// invocation already present.
+    pub fn to_launch_options(&self, output: Resolution, launch_options: &str) -> Result<String> {
+        let args = self.to_args(output)?;
+        let command = strip_gamescope_launch_options(launch_options).trim();
+
+        // Steam appends options without `%command%` as arguments to the game
+        let command = if command.is_empty() {
+            "%command%".to_string()
+        } else if command.contains("%command%") {
+            command.to_string()
+        } else {
+            format!("%command% {command}")
+        };
+
+        Ok(format!(
+            "{}{GAMESCOPE_COMMAND_SEPARATOR}{command}",
+            args.join(" ")
+        ))
+    }
+}
+
+/// Removes a leading gamescope invocation from Steam launch options, returning the wrapped command.
+pub fn strip_gamescope_launch_options(launch_options: &str) -> &str {
+    let trimmed = launch_options.trim_start();
+
+    if trimmed.starts_with("gamescope ") {
+        if let Some((_, command)) = trimmed.split_once(GAMESCOPE_COMMAND_SEPARATOR) {
+            return command;
+        }
+    }
+
+    launch_options
+}
+
 impl ActionImpl for MainAppAutomaticWindowing {
     type State = Action;
 
@@ -84,6 +256,13 @@ impl ActionImpl for MainAppAutomaticWindowing {
             .expect("main app automatic windowing requires launch info");
 
         let maybe_strings = get_maybe_window_names_from_launch_info(launch_info);
+        let app_id = launch_info.app_id.clone();
+        let learned = launch_info.learned_window.clone();
+        let known_window = learned.as_ref().and_then(|learned| {
+            KWinWindowMatcher::new(Some(&learned.window_matcher), &learned.classes)
+                .inspect_err(|err| log::warn!("ignoring invalid learned window match: {err:#?}"))
+                .ok()
+        });
 
         ctx.register_on_launch_callback(Box::new(move |_pid, ctx| {
             log::debug!("main app automatic windowing callback");
@@ -93,6 +272,7 @@ impl ActionImpl for MainAppAutomaticWindowing {
                     min_delay: Duration::from_secs(2),
                     max_delay: Duration::from_secs(30),
                     preferred_ord_if_no_match: std::cmp::Ordering::Greater,
+                    known_window: known_window.clone(),
                     maybe_strings, // match_fn: Box::new(move |clients| {
                                    //     maybe_strings;
                                    //     clients.into_iter().last().cloned()
@@ -102,16 +282,34 @@ impl ActionImpl for MainAppAutomaticWindowing {
 
             log::debug!("using {best_window:?} as app window");
 
+            let learned = match (learned, known_window) {
+                (Some(learned), Some(known)) if known.matches(&best_window) => learned,
+                _ => {
+                    let learned = LearnedWindowMatch {
+                        window_matcher: escape_string_for_regex(best_window.caption),
+                        classes: best_window.window_classes,
+                    };
+
+                    if let Err(err) = record_learned_window(&ctx.decky_env, app_id, learned.clone())
+                    {
+                        log::warn!("failed to record learned window: {err:#?}");
+                    }
+
+                    learned
+                }
+            };
+
             let multi = Action::from(MultiWindow {
                 id,
                 general: general.clone(),
                 cemu: None,
                 citra: None,
                 dolphin: None,
+                melonds: None,
                 custom: Some(CustomWindowOptions {
-                    primary_window_matcher: Some(escape_string_for_regex(best_window.caption)),
+                    primary_window_matcher: Some(learned.window_matcher),
                     secondary_window_matcher: None,
-                    classes: best_window.window_classes,
+                    classes: learned.classes,
                     ..Default::default()
                 }),
             });
@@ -142,6 +340,7 @@ impl ActionImpl for MainAppAutomaticWindowing {
                 cemu: None,
                 citra: None,
                 dolphin: None,
+                melonds: None,
                 custom: None,
             },
             ctx,
@@ -155,3 +354,138 @@ fn get_maybe_window_names_from_launch_info(launch_info: &SteamLaunchInfo) -> Vec
     maybes.push("gamescope".to_string()); // TODO::only if launching from inside gamescope
     maybes
 }
+
+/// Learned windows are recorded to a file rather than the profile db, since the
+/// server may have the db open while the pipeline runs in another process.
+fn learned_windows_path(decky_env: &DeckyEnv) -> PathBuf {
+    decky_env
+        .decky_plugin_runtime_dir
+        .join("learned_windows.json")
+}
+
+fn read_learned_windows(path: &Path) -> Result<HashMap<AppId, LearnedWindowMatch>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn record_learned_window(
+    decky_env: &DeckyEnv,
+    app_id: AppId,
+    learned: LearnedWindowMatch,
+) -> Result<()> {
+    let path = learned_windows_path(decky_env);
+    let mut pending = read_learned_windows(&path)?;
+
+    pending.insert(app_id, learned);
+
+    std::fs::write(&path, serde_json::to_string_pretty(&pending)?)
+        .with_context(|| format!("failed to write learned windows to {path:?}"))
+}
+
+/// Takes the learned windows recorded since the last call, to be saved in the profile db.
+pub fn take_learned_windows(decky_env: &DeckyEnv) -> Result<HashMap<AppId, LearnedWindowMatch>> {
+    let path = learned_windows_path(decky_env);
+    let pending = read_learned_windows(&path)?;
+
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK_RESOLUTION: Resolution = Resolution { w: 1280, h: 800 };
+
+    #[test]
+    fn test_gamescope_launch_options() -> Result<()> {
+        let options = GamescopeOptions {
+            fullscreen_option: GamescopeFullscreenOption::Borderless,
+            filter: GamescopeFilter::Fsr,
+            fsr_sharpness: 5,
+            game_resolution: Some(Resolution { w: 960, h: 600 }),
+            game_refresh: Some(60),
+            ..Default::default()
+        };
+
+        let expected = "gamescope -e -W 1280 -H 800 -F fsr -S auto --sharpness 5 -b -w 960 -h 600 -r 60 -- %command%";
+
+        assert_eq!(expected, options.to_launch_options(DECK_RESOLUTION, "")?);
+
+        assert_eq!(
+            expected.replace("%command%", "%command% -novid"),
+            options.to_launch_options(DECK_RESOLUTION, "-novid")?
+        );
+
+        // existing gamescope invocations are replaced, keeping the wrapped command
+        assert_eq!(
+            expected.replace("%command%", "DXVK_HUD=1 %command% -novid"),
+            options.to_launch_options(
+                DECK_RESOLUTION,
+                "gamescope -W 1920 -H 1080 -f -- DXVK_HUD=1 %command% -novid"
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gamescope_options_validation() {
+        let linear = GamescopeOptions::default();
+        assert!(linear.validate(DECK_RESOLUTION).is_ok());
+
+        let fsr_native = GamescopeOptions {
+            filter: GamescopeFilter::Fsr,
+            ..Default::default()
+        };
+        assert!(fsr_native.validate(DECK_RESOLUTION).is_err());
+
+        let nis_downscale = GamescopeOptions {
+            filter: GamescopeFilter::Nis,
+            game_resolution: Some(Resolution { w: 1920, h: 1080 }),
+            ..Default::default()
+        };
+        assert!(nis_downscale.validate(DECK_RESOLUTION).is_err());
+
+        let oversharp = GamescopeOptions {
+            filter: GamescopeFilter::Fsr,
+            fsr_sharpness: 21,
+            game_resolution: Some(Resolution { w: 960, h: 600 }),
+            ..Default::default()
+        };
+        assert!(oversharp.validate(DECK_RESOLUTION).is_err());
+
+        let zero_refresh = GamescopeOptions {
+            game_refresh: Some(0),
+            ..Default::default()
+        };
+        assert!(zero_refresh.validate(DECK_RESOLUTION).is_err());
+    }
+
+    #[test]
+    fn test_record_and_take_learned_windows() -> Result<()> {
+        let decky_env = DeckyEnv::new_test("learned_windows");
+        let app_id = AppId::new("12345");
+
+        let learned = LearnedWindowMatch {
+            window_matcher: escape_string_for_regex("Game (v1.0)".to_string()),
+            classes: vec!["steam_app_12345".to_string()],
+        };
+
+        record_learned_window(&decky_env, app_id.clone(), learned.clone())?;
+
+        let taken = take_learned_windows(&decky_env)?;
+        assert_eq!(Some(&learned), taken.get(&app_id));
+
+        assert!(take_learned_windows(&decky_env)?.is_empty());
+
+        Ok(())
+    }
+}
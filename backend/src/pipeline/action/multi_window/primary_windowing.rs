@@ -14,11 +14,13 @@ mod cemu_options;
 mod citra_options;
 mod custom_options;
 mod dolphin_options;
+mod melonds_options;
 
 pub use cemu_options::CemuWindowOptions;
 pub use citra_options::CitraWindowOptions;
 pub use custom_options::CustomWindowOptions;
 pub use dolphin_options::DolphinWindowOptions;
+pub use melonds_options::MelonDSWindowOptions;
 
 // This technically could be generic over the app type and just support one app,
 // but it adds a lot of boilerplate elsewhere, and I don't feel like dealing with it.
@@ -33,6 +35,9 @@ pub struct MultiWindow {
     pub citra: Option<CitraWindowOptions>,
     /// Some(options) if Dolphin is configurable, None otherwise
     pub dolphin: Option<DolphinWindowOptions>,
+    /// Some(options) if melonDS is configurable, None otherwise
+    #[serde(default)]
+    pub melonds: Option<MelonDSWindowOptions>,
     // /// Some(options) if Custom is configurable, None otherwise
     pub custom: Option<CustomWindowOptions>,
 }
@@ -65,6 +70,8 @@ pub struct MultiWindowOptions {
     pub cemu: CemuWindowOptions,
     pub citra: CitraWindowOptions,
     pub dolphin: DolphinWindowOptions,
+    #[serde(default)]
+    pub melonds: MelonDSWindowOptions,
     pub custom: CustomWindowOptions,
 }
 
@@ -79,6 +86,7 @@ impl OptionsRW for MultiWindowOptions {
             cemu: CemuWindowOptions::load(kwin)?,
             citra: CitraWindowOptions::load(kwin)?,
             dolphin: DolphinWindowOptions::load(kwin)?,
+            melonds: MelonDSWindowOptions::load(kwin)?,
             custom: CustomWindowOptions::load(kwin)?,
         })
     }
@@ -88,6 +96,7 @@ impl OptionsRW for MultiWindowOptions {
         self.cemu.write(kwin)?;
         self.citra.write(kwin)?;
         self.dolphin.write(kwin)?;
+        self.melonds.write(kwin)?;
         self.custom.write(kwin)?;
 
         kwin.set_script_enabled(SCRIPT, self.enabled)?;
@@ -153,6 +162,10 @@ impl ActionImpl for MultiWindow {
             options.dolphin = dolphin;
         }
 
+        if let Some(melonds) = self.melonds.clone() {
+            options.melonds = melonds;
+        }
+
         if let Some(custom) = self.custom.clone() {
             options.custom = custom;
         }
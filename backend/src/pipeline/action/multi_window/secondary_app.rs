@@ -1,13 +1,23 @@
+mod launch_secondary_app;
+mod launch_secondary_app_group;
 mod launch_secondary_app_preset;
 mod launch_secondary_flatpak_app;
 mod secondary_app_options;
+mod supervision;
 
+use std::time::Instant;
+
+pub use launch_secondary_app::*;
+pub use launch_secondary_app_group::*;
 pub use launch_secondary_app_preset::*;
 pub use launch_secondary_flatpak_app::*;
 use nix::unistd::Pid;
 use schemars::JsonSchema;
 pub use secondary_app_options::*;
 use serde::{Deserialize, Serialize};
+pub use supervision::*;
+
+use crate::secondary_app::SecondaryApp;
 
 #[derive(Debug, Default, Clone, Copy, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
 pub enum SecondaryAppScreenPreference {
@@ -25,15 +35,43 @@ pub enum SecondaryAppWindowingBehavior {
     Unmanaged,
 }
 
+/// How fullscreen secondary apps sharing a screen are laid out. Anything other than
+/// [SecondaryAppTiling::Auto] gives the apps the whole area of their target screen.
+#[derive(Debug, Default, Clone, Copy, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub enum SecondaryAppTiling {
+    /// Lay out alongside the main app's secondary windows
+    #[default]
+    Auto,
+    /// Side by side
+    SplitHorizontal,
+    /// Top to bottom
+    SplitVertical,
+    Grid,
+    /// Overlapping, ordered by z-order
+    Stacked,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SecondaryAppState {
     pid: Option<Pid>,
     options: SecondaryAppWindowOptions,
+    /// The launched app, kept so it can be restarted by the supervisor
+    app: Option<SecondaryApp>,
+    exit_policy: SecondaryAppExitPolicy,
+    restarts: u32,
+    /// Set while waiting to restart the app after it exited
+    restart_at: Option<Instant>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct SerializableSecondaryAppState {
     options: SecondaryAppWindowOptions,
+    #[serde(default)]
+    app: Option<SecondaryApp>,
+    #[serde(default)]
+    exit_policy: SecondaryAppExitPolicy,
+    #[serde(default)]
+    restarts: u32,
 }
 
 impl Serialize for SecondaryAppState {
@@ -43,6 +81,9 @@ impl Serialize for SecondaryAppState {
     {
         SerializableSecondaryAppState {
             options: self.options.clone(),
+            app: self.app.clone(),
+            exit_policy: self.exit_policy,
+            restarts: self.restarts,
         }
         .serialize(serializer)
     }
@@ -56,6 +97,10 @@ impl<'de> Deserialize<'de> for SecondaryAppState {
         SerializableSecondaryAppState::deserialize(deserializer).map(|v| SecondaryAppState {
             pid: None,
             options: v.options,
+            app: v.app,
+            exit_policy: v.exit_policy,
+            restarts: v.restarts,
+            restart_at: None,
         })
     }
 }
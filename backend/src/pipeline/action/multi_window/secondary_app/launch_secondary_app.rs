@@ -0,0 +1,426 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use nix::{
+    errno::Errno,
+    sys::signal::{self, Signal},
+    unistd::Pid,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pipeline::{
+        action::{ActionId, ActionImpl, ActionType},
+        dependency::Dependency,
+        executor::PipelineContext,
+    },
+    secondary_app::{AppImageApp, NativeApp, SecondaryApp, SteamApp, UrlApp},
+    settings::AppId,
+    sys::kwin::KWinClientMatcher,
+    util::{escape_string_for_regex, get_maybe_window_names_classes_from_title},
+};
+
+use super::{
+    secondary_app_options::SecondaryAppWindowOptions, SecondaryAppExitPolicy,
+    SecondaryAppScreenPreference, SecondaryAppState, SecondaryAppTiling,
+    SecondaryAppWindowingBehavior,
+};
+
+/// Launches any [SecondaryApp] alongside the main app, tracking its process (if it
+/// doesn't hand off to an existing one) so it can be stopped during teardown.
+/// Flatpak apps are left running after teardown.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct LaunchSecondaryApp {
+    pub id: ActionId,
+    pub app: SecondaryApp,
+    pub windowing_behavior: SecondaryAppWindowingBehavior,
+    pub screen_preference: SecondaryAppScreenPreference,
+    #[serde(default)]
+    pub exit_policy: SecondaryAppExitPolicy,
+    #[serde(default)]
+    pub tiling: SecondaryAppTiling,
+    #[serde(default)]
+    pub z_order: u32,
+}
+
+impl ActionImpl for LaunchSecondaryApp {
+    type State = SecondaryAppState;
+
+    const TYPE: ActionType = ActionType::LaunchSecondaryApp;
+
+    fn get_id(&self) -> ActionId {
+        self.id
+    }
+
+    fn setup(&self, ctx: &mut PipelineContext) -> Result<()> {
+        let index = ctx
+            .get_state_index::<Self>()
+            .expect("state slot should exist");
+
+        let mut maybe_strings = self.app.get_maybe_window_names_classes();
+        if let SecondaryApp::Steam(app) = &self.app {
+            maybe_strings.append(&mut app.get_shortcut_window_names(ctx));
+        }
+
+        let window_ctx = ctx.kwin.start_tracking_new_windows()?;
+
+        let pid = self.app.launch()?;
+        let options = SecondaryAppWindowOptions::load(&ctx.kwin, index)
+            .with_context(|| "failed to load kwin secondary window options")?;
+
+        if pid.is_none() && self.exit_policy != SecondaryAppExitPolicy::LeaveClosed {
+            log::debug!(
+                "secondary app {:?} has no tracked process; exit policy won't apply",
+                self.app
+            );
+        }
+
+        ctx.set_state::<Self>(SecondaryAppState {
+            pid,
+            options,
+            app: Some(self.app.clone()),
+            exit_policy: self.exit_policy,
+            restarts: 0,
+            restart_at: None,
+        });
+
+        let best_window = window_ctx
+            .get_best_window_client(KWinClientMatcher {
+                min_delay: Duration::from_secs(2),
+                max_delay: Duration::from_secs(30),
+                preferred_ord_if_no_match: std::cmp::Ordering::Less,
+                maybe_strings,
+                known_window: None,
+            })?
+            .context("automatic windowing expected to find a window")?;
+
+        SecondaryAppWindowOptions {
+            window_matcher: escape_string_for_regex(best_window.caption),
+            classes: best_window.window_classes,
+            windowing_behavior: self.windowing_behavior,
+            screen_preference: self.screen_preference,
+            tiling: self.tiling,
+            z_order: self.z_order,
+        }
+        .write(&ctx.kwin, index)
+        .with_context(|| "failed to write kwin secondary window options")
+    }
+
+    fn teardown(&self, ctx: &mut PipelineContext) -> Result<()> {
+        if let Some(state) = ctx.get_state::<Self>() {
+            let index = ctx
+                .get_state_index::<Self>()
+                .expect("state slot should exist");
+
+            let _ = state.options.write(&ctx.kwin, index); // ignore result for now
+
+            // Flatpak apps are left running, as they were before other app kinds could be
+            // launched; `flatpak run` doesn't reliably forward signals to the sandboxed app.
+            match (&self.app, state.pid) {
+                (SecondaryApp::Flatpak(_), _) | (_, None) => (),
+                (_, Some(pid)) => terminate(pid),
+            }
+        };
+
+        Ok(())
+    }
+
+    fn get_dependencies(&self, _ctx: &PipelineContext) -> Vec<Dependency> {
+        self.app.get_dependencies()
+    }
+}
+
+impl SecondaryApp {
+    /// Launches the app, returning the pid of the process if it is still running.
+    pub(super) fn launch(&self) -> Result<Option<Pid>> {
+        match self {
+            SecondaryApp::Flatpak(app) => app.setup(),
+            SecondaryApp::Native(app) => app.setup(),
+            SecondaryApp::AppImage(app) => app.setup(),
+            SecondaryApp::Steam(app) => app.setup(),
+            SecondaryApp::Url(app) => app.setup(),
+        }
+    }
+
+    pub fn get_dependencies(&self) -> Vec<Dependency> {
+        let mut deps = match self {
+            SecondaryApp::Flatpak(app) => return app.get_dependencies(),
+            // the registered default has no path, so it must be set before launching
+            SecondaryApp::Native(app) if app.path.as_os_str().is_empty() => {
+                vec![Dependency::ConfigField("Path".to_string())]
+            }
+            SecondaryApp::AppImage(app) if app.path.as_os_str().is_empty() => {
+                vec![Dependency::ConfigField("Path".to_string())]
+            }
+            SecondaryApp::Native(app) => {
+                let mut deps = vec![if app.path.components().count() > 1 {
+                    Dependency::Path {
+                        path: app.path.clone(),
+                        is_file: true,
+                    }
+                } else {
+                    Dependency::System(app.path.to_string_lossy().to_string())
+                }];
+
+                if let Some(working_dir) = app.working_dir.as_ref() {
+                    deps.push(Dependency::Path {
+                        path: working_dir.clone(),
+                        is_file: false,
+                    });
+                }
+
+                deps
+            }
+            SecondaryApp::AppImage(app) => vec![Dependency::Path {
+                path: app.path.clone(),
+                is_file: true,
+            }],
+            SecondaryApp::Steam(_) => vec![Dependency::System("steam".to_string())],
+            SecondaryApp::Url(_) => vec![Dependency::System("xdg-open".to_string())],
+        };
+
+        deps.push(Dependency::System("xdotool".to_string()));
+        deps
+    }
+
+    fn get_maybe_window_names_classes(&self) -> Vec<String> {
+        match self {
+            SecondaryApp::Flatpak(app) => app.get_maybe_window_names_classes(),
+            SecondaryApp::Native(app) => names_from_path(&app.path),
+            SecondaryApp::AppImage(app) => names_from_path(&app.path),
+            SecondaryApp::Steam(app) => app.get_maybe_window_names_classes(),
+            SecondaryApp::Url(app) => app.get_maybe_window_names_classes(),
+        }
+    }
+}
+
+impl NativeApp {
+    fn setup(&self) -> Result<Option<Pid>> {
+        log::info!(
+            "launching secondary native app: {:?} {:?}",
+            self.path,
+            self.args
+        );
+
+        let mut cmd = Command::new(&self.path);
+        cmd.args(&self.args).envs(&self.env);
+
+        if let Some(working_dir) = self.working_dir.as_ref() {
+            cmd.current_dir(working_dir);
+        }
+
+        spawn_tracked(cmd, &self.path.to_string_lossy())
+    }
+}
+
+impl AppImageApp {
+    fn setup(&self) -> Result<Option<Pid>> {
+        log::info!(
+            "launching secondary AppImage: {:?} {:?}",
+            self.path,
+            self.args
+        );
+
+        let mut cmd = Command::new(&self.path);
+        cmd.args(&self.args);
+
+        spawn_tracked(cmd, &self.path.to_string_lossy())
+    }
+}
+
+impl SteamApp {
+    fn setup(&self) -> Result<Option<Pid>> {
+        log::info!("launching secondary steam app: {:?}", self.game_id);
+
+        // The steam client forwards the request to the running instance and usually exits
+        // right away, so it isn't tracked; if it's still starting up, the short-lived
+        // client process is tracked rather than the game.
+        let mut cmd = Command::new("steam");
+        cmd.arg(format!("steam://rungameid/{}", self.game_id.raw()));
+
+        spawn_tracked(cmd, "steam")
+    }
+
+    /// Non-Steam shortcuts are usually titled after the shortcut rather than the app id,
+    /// so the shortcut name from the user's `shortcuts.vdf` is matched as well.
+    fn get_shortcut_window_names(&self, ctx: &PipelineContext) -> Vec<String> {
+        let app_id = match self.game_id.raw().parse::<u64>() {
+            Ok(id) if id > u32::MAX as u64 => AppId::new(&(id >> 32).to_string()),
+            _ => return vec![],
+        };
+
+        match ctx
+            .steam_user_config()
+            .and_then(|config| config.get_shortcut(&app_id))
+        {
+            Ok(Some(shortcut)) => get_maybe_window_names_classes_from_title(&shortcut.app_name),
+            Ok(None) => vec![],
+            Err(err) => {
+                log::warn!("failed to read steam shortcut {app_id:?}: {err:#}");
+                vec![]
+            }
+        }
+    }
+
+    /// Steam sets the window class of games to `steam_app_<appid>`; non-Steam shortcut
+    /// game ids store the app id in the upper 32 bits.
+    fn get_maybe_window_names_classes(&self) -> Vec<String> {
+        match self.game_id.raw().parse::<u64>() {
+            Ok(id) if id > u32::MAX as u64 => vec![format!("steam_app_{}", id >> 32)],
+            Ok(id) => vec![format!("steam_app_{id}")],
+            Err(_) => vec![],
+        }
+    }
+}
+
+impl UrlApp {
+    fn setup(&self) -> Result<Option<Pid>> {
+        log::info!("opening secondary url: {:?}", self.url);
+
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(&self.url);
+
+        spawn_tracked(cmd, "xdg-open")
+    }
+
+    fn get_maybe_window_names_classes(&self) -> Vec<String> {
+        let host = self
+            .url
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(&self.url)
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches("www.");
+
+        let mut parts = host
+            .split('.')
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>();
+
+        // The browser window title and class are more reliable than the url
+        let browser = Command::new("xdg-settings")
+            .args(["get", "default-web-browser"])
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        if let Some(browser) = browser {
+            let id = browser.trim_end_matches(".desktop").to_string();
+            parts.extend(id.split('.').map(|v| v.to_string()));
+            parts.push(id);
+        }
+
+        parts
+    }
+}
+
+fn names_from_path(path: &Path) -> Vec<String> {
+    let Some(stem) = path.file_stem().map(|v| v.to_string_lossy().to_string()) else {
+        return vec![];
+    };
+
+    // AppImages are commonly named `<App>-<version>-<arch>.AppImage`
+    let mut parts = vec![stem.clone()];
+    if let Some((name, _)) = stem.split_once(['-', '_']) {
+        parts.push(name.to_string());
+    }
+
+    parts.append(&mut get_maybe_window_names_classes_from_title(&stem));
+    parts
+}
+
+/// Spawns `cmd`, returning the pid if the process is still running shortly after.
+/// Processes that exit successfully right away (i.e. ones that hand off to an already
+/// running instance) aren't tracked.
+pub(super) fn spawn_tracked(mut cmd: Command, name: &str) -> Result<Option<Pid>> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .spawn()
+        .with_context(|| format!("{name} process failed to spawn"))?;
+
+    sleep(Duration::from_millis(200));
+
+    match child.try_wait() {
+        Ok(Some(v)) => {
+            if v.success() {
+                log::debug!("{name} process exited immediately; not tracking");
+                Ok(None)
+            } else {
+                Err(anyhow::anyhow!("{name} exited with error: {v}"))
+            }
+        }
+        Ok(None) => Ok(Some(Pid::from_raw(child.id() as i32))),
+        Err(err) => Err(err).with_context(|| format!("error waiting for {name} process")),
+    }
+}
+
+fn terminate(pid: Pid) {
+    match signal::kill(pid, Signal::SIGTERM) {
+        Ok(()) => log::debug!("terminated secondary app process {pid}"),
+        Err(Errno::ESRCH) => log::debug!("secondary app process {pid} already exited"),
+        Err(err) => log::warn!("failed to terminate secondary app process {pid}: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::settings::GameId;
+
+    use super::*;
+
+    #[test]
+    fn test_steam_window_classes() {
+        let steam = SteamApp {
+            game_id: GameId::new("1091500"),
+        };
+        assert_eq!(
+            vec!["steam_app_1091500"],
+            steam.get_maybe_window_names_classes()
+        );
+
+        let shortcut = SteamApp {
+            game_id: GameId::new(&((3123456789u64 << 32) | 0x02000000).to_string()),
+        };
+        assert_eq!(
+            vec!["steam_app_3123456789"],
+            shortcut.get_maybe_window_names_classes()
+        );
+    }
+
+    #[test]
+    fn test_names_from_appimage_path() {
+        let names = names_from_path(&PathBuf::from(
+            "/home/deck/Applications/Cemu-2.0-x86_64.AppImage",
+        ));
+
+        assert!(names.contains(&"Cemu".to_string()));
+        assert!(names.contains(&"Cemu-2.0-x86_64".to_string()));
+    }
+
+    #[test]
+    fn test_empty_path_requires_config() {
+        let app = SecondaryApp::Native(NativeApp {
+            path: PathBuf::new(),
+            args: vec![],
+            env: Default::default(),
+            working_dir: None,
+        });
+
+        assert!(matches!(
+            app.get_dependencies().first(),
+            Some(Dependency::ConfigField(field)) if field == "Path"
+        ));
+    }
+}
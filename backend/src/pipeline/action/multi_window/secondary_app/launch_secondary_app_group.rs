@@ -0,0 +1,130 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pipeline::{
+        action::{Action, ActionId, ActionImpl, ActionType, ErasedPipelineAction},
+        dependency::Dependency,
+        executor::PipelineContext,
+    },
+    secondary_app::SecondaryApp,
+};
+
+use super::{
+    LaunchSecondaryApp, SecondaryAppExitPolicy, SecondaryAppScreenPreference, SecondaryAppTiling,
+    SecondaryAppWindowingBehavior,
+};
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct SecondaryAppGroupEntry {
+    pub app: SecondaryApp,
+    pub windowing_behavior: SecondaryAppWindowingBehavior,
+    #[serde(default)]
+    pub exit_policy: SecondaryAppExitPolicy,
+}
+
+/// Launches several secondary apps, tiled together on the same screen. Apps are
+/// launched, tiled, and stacked in the order they are listed.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct LaunchSecondaryAppGroup {
+    pub id: ActionId,
+    pub apps: Vec<SecondaryAppGroupEntry>,
+    pub screen_preference: SecondaryAppScreenPreference,
+    pub tiling: SecondaryAppTiling,
+}
+
+impl LaunchSecondaryAppGroup {
+    fn as_secondary_app_actions(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Action> + ExactSizeIterator + '_ {
+        self.apps.iter().enumerate().map(|(z_order, entry)| {
+            Action::from(LaunchSecondaryApp {
+                id: self.id,
+                app: entry.app.clone(),
+                windowing_behavior: entry.windowing_behavior,
+                screen_preference: self.screen_preference,
+                exit_policy: entry.exit_policy,
+                tiling: self.tiling,
+                z_order: z_order as u32,
+            })
+        })
+    }
+}
+
+impl ActionImpl for LaunchSecondaryAppGroup {
+    /// The number of apps that have been set up, so a partially launched group
+    /// only tears down what it launched.
+    type State = usize;
+
+    const TYPE: ActionType = ActionType::LaunchSecondaryAppGroup;
+
+    fn get_id(&self) -> ActionId {
+        self.id
+    }
+
+    fn setup(&self, ctx: &mut PipelineContext) -> Result<()> {
+        for (i, action) in self.as_secondary_app_actions().enumerate() {
+            ctx.set_state::<Self>(i + 1);
+            action.setup(ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn teardown(&self, ctx: &mut PipelineContext) -> Result<()> {
+        let launched = ctx.get_state::<Self>().copied().unwrap_or_default();
+
+        self.as_secondary_app_actions()
+            .take(launched)
+            .rev()
+            .map(|action| action.teardown(ctx))
+            .fold(Ok(()), Result::and)
+    }
+
+    fn get_dependencies(&self, _ctx: &PipelineContext) -> Vec<Dependency> {
+        self.apps
+            .iter()
+            .flat_map(|entry| entry.app.get_dependencies())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::secondary_app::UrlApp;
+
+    use super::*;
+
+    #[test]
+    fn test_group_entries_ordered_by_position() {
+        let entry = |url: &str| SecondaryAppGroupEntry {
+            app: SecondaryApp::Url(UrlApp { url: url.into() }),
+            windowing_behavior: SecondaryAppWindowingBehavior::Fullscreen,
+            exit_policy: Default::default(),
+        };
+
+        let group = LaunchSecondaryAppGroup {
+            id: ActionId::nil(),
+            apps: vec![entry("https://a.example"), entry("https://b.example")],
+            screen_preference: SecondaryAppScreenPreference::PreferSecondary,
+            tiling: SecondaryAppTiling::Grid,
+        };
+
+        let actions = group
+            .as_secondary_app_actions()
+            .map(|action| match action {
+                Action::LaunchSecondaryApp(action) => action,
+                _ => panic!("expected LaunchSecondaryApp"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(2, actions.len());
+        assert_eq!(
+            vec![0, 1],
+            actions.iter().map(|a| a.z_order).collect::<Vec<_>>()
+        );
+        assert!(actions.iter().all(|a| a.tiling == SecondaryAppTiling::Grid));
+        assert_eq!(group.apps[1].app, actions[1].app);
+    }
+}
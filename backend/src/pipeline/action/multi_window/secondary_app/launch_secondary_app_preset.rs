@@ -4,15 +4,21 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    option_source::OptionSource,
     pipeline::{
-        action::{Action, ActionId, ActionImpl, ActionType, ErasedPipelineAction},
+        action::{
+            ui_schema::ActionUiSchema, Action, ActionId, ActionImpl, ActionType,
+            ErasedPipelineAction,
+        },
         dependency::Dependency,
+        executor::PipelineContext,
     },
     secondary_app::SecondaryAppPresetId,
 };
 
 use super::{
-    LaunchSecondaryFlatpakApp, SecondaryAppScreenPreference, SecondaryAppWindowingBehavior,
+    LaunchSecondaryApp, SecondaryAppExitPolicy, SecondaryAppScreenPreference,
+    SecondaryAppWindowingBehavior,
 };
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
@@ -21,61 +27,57 @@ pub struct LaunchSecondaryAppPreset {
     pub preset: SecondaryAppPresetId,
     pub windowing_behavior: SecondaryAppWindowingBehavior,
     pub screen_preference: SecondaryAppScreenPreference,
+    #[serde(default)]
+    pub exit_policy: SecondaryAppExitPolicy,
 }
 
-impl ActionImpl for LaunchSecondaryAppPreset {
-    type State = ();
-
-    const TYPE: ActionType = ActionType::LaunchSecondaryAppPreset;
-
-    fn setup(&self, ctx: &mut crate::pipeline::executor::PipelineContext) -> Result<()> {
+impl LaunchSecondaryAppPreset {
+    fn as_secondary_app_action(&self, ctx: &PipelineContext) -> Result<Action> {
         let mut presets = ctx.secondary_app.get_presets();
 
         let preset = presets
             .remove(&self.preset)
             .with_context(|| format!("Secondary app preset {:?} should exist", self.preset))?;
 
-        match preset.app {
-            crate::secondary_app::SecondaryApp::Flatpak(app) => {
-                Action::from(LaunchSecondaryFlatpakApp {
-                    id: self.id,
-                    app,
-                    windowing_behavior: self.windowing_behavior,
-                    screen_preference: self.screen_preference,
-                })
-                .setup(ctx)
-            }
-        }
+        Ok(Action::from(LaunchSecondaryApp {
+            id: self.id,
+            app: preset.app,
+            windowing_behavior: self.windowing_behavior,
+            screen_preference: self.screen_preference,
+            exit_policy: self.exit_policy,
+            tiling: Default::default(),
+            z_order: 0,
+        }))
     }
+}
 
-    fn teardown(&self, ctx: &mut crate::pipeline::executor::PipelineContext) -> Result<()> {
-        let mut presets = ctx.secondary_app.get_presets();
+impl ActionImpl for LaunchSecondaryAppPreset {
+    type State = ();
 
-        let preset = presets
-            .remove(&self.preset)
-            .with_context(|| format!("Secondary app preset {:?} should exist", self.preset))?;
+    const TYPE: ActionType = ActionType::LaunchSecondaryAppPreset;
 
-        match preset.app {
-            crate::secondary_app::SecondaryApp::Flatpak(app) => {
-                Action::from(LaunchSecondaryFlatpakApp {
-                    id: self.id,
-                    app,
-                    windowing_behavior: self.windowing_behavior,
-                    screen_preference: self.screen_preference,
-                })
-                .teardown(ctx)
-            }
-        }
+    fn setup(&self, ctx: &mut PipelineContext) -> Result<()> {
+        self.as_secondary_app_action(ctx)?.setup(ctx)
+    }
+
+    fn teardown(&self, ctx: &mut PipelineContext) -> Result<()> {
+        self.as_secondary_app_action(ctx)?.teardown(ctx)
     }
 
     fn get_id(&self) -> ActionId {
         self.id
     }
 
-    fn get_dependencies(
-        &self,
-        _ctx: &crate::pipeline::executor::PipelineContext,
-    ) -> Vec<crate::pipeline::dependency::Dependency> {
+    fn ui_schema() -> ActionUiSchema {
+        ActionUiSchema::generate::<Self>().options_from(
+            "preset",
+            OptionSource::SecondaryAppPresets {
+                installed_only: false,
+            },
+        )
+    }
+
+    fn get_dependencies(&self, _ctx: &PipelineContext) -> Vec<Dependency> {
         vec![Dependency::SecondaryAppPreset(self.preset)]
     }
 }
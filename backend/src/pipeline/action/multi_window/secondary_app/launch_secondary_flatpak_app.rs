@@ -7,19 +7,23 @@ use serde::{Deserialize, Serialize};
 use std::thread::sleep;
 
 use crate::{
+    option_source::OptionSource,
     pipeline::{
-        action::{ActionId, ActionImpl, ActionType},
+        action::{
+            ui_schema::ActionUiSchema, Action, ActionId, ActionImpl, ActionType,
+            ErasedPipelineAction,
+        },
         dependency::Dependency,
         executor::PipelineContext,
     },
-    secondary_app::FlatpakApp,
-    sys::{flatpak::list_installed_flatpaks, kwin::KWinClientMatcher},
-    util::{escape_string_for_regex, get_maybe_window_names_classes_from_title},
+    secondary_app::{FlatpakApp, SecondaryApp},
+    sys::flatpak::list_installed_flatpaks,
+    util::get_maybe_window_names_classes_from_title,
 };
 
 use super::{
-    secondary_app_options::SecondaryAppWindowOptions, SecondaryAppScreenPreference,
-    SecondaryAppState, SecondaryAppWindowingBehavior,
+    LaunchSecondaryApp, SecondaryAppExitPolicy, SecondaryAppScreenPreference,
+    SecondaryAppWindowingBehavior,
 };
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
@@ -28,10 +32,27 @@ pub struct LaunchSecondaryFlatpakApp {
     pub app: FlatpakApp,
     pub windowing_behavior: SecondaryAppWindowingBehavior,
     pub screen_preference: SecondaryAppScreenPreference,
+    #[serde(default)]
+    pub exit_policy: SecondaryAppExitPolicy,
 }
 
+impl LaunchSecondaryFlatpakApp {
+    fn as_secondary_app_action(&self) -> Action {
+        Action::from(LaunchSecondaryApp {
+            id: self.id,
+            app: SecondaryApp::Flatpak(self.app.clone()),
+            windowing_behavior: self.windowing_behavior,
+            screen_preference: self.screen_preference,
+            exit_policy: self.exit_policy,
+            tiling: Default::default(),
+            z_order: 0,
+        })
+    }
+}
+
+/// Superseded by [LaunchSecondaryApp]; kept so existing profiles continue to work.
 impl ActionImpl for LaunchSecondaryFlatpakApp {
-    type State = SecondaryAppState;
+    type State = ();
 
     const TYPE: ActionType = ActionType::LaunchSecondaryFlatpakApp;
 
@@ -39,54 +60,18 @@ impl ActionImpl for LaunchSecondaryFlatpakApp {
         self.id
     }
 
+    fn ui_schema() -> ActionUiSchema {
+        ActionUiSchema::generate::<Self>()
+            .label("app.app_id", "Flatpak")
+            .options_from("app.app_id", OptionSource::InstalledFlatpaks)
+    }
+
     fn setup(&self, ctx: &mut PipelineContext) -> Result<()> {
-        let index = ctx
-            .get_state_index::<Self>()
-            .expect("state slot should exist");
-
-        let window_ctx = ctx.kwin.start_tracking_new_windows()?;
-
-        let pid = self
-            .app
-            .setup()?
-            .with_context(|| format!("secondary app {self:?} not running"))?;
-        let options = SecondaryAppWindowOptions::load(&ctx.kwin, index)
-            .with_context(|| "failed to load kwin secondary window options")?;
-
-        ctx.set_state::<Self>(SecondaryAppState {
-            pid: Some(pid),
-            options,
-        });
-
-        let best_window = window_ctx
-            .get_best_window_client(KWinClientMatcher {
-                min_delay: Duration::from_secs(2),
-                max_delay: Duration::from_secs(30),
-                preferred_ord_if_no_match: std::cmp::Ordering::Less,
-                maybe_strings: self.app.get_maybe_window_names_classes(),
-            })?
-            .context("automatic windowing expected to find a window")?;
-
-        SecondaryAppWindowOptions {
-            window_matcher: escape_string_for_regex(best_window.caption),
-            classes: best_window.window_classes,
-            windowing_behavior: self.windowing_behavior,
-            screen_preference: self.screen_preference,
-        }
-        .write(&ctx.kwin, index)
-        .with_context(|| "failed to write kwin secondary window options")
+        self.as_secondary_app_action().setup(ctx)
     }
 
     fn teardown(&self, ctx: &mut PipelineContext) -> Result<()> {
-        if let Some(state) = ctx.get_state::<Self>() {
-            let index = ctx
-                .get_state_index::<Self>()
-                .expect("state slot should exist");
-
-            let _ = state.options.write(&ctx.kwin, index); // ignore result for now
-        };
-
-        Ok(())
+        self.as_secondary_app_action().teardown(ctx)
     }
 
     fn get_dependencies(&self, _ctx: &PipelineContext) -> Vec<Dependency> {
@@ -95,7 +80,7 @@ impl ActionImpl for LaunchSecondaryFlatpakApp {
 }
 
 impl FlatpakApp {
-    fn setup(&self) -> Result<Option<Pid>> {
+    pub(super) fn setup(&self) -> Result<Option<Pid>> {
         log::info!(
             "launching secondary flatpak app: {:?} {:?}",
             self.app_id,
@@ -132,14 +117,14 @@ impl FlatpakApp {
         }
     }
 
-    fn get_dependencies(&self) -> Vec<Dependency> {
+    pub(super) fn get_dependencies(&self) -> Vec<Dependency> {
         vec![
             Dependency::Flatpak(self.app_id.clone()),
             Dependency::System("xdotool".to_string()),
         ]
     }
 
-    fn get_maybe_window_names_classes(&self) -> Vec<String> {
+    pub(super) fn get_maybe_window_names_classes(&self) -> Vec<String> {
         let title = list_installed_flatpaks()
             .unwrap_or_default()
             .into_iter()
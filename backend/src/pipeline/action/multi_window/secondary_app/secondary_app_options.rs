@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::pipeline::action::multi_window::SCRIPT;
 
-use super::{SecondaryAppScreenPreference, SecondaryAppWindowingBehavior};
+use super::{SecondaryAppScreenPreference, SecondaryAppTiling, SecondaryAppWindowingBehavior};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct SecondaryAppWindowOptions {
@@ -11,6 +11,11 @@ pub struct SecondaryAppWindowOptions {
     pub classes: Vec<String>,
     pub windowing_behavior: SecondaryAppWindowingBehavior,
     pub screen_preference: SecondaryAppScreenPreference,
+    #[serde(default)]
+    pub tiling: SecondaryAppTiling,
+    /// Position within the tiling of the target screen; higher values are stacked above.
+    #[serde(default)]
+    pub z_order: u32,
 }
 
 impl SecondaryAppWindowOptions {
@@ -41,11 +46,23 @@ impl SecondaryAppWindowOptions {
             .and_then(|v| serde_json::from_str(&v).ok())
             .unwrap_or(Default::default());
 
+        let tiling = kwin
+            .get_script_string_setting(SCRIPT, &format!("secondaryAppTiling{index}"))?
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or(Default::default());
+
+        let z_order = kwin
+            .get_script_string_setting(SCRIPT, &format!("secondaryAppZOrder{index}"))?
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or_default();
+
         Ok(Self {
             window_matcher,
             classes,
             windowing_behavior,
             screen_preference,
+            tiling,
+            z_order,
         })
     }
 
@@ -74,6 +91,18 @@ impl SecondaryAppWindowOptions {
             &serde_json::to_string(&self.screen_preference).unwrap(),
         )?;
 
+        kwin.set_script_string_setting(
+            SCRIPT,
+            &format!("secondaryAppTiling{index}"),
+            &serde_json::to_string(&self.tiling).unwrap(),
+        )?;
+
+        kwin.set_script_string_setting(
+            SCRIPT,
+            &format!("secondaryAppZOrder{index}"),
+            &self.z_order.to_string(),
+        )?;
+
         Ok(())
     }
 }
@@ -0,0 +1,342 @@
+use std::time::{Duration, Instant};
+
+use nix::{
+    sys::{
+        signal,
+        wait::{waitpid, WaitPidFlag, WaitStatus},
+    },
+    unistd::Pid,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pipeline::{action::session_handler::UiEvent, executor::PipelineContext},
+    secondary_app::SecondaryApp,
+};
+
+use super::{LaunchSecondaryApp, SecondaryAppState};
+
+const RESTART_BACKOFF_BASE: Duration = Duration::from_secs(1);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// What to do when a secondary app exits while the main app is still running.
+#[derive(Debug, Default, Clone, Copy, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub enum SecondaryAppExitPolicy {
+    /// Leave the app closed.
+    #[default]
+    LeaveClosed,
+    /// Restart the app with an increasing delay, giving up after `max_restarts`.
+    Restart { max_restarts: u32 },
+    /// Close the main app, ending the session.
+    EndSession,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SupervisionOutcome {
+    Unchanged,
+    /// Secondary app state changed, and should be persisted
+    Changed,
+    /// A secondary app with [SecondaryAppExitPolicy::EndSession] exited
+    EndSession,
+}
+
+/// Checks the secondary apps launched by [LaunchSecondaryApp] and applies their
+/// exit policies. Meant to be polled while the main app is running.
+pub fn supervise_secondary_apps(ctx: &mut PipelineContext) -> SupervisionOutcome {
+    let now = Instant::now();
+    let mut outcome = SupervisionOutcome::Unchanged;
+    let mut events = vec![];
+
+    for state in ctx.get_states_mut::<LaunchSecondaryApp>() {
+        let Some(app) = state.app.clone() else {
+            continue;
+        };
+
+        if let Some(restart_at) = state.restart_at {
+            if now < restart_at {
+                continue;
+            }
+
+            state.restart_at = None;
+            state.restarts += 1;
+            outcome = outcome.max(SupervisionOutcome::Changed);
+
+            match app.launch() {
+                Ok(Some(pid)) => {
+                    log::info!("restarted secondary app {app:?} as {pid}");
+                    state.pid = Some(pid);
+                    events.push(UiEvent::ClearStatus);
+                }
+                Ok(None) => {
+                    log::info!("restarted secondary app {app:?}; no process to track");
+                    events.push(UiEvent::ClearStatus);
+                }
+                Err(err) => {
+                    log::warn!("failed to restart secondary app {app:?}: {err:#?}");
+                    events.push(handle_exit(state, &app, now));
+                }
+            }
+
+            continue;
+        }
+
+        let Some(pid) = state.pid else {
+            continue;
+        };
+
+        if is_running(pid) {
+            continue;
+        }
+
+        log::info!("secondary app {app:?} ({pid}) exited");
+
+        state.pid = None;
+        outcome = outcome.max(match state.exit_policy {
+            SecondaryAppExitPolicy::EndSession => SupervisionOutcome::EndSession,
+            _ => SupervisionOutcome::Changed,
+        });
+        events.push(handle_exit(state, &app, now));
+    }
+
+    for event in events {
+        ctx.send_ui_event(event);
+    }
+
+    outcome
+}
+
+/// Applies the exit policy to an app that is no longer running, returning the status to display.
+fn handle_exit(state: &mut SecondaryAppState, app: &SecondaryApp, now: Instant) -> UiEvent {
+    let name = app_name(app);
+
+    let msg = match state.exit_policy {
+        SecondaryAppExitPolicy::LeaveClosed => format!("{name} closed"),
+        SecondaryAppExitPolicy::EndSession => format!("{name} closed; ending session..."),
+        SecondaryAppExitPolicy::Restart { max_restarts } if state.restarts < max_restarts => {
+            let delay = restart_backoff(state.restarts);
+            state.restart_at = Some(now + delay);
+
+            format!(
+                "{name} closed; restarting in {}s ({}/{max_restarts})...",
+                delay.as_secs(),
+                state.restarts + 1
+            )
+        }
+        SecondaryAppExitPolicy::Restart { max_restarts } => {
+            log::warn!("secondary app {app:?} exceeded {max_restarts} restarts; leaving closed");
+            format!("{name} closed; giving up after {max_restarts} restarts")
+        }
+    };
+
+    UiEvent::UpdateStatusMsg(msg)
+}
+
+fn restart_backoff(restarts: u32) -> Duration {
+    RESTART_BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(restarts))
+        .min(RESTART_BACKOFF_MAX)
+}
+
+fn is_running(pid: Pid) -> bool {
+    match waitpid(pid, Some(WaitPidFlag::WNOHANG)) {
+        Ok(WaitStatus::StillAlive) => true,
+        Ok(_) => false,
+        // Not our child (i.e. state loaded after a crash); fall back to checking it exists
+        Err(_) => signal::kill(pid, None).is_ok(),
+    }
+}
+
+fn app_name(app: &SecondaryApp) -> String {
+    match app {
+        SecondaryApp::Flatpak(app) => app.app_id.clone(),
+        SecondaryApp::Native(app) => app
+            .path
+            .file_name()
+            .map(|v| v.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        SecondaryApp::AppImage(app) => app
+            .path
+            .file_name()
+            .map(|v| v.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        SecondaryApp::Steam(app) => format!("Steam app {}", app.game_id.raw()),
+        SecondaryApp::Url(app) => app.url.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        decky_env::DeckyEnv,
+        pipeline::action::{multi_window::secondary_app::SecondaryAppWindowOptions, ActionType},
+        secondary_app::NativeApp,
+    };
+
+    use super::*;
+
+    /// Long enough to outlive the launch check, short enough to exit during the test.
+    const APP_LIFETIME: Duration = Duration::from_millis(400);
+
+    fn short_lived_app() -> SecondaryApp {
+        SecondaryApp::Native(NativeApp {
+            path: "sleep".into(),
+            args: vec![APP_LIFETIME.as_secs_f32().to_string()],
+            env: Default::default(),
+            working_dir: None,
+        })
+    }
+
+    /// Creates a context with a running secondary app using `exit_policy`.
+    fn launch(name: &str, exit_policy: SecondaryAppExitPolicy) -> PipelineContext {
+        let decky_env = Arc::new(DeckyEnv::new_test(name));
+        let mut ctx = PipelineContext::new(None, Default::default(), decky_env);
+        let app = short_lived_app();
+        let pid = app
+            .launch()
+            .expect("secondary app should launch")
+            .expect("secondary app should still be running");
+
+        ctx.handle_state_slot(&ActionType::LaunchSecondaryApp, true);
+        ctx.set_state::<LaunchSecondaryApp>(SecondaryAppState {
+            pid: Some(pid),
+            options: SecondaryAppWindowOptions {
+                window_matcher: "sleep".into(),
+                classes: vec![],
+                windowing_behavior: Default::default(),
+                screen_preference: Default::default(),
+                tiling: Default::default(),
+                z_order: 0,
+            },
+            app: Some(app),
+            exit_policy,
+            restarts: 0,
+            restart_at: None,
+        });
+
+        ctx
+    }
+
+    fn wait_for_exit() {
+        std::thread::sleep(APP_LIFETIME);
+    }
+
+    fn state(ctx: &PipelineContext) -> &SecondaryAppState {
+        ctx.get_state::<LaunchSecondaryApp>()
+            .expect("secondary app state should exist")
+    }
+
+    /// Skips the restart backoff, so the pending restart happens on the next poll.
+    fn skip_backoff(ctx: &mut PipelineContext) {
+        let state = ctx
+            .get_state_mut::<LaunchSecondaryApp>()
+            .expect("secondary app state should exist");
+        assert!(state.restart_at.is_some(), "restart should be scheduled");
+        state.restart_at = Some(Instant::now());
+    }
+
+    #[test]
+    fn test_restart_gives_up_after_max_restarts() {
+        let mut ctx = launch(
+            "supervision_restart",
+            SecondaryAppExitPolicy::Restart { max_restarts: 2 },
+        );
+
+        assert_eq!(
+            SupervisionOutcome::Unchanged,
+            supervise_secondary_apps(&mut ctx)
+        );
+
+        for restarts in 1..=2 {
+            wait_for_exit();
+            assert_eq!(
+                SupervisionOutcome::Changed,
+                supervise_secondary_apps(&mut ctx)
+            );
+            assert_eq!(None, state(&ctx).pid);
+
+            skip_backoff(&mut ctx);
+            assert_eq!(
+                SupervisionOutcome::Changed,
+                supervise_secondary_apps(&mut ctx)
+            );
+            assert_eq!(restarts, state(&ctx).restarts);
+            assert!(state(&ctx).pid.is_some(), "app should be restarted");
+        }
+
+        wait_for_exit();
+        assert_eq!(
+            SupervisionOutcome::Changed,
+            supervise_secondary_apps(&mut ctx)
+        );
+
+        let state = state(&ctx);
+        assert_eq!(None, state.pid);
+        assert_eq!(None, state.restart_at, "no restarts should remain");
+        assert_eq!(2, state.restarts);
+        assert_eq!(
+            SupervisionOutcome::Unchanged,
+            supervise_secondary_apps(&mut ctx)
+        );
+    }
+
+    /// Stands in for the main app, which is killed when the session ends.
+    fn spawn_main_app() -> std::process::Child {
+        std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("main app should spawn")
+    }
+
+    #[test]
+    fn test_end_session_kills_main_app() {
+        let mut ctx = launch(
+            "supervision_end_session",
+            SecondaryAppExitPolicy::EndSession,
+        );
+        let mut main_app = spawn_main_app();
+
+        assert!(ctx.supervise_secondary_apps(|| main_app.kill()).is_none());
+        assert!(main_app.try_wait().unwrap().is_none());
+
+        wait_for_exit();
+        let killed = ctx.supervise_secondary_apps(|| main_app.kill());
+        assert!(matches!(killed, Some(Ok(()))), "main app should be killed");
+        assert!(main_app.try_wait().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_leave_closed_does_nothing() {
+        let mut ctx = launch(
+            "supervision_leave_closed",
+            SecondaryAppExitPolicy::LeaveClosed,
+        );
+        let mut main_app = spawn_main_app();
+
+        wait_for_exit();
+        assert!(ctx.supervise_secondary_apps(|| main_app.kill()).is_none());
+
+        let state = state(&ctx);
+        assert_eq!(None, state.pid);
+        assert_eq!(None, state.restart_at);
+        assert_eq!(0, state.restarts);
+        assert_eq!(
+            SupervisionOutcome::Unchanged,
+            supervise_secondary_apps(&mut ctx)
+        );
+
+        assert!(main_app.try_wait().unwrap().is_none());
+        main_app.kill().unwrap();
+    }
+
+    #[test]
+    fn test_restart_backoff() {
+        assert_eq!(Duration::from_secs(1), restart_backoff(0));
+        assert_eq!(Duration::from_secs(2), restart_backoff(1));
+        assert_eq!(Duration::from_secs(16), restart_backoff(4));
+        assert_eq!(RESTART_BACKOFF_MAX, restart_backoff(5));
+        assert_eq!(RESTART_BACKOFF_MAX, restart_backoff(u32::MAX));
+    }
+}
@@ -0,0 +1,486 @@
+//! UI descriptors for action editors, so the frontend can render them generically instead
+//! of with a component per action. Fields are generated from the action's JSON schema, and
+//! actions adjust them by overriding [ActionImpl::ui_schema].
+
+use schemars::{
+    schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec},
+    JsonSchema,
+};
+use serde::Serialize;
+
+use crate::option_source::OptionSource;
+
+use super::{
+    cemu_audio::CemuAudio,
+    cemu_layout::CemuLayout,
+    citra_layout::CitraLayout,
+    desktop_controller_layout_hack::DesktopControllerLayoutHack,
+    display_config::DisplayConfig,
+    emu_source::EmuSettingsSourceConfig,
+    lime_3ds_layout::Lime3dsLayout,
+    melonds_layout::MelonDSLayout,
+    multi_window::{
+        main_app_automatic_windowing::MainAppAutomaticWindowing,
+        primary_windowing::MultiWindow,
+        secondary_app::{
+            LaunchSecondaryApp, LaunchSecondaryAppGroup, LaunchSecondaryAppPreset,
+            LaunchSecondaryFlatpakApp,
+        },
+    },
+    session_handler::DesktopSessionHandler,
+    touch_config::TouchConfig,
+    virtual_screen::VirtualScreen,
+    ActionImpl, ActionType,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ActionUiSchema {
+    pub action_type: ActionType,
+    /// The JSON schema of the action value
+    pub schema: serde_json::Value,
+    /// The editable fields, in display order
+    pub fields: Vec<UiField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct UiField {
+    /// Path of the field in the action value; empty if the whole value is a single field
+    pub path: Vec<String>,
+    pub label: String,
+    pub description: Option<String>,
+    /// Label of the section the field is shown in, if any
+    pub group: Option<String>,
+    pub widget: UiWidget,
+    /// Whether the field may be unset (`null`)
+    pub optional: bool,
+    pub placement: UiPlacement,
+    /// If set, the field is only shown while the condition holds
+    pub visible_if: Option<UiCondition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "type", content = "value")]
+pub enum UiWidget {
+    Toggle,
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+        step: Option<f64>,
+    },
+    Slider {
+        min: f64,
+        max: f64,
+        step: f64,
+    },
+    Text,
+    Dropdown(Vec<UiOption>),
+    /// Dropdown with options listed by the backend at edit time, through `get_options`
+    SourceDropdown(OptionSource),
+    /// No generic widget fits; the frontend needs a dedicated editor for the field
+    Custom,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct UiOption {
+    pub value: serde_json::Value,
+    pub label: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum UiPlacement {
+    /// Shown in the quick access menu, as well as the full page editor
+    Qam,
+    /// Shown only in the full page editor
+    #[default]
+    FullPage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct UiCondition {
+    /// Path of the field the condition checks
+    pub path: Vec<String>,
+    /// The condition holds while the field has one of these values
+    pub one_of: Vec<serde_json::Value>,
+}
+
+impl ActionUiSchema {
+    /// Generates the fields from the JSON schema of `T`. Nested structs are flattened into
+    /// their fields; values no generic widget fits use [UiWidget::Custom].
+    pub fn generate<T: ActionImpl + JsonSchema>() -> Self {
+        let root = schemars::schema_for!(T);
+        let mut fields = vec![];
+
+        collect_fields(&root, &root.schema, &mut vec![], &mut fields);
+
+        // the id is managed by the backend
+        fields.retain(|f| f.path != ["id"]);
+        for field in fields.iter_mut().filter(|f| f.path.is_empty()) {
+            field.label = humanize(&T::TYPE.to_string());
+        }
+
+        Self {
+            action_type: T::TYPE,
+            schema: serde_json::to_value(&root).unwrap_or_default(),
+            fields,
+        }
+    }
+
+    /// Applies `f` to the field at `path`, given as dot-separated names.
+    pub fn with_field<F>(mut self, path: &str, f: F) -> Self
+    where
+        F: FnOnce(&mut UiField),
+    {
+        let path = split_path(path);
+
+        match self.fields.iter_mut().find(|field| field.path == path) {
+            Some(field) => f(field),
+            None => log::warn!("{:?} has no ui field {path:?}", self.action_type),
+        }
+
+        self
+    }
+
+    pub fn label(self, path: &str, label: &str) -> Self {
+        self.with_field(path, |field| field.label = label.to_string())
+    }
+
+    pub fn widget(self, path: &str, widget: UiWidget) -> Self {
+        self.with_field(path, |field| field.widget = widget)
+    }
+
+    pub fn slider(self, path: &str, min: f64, max: f64, step: f64) -> Self {
+        self.widget(path, UiWidget::Slider { min, max, step })
+    }
+
+    pub fn options_from(self, path: &str, source: OptionSource) -> Self {
+        self.widget(path, UiWidget::SourceDropdown(source))
+    }
+
+    /// Shows the fields at `paths` in the quick access menu.
+    pub fn on_qam(self, paths: &[&str]) -> Self {
+        paths.iter().fold(self, |schema, path| {
+            schema.with_field(path, |field| field.placement = UiPlacement::Qam)
+        })
+    }
+
+    /// Shows the field at `path` only while the field at `condition` has one of `values`.
+    pub fn visible_if<V: Serialize>(self, path: &str, condition: &str, values: &[V]) -> Self {
+        let condition = UiCondition {
+            path: split_path(condition),
+            one_of: values
+                .iter()
+                .filter_map(|v| serde_json::to_value(v).ok())
+                .collect(),
+        };
+
+        self.with_field(path, |field| field.visible_if = Some(condition))
+    }
+
+    /// Sets the group label of every field under `prefix`.
+    pub fn group(mut self, prefix: &str, label: &str) -> Self {
+        let prefix = split_path(prefix);
+
+        for field in self
+            .fields
+            .iter_mut()
+            .filter(|f| f.path.starts_with(&prefix))
+        {
+            field.group = Some(label.to_string());
+        }
+
+        self
+    }
+}
+
+impl ActionType {
+    pub fn ui_schema(&self) -> ActionUiSchema {
+        match self {
+            ActionType::CemuAudio => CemuAudio::ui_schema(),
+            ActionType::CemuLayout => CemuLayout::ui_schema(),
+            ActionType::CitraLayout => CitraLayout::ui_schema(),
+            ActionType::DesktopControllerLayoutHack => DesktopControllerLayoutHack::ui_schema(),
+            ActionType::Lime3dsLayout => Lime3dsLayout::ui_schema(),
+            ActionType::DesktopSessionHandler => DesktopSessionHandler::ui_schema(),
+            ActionType::DisplayConfig => DisplayConfig::ui_schema(),
+            ActionType::MultiWindow => MultiWindow::ui_schema(),
+            ActionType::MainAppAutomaticWindowing => MainAppAutomaticWindowing::ui_schema(),
+            ActionType::MelonDSLayout => MelonDSLayout::ui_schema(),
+            ActionType::SourceFile => EmuSettingsSourceConfig::ui_schema(),
+            ActionType::TouchConfig => TouchConfig::ui_schema(),
+            ActionType::VirtualScreen => VirtualScreen::ui_schema(),
+            ActionType::LaunchSecondaryFlatpakApp => LaunchSecondaryFlatpakApp::ui_schema(),
+            ActionType::LaunchSecondaryAppPreset => LaunchSecondaryAppPreset::ui_schema(),
+            ActionType::LaunchSecondaryApp => LaunchSecondaryApp::ui_schema(),
+            ActionType::LaunchSecondaryAppGroup => LaunchSecondaryAppGroup::ui_schema(),
+        }
+    }
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split_terminator('.').map(|s| s.to_string()).collect()
+}
+
+fn collect_fields(
+    root: &RootSchema,
+    schema: &SchemaObject,
+    path: &mut Vec<String>,
+    fields: &mut Vec<UiField>,
+) {
+    let (resolved, optional) = resolve(root, schema);
+    let properties = resolved
+        .object
+        .as_ref()
+        .map(|o| &o.properties)
+        .filter(|p| !p.is_empty());
+
+    match properties {
+        // optional structs can't be flattened, since the fields would have nowhere to go
+        Some(properties) if !optional => {
+            for (name, property) in properties {
+                if let Schema::Object(property) = property {
+                    path.push(name.clone());
+                    collect_fields(root, property, path, fields);
+                    path.pop();
+                }
+            }
+        }
+        _ => {
+            let metadata = schema.metadata.as_deref();
+            let name = path.last().map(|s| s.as_str()).unwrap_or_default();
+            let parent = path.iter().rev().nth(1);
+
+            fields.push(UiField {
+                path: path.clone(),
+                label: metadata
+                    .and_then(|m| m.title.clone())
+                    .unwrap_or_else(|| humanize(name)),
+                description: metadata.and_then(|m| m.description.clone()),
+                group: parent.map(|p| humanize(p)),
+                widget: widget_for(root, resolved),
+                optional,
+                placement: UiPlacement::FullPage,
+                visible_if: None,
+            });
+        }
+    }
+}
+
+/// Follows references and strips `null` from optional schemas, returning the schema along
+/// with whether it was optional.
+fn resolve<'a>(root: &'a RootSchema, schema: &'a SchemaObject) -> (&'a SchemaObject, bool) {
+    if let Some(name) = schema
+        .reference
+        .as_deref()
+        .and_then(|r| r.strip_prefix("#/definitions/"))
+    {
+        if let Some(Schema::Object(definition)) = root.definitions.get(name) {
+            return resolve(root, definition);
+        }
+    }
+
+    if let Some(subschemas) = schema.subschemas.as_deref() {
+        if let Some([Schema::Object(single)]) = subschemas.all_of.as_deref() {
+            return resolve(root, single);
+        }
+
+        if let Some(any_of) = subschemas.any_of.as_deref() {
+            let non_null = any_of
+                .iter()
+                .filter_map(|s| match s {
+                    Schema::Object(o) if !is_null(o) => Some(o),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            if let [single] = non_null.as_slice() {
+                let (resolved, optional) = resolve(root, single);
+                return (resolved, optional || non_null.len() < any_of.len());
+            }
+        }
+    }
+
+    let optional = matches!(
+        &schema.instance_type,
+        Some(SingleOrVec::Vec(types)) if types.contains(&InstanceType::Null)
+    );
+
+    (schema, optional)
+}
+
+fn is_null(schema: &SchemaObject) -> bool {
+    matches!(&schema.instance_type, Some(SingleOrVec::Single(t)) if **t == InstanceType::Null)
+}
+
+/// The instance type of `schema`, ignoring `null`, if it has exactly one.
+fn instance_type(schema: &SchemaObject) -> Option<InstanceType> {
+    match schema.instance_type.as_ref()? {
+        SingleOrVec::Single(t) => Some(**t),
+        SingleOrVec::Vec(types) => {
+            let mut types = types.iter().filter(|t| **t != InstanceType::Null);
+
+            match (types.next(), types.next()) {
+                (Some(t), None) => Some(*t),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn widget_for(root: &RootSchema, schema: &SchemaObject) -> UiWidget {
+    if let Some(options) = enum_options(root, schema) {
+        return UiWidget::Dropdown(options);
+    }
+
+    match instance_type(schema) {
+        Some(InstanceType::Boolean) => UiWidget::Toggle,
+        Some(t @ (InstanceType::Integer | InstanceType::Number)) => {
+            let number = schema.number.as_deref();
+
+            UiWidget::Number {
+                min: number.and_then(|n| n.minimum),
+                max: number.and_then(|n| n.maximum),
+                step: (t == InstanceType::Integer).then_some(1.0),
+            }
+        }
+        Some(InstanceType::String) => UiWidget::Text,
+        _ => UiWidget::Custom,
+    }
+}
+
+/// Options for enums with only unit variants, which are serialized as strings. Variants
+/// with doc comments are generated as a `oneOf` of single value enums.
+fn enum_options(root: &RootSchema, schema: &SchemaObject) -> Option<Vec<UiOption>> {
+    let option = |value: &serde_json::Value| UiOption {
+        label: value
+            .as_str()
+            .map(humanize)
+            .unwrap_or_else(|| value.to_string()),
+        value: value.clone(),
+    };
+
+    if let Some(values) = schema.enum_values.as_ref() {
+        return Some(values.iter().filter(|v| !v.is_null()).map(option).collect());
+    }
+
+    schema
+        .subschemas
+        .as_ref()?
+        .one_of
+        .as_ref()?
+        .iter()
+        .map(|s| match s {
+            Schema::Object(o) => match resolve(root, o).0.enum_values.as_deref() {
+                Some([value]) => Some(option(value)),
+                _ => None,
+            },
+            Schema::Bool(_) => None,
+        })
+        .collect()
+}
+
+/// Turns a field or variant name into a label, e.g. `swap_screens` or `SwapScreens`
+/// into "Swap Screens".
+fn humanize(name: &str) -> String {
+    let mut label = String::new();
+    let mut prev: Option<char> = None;
+
+    for c in name.chars() {
+        match c {
+            '_' | '-' => label.push(' '),
+            c if prev.is_none_or(|p| p == '_' || p == '-') => label.extend(c.to_uppercase()),
+            c if c.is_uppercase() && prev.is_some_and(|p| p.is_lowercase() || p.is_numeric()) => {
+                label.push(' ');
+                label.push(c);
+            }
+            c => label.push(c),
+        }
+
+        prev = Some(c);
+    }
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use strum::IntoEnumIterator;
+
+    use crate::{
+        option_source::AudioDeviceKind, pipeline::action::melonds_layout::MelonDSLayoutOption,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!("Swap Screens", humanize("swap_screens"));
+        assert_eq!("Emphasize Top", humanize("EmphasizeTop"));
+        assert_eq!("Tv Out", humanize("tv_out"));
+    }
+
+    #[test]
+    fn test_all_action_ui_schemas() {
+        for action_type in ActionType::iter() {
+            let schema = action_type.ui_schema();
+
+            assert_eq!(action_type, schema.action_type);
+            assert!(!schema.fields.is_empty(), "{action_type:?} has no fields");
+            assert!(
+                schema.fields.iter().all(|f| f.path != ["id"]),
+                "{action_type:?} should not expose its id"
+            );
+        }
+    }
+
+    #[test]
+    fn test_melonds_layout_ui_schema() {
+        let schema = MelonDSLayout::ui_schema();
+        let field = |path: &[&str]| {
+            schema
+                .fields
+                .iter()
+                .find(|f| f.path == path)
+                .unwrap_or_else(|| panic!("missing field {path:?}"))
+        };
+
+        assert_eq!(UiWidget::Toggle, field(&["swap_screens"]).widget);
+        assert_eq!(UiPlacement::Qam, field(&["swap_screens"]).placement);
+
+        let window_index = field(&["window_index"]);
+        assert!(window_index.optional);
+        assert_eq!(UiPlacement::FullPage, window_index.placement);
+
+        let UiWidget::Dropdown(options) = &field(&["sizing_option"]).widget else {
+            panic!("sizing should be a dropdown");
+        };
+        assert!(options.contains(&UiOption {
+            value: "EmphasizeTop".into(),
+            label: "Emphasize Top".into(),
+        }));
+
+        let visible_if = field(&["sizing_option"]).visible_if.clone().unwrap();
+        assert_eq!(vec!["layout_option".to_string()], visible_if.path);
+        assert!(!visible_if
+            .one_of
+            .contains(&serde_json::to_value(MelonDSLayoutOption::Hybrid).unwrap()));
+    }
+
+    #[test]
+    fn test_option_source_fields() {
+        let schema = CemuAudio::ui_schema();
+        let mic = schema
+            .fields
+            .iter()
+            .find(|f| f.path == ["state", "mic_in", "device"])
+            .unwrap();
+
+        assert_eq!(
+            UiWidget::SourceDropdown(OptionSource::AudioDevices {
+                kind: AudioDeviceKind::Source
+            }),
+            mic.widget
+        );
+        assert_eq!(Some("Microphone Input".to_string()), mic.group);
+    }
+}
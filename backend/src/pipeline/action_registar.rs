@@ -1,7 +1,9 @@
 use strum::IntoEnumIterator;
 
 use crate::{
-    secondary_app::{FlatpakApp, SecondaryAppPresetId},
+    asset::AssetManager,
+    emu_discovery::{version::EmuVersionMatcher, Emulator},
+    secondary_app::{FlatpakApp, NativeApp, SecondaryApp, SecondaryAppPresetId},
     settings::ProfileId,
     sys::x_display::x_touch::TouchSelectionMode,
 };
@@ -15,20 +17,23 @@ use super::{
         display_config::DisplayConfig,
         emu_source::{
             AppImageSource, CustomEmuSource, EmuDeckSource, EmuSettingsSource,
-            EmuSettingsSourceConfig, FlatpakSource,
+            EmuSettingsSourceConfig, FlatpakSource, RetroDeckSource,
         },
         lime_3ds_layout::Lime3dsLayout,
         melonds_layout::{MelonDSLayout, MelonDSLayoutOption, MelonDSSizingOption},
         multi_window::{
             main_app_automatic_windowing::MainAppAutomaticWindowing,
             primary_windowing::{
-                CemuWindowOptions, CitraWindowOptions, GeneralOptions, MultiWindow,
+                CemuWindowOptions, CitraWindowOptions, GeneralOptions, MelonDSWindowOptions,
+                MultiWindow,
+            },
+            secondary_app::{
+                LaunchSecondaryApp, LaunchSecondaryAppGroup, LaunchSecondaryAppPreset,
+                LaunchSecondaryFlatpakApp, SecondaryAppTiling,
             },
-            secondary_app::{LaunchSecondaryAppPreset, LaunchSecondaryFlatpakApp},
         },
         session_handler::{DesktopSessionHandler, ExternalDisplaySettings, RelativeLocation},
         touch_config::TouchConfig,
-        version_matchers::MelonDSVersionMatcher,
         virtual_screen::VirtualScreen,
         ActionId,
     },
@@ -36,6 +41,7 @@ use super::{
         DefinitionSelection, PipelineActionDefinition, PipelineActionId, PipelineActionLookup,
         PipelineActionSettings, PipelineTarget, VersionConfig,
     },
+    registry_file::{parse_registry_file, read_registry_files, RegistryIssue},
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -47,6 +53,7 @@ use self::internal::{PipelineActionRegistarBuilder, PluginScopeBuilder};
 #[derive(Debug, Clone)]
 pub struct PipelineActionRegistrar {
     actions: Arc<HashMap<PipelineActionId, PipelineActionDefinition>>,
+    issues: Arc<Vec<RegistryIssue>>,
 }
 
 impl PipelineActionRegistrar {
@@ -67,6 +74,11 @@ impl PipelineActionRegistrar {
         self.actions.clone()
     }
 
+    /// Problems found loading registry files; the affected files and actions were skipped.
+    pub fn issues(&self) -> &[RegistryIssue] {
+        &self.issues
+    }
+
     pub fn toplevel(&self) -> HashMap<&PipelineActionId, &PipelineActionDefinition> {
         self.actions
             .iter()
@@ -150,6 +162,9 @@ mod internal {
     pub struct PipelineActionRegistarBuilder {
         pub(super) scopes:
             HashMap<String, HashMap<String, HashMap<String, PipelineActionDefinition>>>,
+        /// Actions added from registry files, with the file they came from
+        pub(super) file_actions: HashMap<PipelineActionId, String>,
+        pub(super) issues: Vec<RegistryIssue>,
     }
 
     impl PipelineActionRegistarBuilder {
@@ -225,8 +240,72 @@ impl PipelineActionRegistarBuilder {
         self
     }
 
+    /// Adds the actions of every registry file in the asset dir. Should be called after
+    /// the built-in scopes are added, so conflicts with them are reported.
+    pub fn with_registry_files(self, asset_manager: &AssetManager) -> Self {
+        read_registry_files(asset_manager)
+            .into_iter()
+            .fold(self, |builder, (origin, contents)| {
+                builder.with_registry_file(&origin, &contents)
+            })
+    }
+
+    /// Adds the actions of a single registry file. Actions that already exist are skipped.
+    pub fn with_registry_file(mut self, origin: &str, contents: &str) -> Self {
+        let actions = match parse_registry_file(origin, contents) {
+            Ok((actions, issues)) => {
+                self.issues.extend(issues);
+                actions
+            }
+            Err(err) => {
+                self.issues.push(RegistryIssue {
+                    origin: origin.to_string(),
+                    action: None,
+                    message: format!("{err:#}"),
+                });
+                return self;
+            }
+        };
+
+        for action in actions {
+            let id = action.id();
+            let key = match action.target {
+                Some(t) => PipelineActionId::new(&action.name)
+                    .variant(t)
+                    .raw()
+                    .to_string(),
+                None => action.name,
+            };
+
+            let group = self
+                .scopes
+                .entry(action.plugin)
+                .or_default()
+                .entry(action.group)
+                .or_default();
+
+            if group.contains_key(&key) {
+                let message = match self.file_actions.get(&id) {
+                    Some(existing) => format!("already defined in {existing}"),
+                    None => "conflicts with a built-in action".to_string(),
+                };
+
+                self.issues.push(RegistryIssue {
+                    origin: origin.to_string(),
+                    action: Some(id),
+                    message,
+                });
+            } else {
+                group.insert(key, action.builder.build(id.clone()));
+                self.file_actions.insert(id, origin.to_string());
+            }
+        }
+
+        self
+    }
+
     pub fn build(self) -> PipelineActionRegistrar {
-        let actions = self
+        let mut actions: HashMap<_, _> = self
             .scopes
             .into_iter()
             .flat_map(|(ref scope_id, scope)| {
@@ -247,8 +326,51 @@ impl PipelineActionRegistarBuilder {
             })
             .collect();
 
+        // Drop file actions that reference missing actions, until the remaining ones are
+        // consistent; removing one can invalidate another that references it.
+        let mut file_actions = self.file_actions;
+        let mut issues = self.issues;
+
+        loop {
+            let invalid = file_actions
+                .iter()
+                .filter_map(|(id, origin)| {
+                    let selection = &actions.get(id)?.settings.selection;
+                    validate_references(&actions, selection)
+                        .err()
+                        .map(|err| (id.clone(), origin.clone(), err))
+                })
+                .collect::<Vec<_>>();
+
+            if invalid.is_empty() {
+                break;
+            }
+
+            for (id, origin, err) in invalid {
+                actions.remove(&id);
+                file_actions.remove(&id);
+                issues.push(RegistryIssue {
+                    origin,
+                    action: Some(id),
+                    message: format!("{err:#}"),
+                });
+            }
+        }
+
+        for issue in issues.iter() {
+            match &issue.action {
+                Some(id) => log::warn!(
+                    "skipping registry action {id:?} from {}: {}",
+                    issue.origin,
+                    issue.message
+                ),
+                None => log::warn!("skipping registry file {}: {}", issue.origin, issue.message),
+            }
+        }
+
         PipelineActionRegistrar {
             actions: Arc::new(actions),
+            issues: Arc::new(issues),
         }
     }
 
@@ -275,6 +397,8 @@ impl PipelineActionRegistarBuilder {
                             actions: vec![
                                 PipelineActionId::new("core:secondary:launch_secondary_app_preset"),
                                 PipelineActionId::new("core:secondary:launch_secondary_flatpak_app"),
+                                PipelineActionId::new("core:secondary:launch_secondary_app"),
+                                PipelineActionId::new("core:secondary:launch_secondary_app_group"),
                             ]
                         },
                     })
@@ -294,6 +418,39 @@ impl PipelineActionRegistarBuilder {
                             },
                             windowing_behavior: Default::default(),
                             screen_preference: Default::default(),
+                            exit_policy: Default::default(),
+                        }.into()),
+                    }).with_action("launch_secondary_app", Some(PipelineTarget::Desktop), PipelineActionDefinitionBuilder {
+                        name: "Other App".into(),
+                        description: Some("Native command, AppImage, Steam app, or URL to launch along with the main Steam app.".into()),
+                        enabled: None,
+                        profile_override: None,
+                        is_visible_on_qam: true,
+                        selection: DefinitionSelection::Action(LaunchSecondaryApp {
+                            id: ActionId::nil(),
+                            app: SecondaryApp::Native(NativeApp {
+                                path: "".into(),
+                                args: vec![],
+                                env: Default::default(),
+                                working_dir: None,
+                            }),
+                            windowing_behavior: Default::default(),
+                            screen_preference: Default::default(),
+                            exit_policy: Default::default(),
+                            tiling: Default::default(),
+                            z_order: 0,
+                        }.into()),
+                    }).with_action("launch_secondary_app_group", Some(PipelineTarget::Desktop), PipelineActionDefinitionBuilder {
+                        name: "App Group".into(),
+                        description: Some("Several apps to launch along with the main Steam app, tiled together on one screen.".into()),
+                        enabled: None,
+                        profile_override: None,
+                        is_visible_on_qam: true,
+                        selection: DefinitionSelection::Action(LaunchSecondaryAppGroup {
+                            id: ActionId::nil(),
+                            apps: vec![],
+                            screen_preference: Default::default(),
+                            tiling: SecondaryAppTiling::SplitHorizontal,
                         }.into()),
                     }).with_action("launch_secondary_app_preset", Some(PipelineTarget::Desktop), PipelineActionDefinitionBuilder {
                         name: "Preset".into(),
@@ -306,6 +463,7 @@ impl PipelineActionRegistarBuilder {
                             preset: SecondaryAppPresetId::parse("fd811b3f-4e09-4828-92aa-9220239c274b"), // Youtube [Firefox]
                             windowing_behavior: Default::default(),
                             screen_preference: Default::default(),
+                            exit_policy: Default::default(),
                         }.into()),
                     })
                 })
@@ -413,6 +571,8 @@ impl PipelineActionRegistarBuilder {
                         profile_override: None,
                         selection:  DefinitionSelection::OneOf {selection: PipelineActionId::new("core:citra:flatpak_source"), actions: vec![
                             PipelineActionId::new("core:citra:flatpak_source"),
+                            PipelineActionId::new("core:citra:emudeck_source"),
+                            PipelineActionId::new("core:citra:retrodeck_source"),
                             PipelineActionId::new("core:citra:custom_source")
                         ]},
                     })
@@ -427,6 +587,28 @@ impl PipelineActionRegistarBuilder {
                             source: EmuSettingsSource::Flatpak(FlatpakSource::Citra),
                         }.into()
                     })
+                    .with_action("emudeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "EmuDeck".to_string(),
+                        description: Some("Sets the settings INI file location to the location used by EmuDeck.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::EmuDeck(EmuDeckSource::Citra)
+                        }.into(),
+                    })
+                    .with_action("retrodeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "RetroDECK".to_string(),
+                        description: Some("Sets the settings INI file location to the RetroDECK location.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::RetroDeck(RetroDeckSource::Citra)
+                        }.into(),
+                    })
                     .with_action("custom_source", None, PipelineActionDefinitionBuilder {
                         name: "Custom".to_string(),
                         description: Some("Sets the settings INI file location to a custom location.".to_string()),
@@ -481,6 +663,7 @@ impl PipelineActionRegistarBuilder {
                             citra: Some(CitraWindowOptions::default()),
                             cemu: None,
                             dolphin: None,
+                            melonds: None,
                             custom: None,
                         }.into(),
                     })
@@ -514,6 +697,8 @@ impl PipelineActionRegistarBuilder {
                         profile_override: None,
                         selection:  DefinitionSelection::OneOf {selection: PipelineActionId::new("core:lime3ds:flatpak_source"), actions: vec![
                             PipelineActionId::new("core:lime3ds:flatpak_source"),
+                            PipelineActionId::new("core:lime3ds:emudeck_source"),
+                            PipelineActionId::new("core:lime3ds:retrodeck_source"),
                             PipelineActionId::new("core:lime3ds:custom_source")
                         ]},
                     })
@@ -528,6 +713,28 @@ impl PipelineActionRegistarBuilder {
                             source: EmuSettingsSource::Flatpak(FlatpakSource::Lime3ds),
                         }.into()
                     })
+                    .with_action("emudeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "EmuDeck".to_string(),
+                        description: Some("Sets the settings INI file location to the location used by EmuDeck.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::EmuDeck(EmuDeckSource::Lime3ds)
+                        }.into(),
+                    })
+                    .with_action("retrodeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "RetroDECK".to_string(),
+                        description: Some("Sets the settings INI file location to the RetroDECK location.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::RetroDeck(RetroDeckSource::Lime3ds)
+                        }.into(),
+                    })
                     .with_action("custom_source", None, PipelineActionDefinitionBuilder {
                         name: "Custom".to_string(),
                         description: Some("Sets the settings INI file location to a custom location.".to_string()),
@@ -582,6 +789,7 @@ impl PipelineActionRegistarBuilder {
                             citra: Some(CitraWindowOptions::default()),
                             cemu: None,
                             dolphin: None,
+                            melonds: None,
                             custom: None,
                         }.into(),
                     })
@@ -621,6 +829,8 @@ impl PipelineActionRegistarBuilder {
                         selection:  DefinitionSelection::OneOf {selection: PipelineActionId::new("core:cemu:flatpak_source"), actions: vec![
                             PipelineActionId::new("core:cemu:flatpak_source"),
                             PipelineActionId::new("core:cemu:appimage_source"),
+                            PipelineActionId::new("core:cemu:emudeck_source"),
+                            PipelineActionId::new("core:cemu:retrodeck_source"),
                             PipelineActionId::new("core:cemu:custom_source")
                         ]},
                     })
@@ -646,6 +856,28 @@ impl PipelineActionRegistarBuilder {
                             source: EmuSettingsSource::AppImage(AppImageSource::Cemu)
                         }.into(),
                     })
+                    .with_action("emudeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "EmuDeck".to_string(),
+                        description: Some("Sets the settings XML file location to the location used by EmuDeck.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::EmuDeck(EmuDeckSource::Cemu)
+                        }.into(),
+                    })
+                    .with_action("retrodeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "RetroDECK".to_string(),
+                        description: Some("Sets the settings XML file location to the RetroDECK location.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::RetroDeck(RetroDeckSource::Cemu)
+                        }.into(),
+                    })
                     .with_action("custom_source", None, PipelineActionDefinitionBuilder {
                         name: "Custom".to_string(),
                         description: Some("Sets the settings XML file location to a custom location.".to_string()),
@@ -749,6 +981,7 @@ impl PipelineActionRegistarBuilder {
                             cemu: Some(CemuWindowOptions::default()),
                             citra: None,
                             dolphin: None,
+                            melonds: None,
                             custom: None,
                         }.into(),
                     })
@@ -796,9 +1029,9 @@ impl PipelineActionRegistarBuilder {
                 })
                 .with_group("melonds", |group| {
                     let melonds_name = "melonDS".to_string();
-                    let melonds_description = Some("Maps the internal and external monitor to a single virtual screen, as melonDS does not currently support multiple windows. Allows optional melonDS layout configuration.".to_string());
+                    let melonds_description = Some("Maps primary and secondary windows to different screens for melonDS 1.0, or the internal and external monitor to a single virtual screen for earlier versions. Allows optional melonDS layout configuration.".to_string());
                     let melonds_layout_name = "Layout".to_string();
-                    let melonds_layout_description = Some("Edits melonDS ini file to desired layout settings.".to_string());
+                    let melonds_layout_description = Some("Edits melonDS settings file to desired layout settings.".to_string());
 
                     group.with_action("platform", None, PipelineActionDefinitionBuilder {
                         name: melonds_name.clone(),
@@ -807,8 +1040,7 @@ impl PipelineActionRegistarBuilder {
                         profile_override: None,
                         selection: DefinitionSelection::AllOf(vec![
                             PipelineActionId::new("core:melonds:source"),
-                            PipelineActionId::new("core:melonds:single_window"),
-                            // PipelineActionId::new("core:melonds:version"),
+                            PipelineActionId::new("core:melonds:version"),
                             PipelineActionId::new("core:core:touch_config"),
                         ]),
                         is_visible_on_qam: true,
@@ -821,11 +1053,11 @@ impl PipelineActionRegistarBuilder {
                         is_visible_on_qam: true,
                         selection: DefinitionSelection::Versioned { default_action: PipelineActionId::new("core:melonds:single_window"), versions: vec![
                             VersionConfig { 
-                                matcher: Arc::new(MelonDSVersionMatcher::v1()), 
+                                matcher: Arc::new(EmuVersionMatcher::new(Emulator::MelonDS, ">=1.0").expect("melonDS v1 requirement should be valid")), 
                                 action: PipelineActionId::new("core:melonds:select_windows")
                             },
                             VersionConfig { 
-                                matcher: Arc::new(MelonDSVersionMatcher::prerelease()), 
+                                matcher: Arc::new(EmuVersionMatcher::new(Emulator::MelonDS, "<1.0").expect("melonDS prerelease requirement should be valid")), 
                                 action: PipelineActionId::new("core:melonds:single_window") 
                             }
                         ] },
@@ -843,7 +1075,7 @@ impl PipelineActionRegistarBuilder {
                         description: Some("selects window configuration for melonDS".to_string()),
                         enabled: None,
                         profile_override: None,
-                        selection: DefinitionSelection::OneOf { selection: PipelineActionId::new("core:melonds:kwin_multi_window"), actions: vec![
+                        selection: DefinitionSelection::OneOf { selection: PipelineActionId::new("core:melonds:multi_window"), actions: vec![
                             PipelineActionId::new("core:melonds:single_window"),
                             PipelineActionId::new("core:melonds:multi_window"),
                         ] },
@@ -872,21 +1104,81 @@ impl PipelineActionRegistarBuilder {
                         ]),
                         is_visible_on_qam: true,
                     })
-                    // TODO::kwin_multi_window, layout0, layout1
+                    .with_action("layout0", Some(PipelineTarget::Desktop), PipelineActionDefinitionBuilder {
+                        name: "Primary Window Layout".to_string(),
+                        description: Some("Edits melonDS toml file to show the top screen in the primary window.".to_string()),
+                        enabled: Some(true),
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: MelonDSLayout {
+                            id: ActionId::nil(),
+                            layout_option: MelonDSLayoutOption::Single,
+                            sizing_option: MelonDSSizingOption::TopOnly,
+                            book_mode: false,
+                            swap_screens: false,
+                            window_index: Some(0),
+                        }.into(),
+                    })
+                    .with_action("layout1", Some(PipelineTarget::Desktop), PipelineActionDefinitionBuilder {
+                        name: "Secondary Window Layout".to_string(),
+                        description: Some("Edits melonDS toml file to show the bottom screen in a secondary window.".to_string()),
+                        enabled: Some(true),
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: MelonDSLayout {
+                            id: ActionId::nil(),
+                            layout_option: MelonDSLayoutOption::Single,
+                            sizing_option: MelonDSSizingOption::BottomOnly,
+                            book_mode: false,
+                            swap_screens: false,
+                            window_index: Some(1),
+                        }.into(),
+                    })
+                    .with_action("kwin_multi_window", Some(PipelineTarget::Desktop), PipelineActionDefinitionBuilder {
+                        name: multi_window_name.clone(),
+                        description: multi_window_description.clone(),
+                        enabled: None,
+                        is_visible_on_qam: false,
+                        profile_override: None,
+                        selection: MultiWindow {
+                            id: ActionId::nil(),
+                            general: GeneralOptions::default(),
+                            cemu: None,
+                            citra: None,
+                            dolphin: None,
+                            melonds: Some(MelonDSWindowOptions::default()),
+                            custom: None,
+                        }.into(),
+                    })
                     .with_action("source", None, PipelineActionDefinitionBuilder {
                         name: "melonDS Settings Source".to_string(),
                         description: Some("Source file to use when editing melonDS settings.".to_string()),
                         enabled: None,
                         is_visible_on_qam: false,
                         profile_override: None,
-                        selection:  DefinitionSelection::OneOf {selection: PipelineActionId::new("core:melonds:flatpak_source"), actions: vec![
+                        selection:  DefinitionSelection::OneOf {selection: PipelineActionId::new("core:melonds:flatpak_detected_source"), actions: vec![
+                            PipelineActionId::new("core:melonds:flatpak_detected_source"),
                             PipelineActionId::new("core:melonds:flatpak_source"),
+                            PipelineActionId::new("core:melonds:flatpak_v1_source"),
+                            PipelineActionId::new("core:melonds:emudeck_source"),
+                            PipelineActionId::new("core:melonds:retrodeck_source"),
                             PipelineActionId::new("core:melonds:custom_source")
                         ]},
                     })
-                    .with_action("flatpak_source", None, PipelineActionDefinitionBuilder {
+                    .with_action("flatpak_detected_source", None, PipelineActionDefinitionBuilder {
                         name: "Flatpak".to_string(),
-                        description: Some("Sets the settings INI file location to the default Flatpak location.".to_string()),
+                        description: Some("Sets the settings file location to the default Flatpak location for the installed melonDS version.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::Flatpak(FlatpakSource::MelonDSDetected)
+                        }.into(),
+                    })
+                    .with_action("flatpak_source", None, PipelineActionDefinitionBuilder {
+                        name: "Flatpak (Prerelease)".to_string(),
+                        description: Some("Sets the settings INI file location to the default Flatpak location used by melonDS before 1.0.".to_string()),
                         enabled: None,
                         is_visible_on_qam: true,
                         profile_override: None,
@@ -895,6 +1187,39 @@ impl PipelineActionRegistarBuilder {
                             source: EmuSettingsSource::Flatpak(FlatpakSource::MelonDSPrerelease)
                         }.into(),
                     })
+                    .with_action("flatpak_v1_source", None, PipelineActionDefinitionBuilder {
+                        name: "Flatpak (1.0)".to_string(),
+                        description: Some("Sets the settings TOML file location to the default Flatpak location used by melonDS 1.0 and later.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::Flatpak(FlatpakSource::MelonDS)
+                        }.into(),
+                    })
+                    .with_action("emudeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "EmuDeck".to_string(),
+                        description: Some("Sets the settings INI file location to the location used by EmuDeck.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::EmuDeck(EmuDeckSource::MelonDS)
+                        }.into(),
+                    })
+                    .with_action("retrodeck_source", None, PipelineActionDefinitionBuilder {
+                        name: "RetroDECK".to_string(),
+                        description: Some("Sets the settings INI file location to the RetroDECK location.".to_string()),
+                        enabled: None,
+                        is_visible_on_qam: true,
+                        profile_override: None,
+                        selection: EmuSettingsSourceConfig {
+                            id: ActionId::nil(),
+                            source: EmuSettingsSource::RetroDeck(RetroDeckSource::MelonDS)
+                        }.into(),
+                    })
                     .with_action("custom_source", None, PipelineActionDefinitionBuilder {
                         name: "Custom".to_string(),
                         description: Some("Sets the settings INI file location to a custom location.".to_string()),
@@ -903,7 +1228,7 @@ impl PipelineActionRegistarBuilder {
                         profile_override: None,
                         selection: EmuSettingsSourceConfig {
                             id: ActionId::nil(),
-                            source: EmuSettingsSource::Custom(CustomEmuSource {settings_path: None, valid_ext: vec!["ini".to_string()]}),
+                            source: EmuSettingsSource::Custom(CustomEmuSource {settings_path: None, valid_ext: vec!["ini".to_string(), "toml".to_string()]}),
                         }.into()
                     })
                     .with_action("layout", Some(PipelineTarget::Desktop),     PipelineActionDefinitionBuilder {
@@ -970,6 +1295,43 @@ impl PipelineActionRegistarBuilder {
     }
 }
 
+/// Checks that every action referenced by `selection` exists, for any target.
+fn validate_references(
+    actions: &HashMap<PipelineActionId, PipelineActionDefinition>,
+    selection: &DefinitionSelection,
+) -> anyhow::Result<()> {
+    let references = match selection {
+        DefinitionSelection::Action(_) => vec![],
+        DefinitionSelection::OneOf {
+            selection,
+            actions: choices,
+        } => {
+            anyhow::ensure!(
+                choices.contains(selection),
+                "selected action {selection:?} is not one of the choices"
+            );
+            choices.iter().collect()
+        }
+        DefinitionSelection::AllOf(choices) => choices.iter().collect(),
+        DefinitionSelection::Versioned {
+            default_action,
+            versions,
+        } => versions
+            .iter()
+            .map(|v| &v.action)
+            .chain([default_action])
+            .collect(),
+    };
+
+    for id in references {
+        let exists = actions.contains_key(id)
+            || PipelineTarget::iter().any(|t| actions.contains_key(&id.variant(t)));
+        anyhow::ensure!(exists, "references missing action {id:?}");
+    }
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct PipelineActionDefinitionBuilder {
     pub name: String,
@@ -1005,6 +1367,17 @@ impl PipelineActionDefinitionBuilder {
 mod tests {
     use std::hash::RandomState;
 
+    use crate::{
+        decky_env::DeckyEnv,
+        pipeline::{
+            data::{
+                ConfigSelection, PipelineDefinition, PipelineDefinitionId, RuntimeSelection,
+                TopLevelDefinition, TopLevelId,
+            },
+            executor::PipelineContext,
+        },
+    };
+
     use super::*;
 
     #[test]
@@ -1056,4 +1429,184 @@ mod tests {
         assert!(toplevel.contains_key(&PipelineActionId::new("core:toplevel:secondary:desktop")));
         assert_eq!(toplevel.len(), 1);
     }
+
+    #[test]
+    fn test_registry_files() {
+        let read = |name: &str| std::fs::read_to_string(format!("test/assets/registry/{name}"));
+
+        let registrar = PipelineActionRegistrar::builder()
+            .with_core()
+            .with_registry_file("community.toml", &read("community.toml").unwrap())
+            .with_registry_file("conflicts.json", &read("conflicts.json").unwrap())
+            .with_registry_file("community.toml", &read("community.toml").unwrap())
+            .build();
+
+        let lookup = registrar.make_lookup(&PipelineActionId::new("community:dolphin:platform"));
+        assert_eq!(3, lookup.actions.len());
+
+        let cemu = registrar
+            .get(
+                &PipelineActionId::new("core:cemu:platform"),
+                PipelineTarget::Desktop,
+            )
+            .unwrap();
+        assert!(
+            !matches!(&cemu.settings.selection, DefinitionSelection::AllOf(a) if a.is_empty()),
+            "built-in action should not be replaced"
+        );
+        assert!(registrar
+            .get(
+                &PipelineActionId::new("core:dolphin:source"),
+                PipelineTarget::Desktop
+            )
+            .is_some());
+
+        let skipped = registrar
+            .issues()
+            .iter()
+            .map(|issue| (issue.origin.as_str(), issue.action.clone().unwrap()))
+            .collect::<HashSet<_>>();
+
+        assert_eq!(
+            HashSet::from_iter([
+                (
+                    "community.toml",
+                    PipelineActionId::new("community:dolphin:unknown")
+                ),
+                (
+                    "conflicts.json",
+                    PipelineActionId::new("core:cemu:platform")
+                ),
+                (
+                    "conflicts.json",
+                    PipelineActionId::new("core:dolphin:platform")
+                ),
+                (
+                    "community.toml",
+                    PipelineActionId::new("community:dolphin:platform")
+                ),
+                (
+                    "community.toml",
+                    PipelineActionId::new("community:dolphin:window")
+                ),
+                (
+                    "community.toml",
+                    PipelineActionId::new("community:dolphin:layout:desktop")
+                ),
+            ]),
+            skipped
+        );
+    }
+
+    /// Collects the ids of every action a runtime selection resolves to.
+    fn selected_ids(selection: &RuntimeSelection) -> HashSet<PipelineActionId> {
+        let actions: Vec<_> = match selection {
+            RuntimeSelection::Action(_) => vec![],
+            RuntimeSelection::OneOf { selection, actions } => actions
+                .iter()
+                .filter(|a| a.id.no_variant() == selection.no_variant())
+                .collect(),
+            RuntimeSelection::AllOf(actions) | RuntimeSelection::AllOfErased(actions) => {
+                actions.iter().collect()
+            }
+        };
+
+        actions
+            .into_iter()
+            .flat_map(|a| {
+                let mut ids = selected_ids(&a.selection);
+                ids.insert(a.id.no_variant());
+                ids
+            })
+            .collect()
+    }
+
+    /// Reifies the melonDS platform for the desktop against a flatpak install with
+    /// `settings_file`, selecting `source` if set.
+    fn reify_melonds(
+        name: &str,
+        settings_file: &str,
+        source: Option<&str>,
+    ) -> HashSet<PipelineActionId> {
+        let registrar = PipelineActionRegistrar::builder().with_core().build();
+        let decky_env = DeckyEnv::new_test(name);
+        let config = decky_env
+            .deck_user_home
+            .join(".var/app/net.kuribo64.melonDS/config/melonDS");
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::write(config.join(settings_file), "").unwrap();
+
+        let root = PipelineActionId::new("core:melonds:platform");
+        let mut actions = registrar.make_lookup(&root);
+        if let Some(source) = source {
+            actions
+                .actions
+                .get_mut(&PipelineActionId::new("core:melonds:source"))
+                .unwrap()
+                .selection = ConfigSelection::OneOf {
+                selection: PipelineActionId::new(source),
+            };
+        }
+
+        let definition = PipelineDefinition {
+            id: PipelineDefinitionId::nil(),
+            name: "melonDS".into(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::nil(),
+                root,
+                actions,
+            },
+            toplevel: vec![],
+            desktop_controller_layout_hack: Default::default(),
+        };
+
+        let ctx = &mut PipelineContext::new(None, Default::default(), Arc::new(decky_env));
+        let pipeline = definition.reify(&[], None, ctx, &registrar).unwrap();
+
+        selected_ids(&pipeline.targets[&PipelineTarget::Desktop])
+    }
+
+    #[test]
+    fn test_melonds_version_follows_source() {
+        let select_windows = PipelineActionId::new("core:melonds:select_windows");
+        let single_window = PipelineActionId::new("core:melonds:single_window");
+
+        let v1 = reify_melonds(
+            "melonds_v1_source",
+            "melonDS.toml",
+            Some("core:melonds:flatpak_v1_source"),
+        );
+        assert!(v1.contains(&select_windows));
+        assert!(!v1.contains(&single_window));
+
+        let prerelease = reify_melonds(
+            "melonds_prerelease_source",
+            "melonDS.ini",
+            Some("core:melonds:flatpak_source"),
+        );
+        assert!(prerelease.contains(&single_window));
+        assert!(!prerelease.contains(&select_windows));
+
+        // a 1.0 install with the prerelease source still edits the INI file it points to
+        let mismatched = reify_melonds(
+            "melonds_mismatched_source",
+            "melonDS.toml",
+            Some("core:melonds:flatpak_source"),
+        );
+        assert!(mismatched.contains(&single_window));
+    }
+
+    #[test]
+    fn test_melonds_default_source_follows_install() {
+        let select_windows = PipelineActionId::new("core:melonds:select_windows");
+        let single_window = PipelineActionId::new("core:melonds:single_window");
+
+        // without the flatpak installed on the test machine, the settings files decide
+        let v1 = reify_melonds("melonds_detected_v1", "melonDS.toml", None);
+        assert!(v1.contains(&select_windows));
+
+        let prerelease = reify_melonds("melonds_detected_prerelease", "melonDS.ini", None);
+        assert!(prerelease.contains(&single_window));
+    }
 }
@@ -75,11 +75,27 @@ pub enum PipelineTarget {
     Gamemode,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Template {
     pub id: TemplateId,
     pub pipeline: PipelineDefinition,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Where the template comes from; only user templates can be changed or deleted
+    #[serde(default)]
+    pub source: TemplateSource,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum TemplateSource {
+    /// Built into the plugin
+    #[default]
+    Builtin,
+    /// Loaded from a template file in the settings or asset dir
+    File,
+    /// Saved from a profile by the user
+    User,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -215,6 +231,9 @@ pub struct PipelineAction {
     pub is_visible_on_qam: bool,
     /// Flags whether the selection is overridden by the setting from a different profile.
     pub profile_override: Option<ProfileId>,
+    /// The parent profile the settings were inherited from, if the pipeline doesn't specify them.
+    #[serde(default)]
+    pub inherited_from: Option<ProfileId>,
     /// The value of the pipeline action
     pub selection: RuntimeSelection,
 }
@@ -348,12 +367,17 @@ impl PipelineDefinition {
         [self.toplevel.iter().collect(), vec![platform_ref]].concat()
     }
 
+    /// Reifies the pipeline. Actions the pipeline doesn't specify are inherited from `parent`
+    /// and its ancestors, before falling back to the registered defaults.
     pub fn reify<'a>(
         &'a self,
         profiles: &[CategoryProfile],
+        parent: Option<ProfileId>,
         ctx: &mut PipelineContext,
         registrar: &'a PipelineActionRegistrar,
     ) -> Result<Pipeline> {
+        let chain = inheritance_chain(parent, profiles)?;
+
         let targets = PipelineTarget::iter()
             .map(|t: PipelineTarget| {
                 // put platform after toplevel actions for now, to simplify automatic windowing, since the main app
@@ -362,7 +386,7 @@ impl PipelineDefinition {
                 let reified: Vec<_> = toplevel
                     .iter()
                     .filter(|v| actions_have_target(&v.root, t, registrar))
-                    .map(|v| v.reify(t, profiles, registrar, ctx))
+                    .map(|v| v.reify(t, profiles, &chain, registrar, ctx))
                     .filter_map(|v| v.transpose())
                     .collect::<Result<_>>()?;
 
@@ -394,19 +418,48 @@ impl PipelineDefinition {
     }
 }
 
+/// Returns the profiles inherited from, starting at `parent` and nearest first.
+/// Fails if a profile in the chain doesn't exist, or the chain has a cycle.
+pub fn inheritance_chain(
+    parent: Option<ProfileId>,
+    profiles: &[CategoryProfile],
+) -> Result<Vec<&CategoryProfile>> {
+    let mut chain: Vec<&CategoryProfile> = vec![];
+    let mut next = parent;
+
+    while let Some(id) = next {
+        if chain.iter().any(|p| p.id == id) {
+            anyhow::bail!("profile inheritance cycle at {id:?}");
+        }
+
+        let profile = profiles
+            .iter()
+            .find(|p| p.id == id)
+            .with_context(|| format!("Failed to find parent profile {id:?}"))?;
+
+        chain.push(profile);
+        next = profile.parent;
+    }
+
+    Ok(chain)
+}
+
 impl TopLevelDefinition {
     fn reify(
         &self,
         target: PipelineTarget,
         profiles: &[CategoryProfile],
+        chain: &[&CategoryProfile],
         registrar: &PipelineActionRegistrar,
         ctx: &mut PipelineContext,
     ) -> Result<Option<PipelineAction>> {
         self.root.reify(&mut ReificationCtx {
             toplevel_id: self.id,
+            toplevel_root: &self.root,
             target,
             actions: &self.actions,
             profiles,
+            chain,
             registrar,
             ctx,
         })
@@ -416,9 +469,12 @@ impl TopLevelDefinition {
 #[derive(Debug)]
 struct ReificationCtx<'a> {
     toplevel_id: TopLevelId,
+    toplevel_root: &'a PipelineActionId,
     target: PipelineTarget,
     actions: &'a PipelineActionLookup,
     profiles: &'a [CategoryProfile],
+    /// Profiles to inherit unspecified actions from, nearest first
+    chain: &'a [&'a CategoryProfile],
     registrar: &'a PipelineActionRegistrar,
     ctx: &'a mut PipelineContext,
 }
@@ -431,34 +487,49 @@ impl Drop for ReificationCtx<'_> {
 
 impl PipelineActionId {
     fn reify(&self, ctx: &mut ReificationCtx) -> Result<Option<PipelineAction>> {
-        let config = ctx.actions.get(self, ctx.target).cloned().or_else(|| {
-            log::warn!("missing action {self:?}; reifying from registry");
-            ctx.registrar.get(self, ctx.target).and_then(|v| {
-                Some(PipelineActionSettings {
-                    enabled: v.settings.enabled,
-                    is_visible_on_qam: v.settings.is_visible_on_qam,
-                    profile_override: v.settings.profile_override,
-                    selection: match &v.settings.selection {
-                        DefinitionSelection::Action(a) => Some(ConfigSelection::Action(a.clone())),
-                        DefinitionSelection::OneOf { selection, actions } => {
-                            let action = ctx.registrar.get(selection, ctx.target).or_else(|| {
-                                actions
-                                    .iter()
-                                    .map(|v| ctx.registrar.get(v, ctx.target))
-                                    .next()
-                                    .flatten()
-                            });
-
-                            action.map(|v| ConfigSelection::OneOf {
-                                selection: v.id.clone(),
-                            })
-                        }
-                        DefinitionSelection::AllOf(_) => Some(ConfigSelection::AllOf),
-                        DefinitionSelection::Versioned { .. } => Some(ConfigSelection::Versioned),
-                    }?,
+        let own = ctx.actions.get(self, ctx.target);
+        let inherited = own
+            .is_none()
+            .then(|| resolve_action_from_parents(self, ctx))
+            .flatten();
+        let inherited_from = inherited.map(|(profile, _)| profile);
+
+        let config = own
+            .or(inherited.map(|(_, config)| config))
+            .cloned()
+            .or_else(|| {
+                log::warn!("missing action {self:?}; reifying from registry");
+                ctx.registrar.get(self, ctx.target).and_then(|v| {
+                    Some(PipelineActionSettings {
+                        enabled: v.settings.enabled,
+                        is_visible_on_qam: v.settings.is_visible_on_qam,
+                        profile_override: v.settings.profile_override,
+                        selection: match &v.settings.selection {
+                            DefinitionSelection::Action(a) => {
+                                Some(ConfigSelection::Action(a.clone()))
+                            }
+                            DefinitionSelection::OneOf { selection, actions } => {
+                                let action =
+                                    ctx.registrar.get(selection, ctx.target).or_else(|| {
+                                        actions
+                                            .iter()
+                                            .map(|v| ctx.registrar.get(v, ctx.target))
+                                            .next()
+                                            .flatten()
+                                    });
+
+                                action.map(|v| ConfigSelection::OneOf {
+                                    selection: v.id.clone(),
+                                })
+                            }
+                            DefinitionSelection::AllOf(_) => Some(ConfigSelection::AllOf),
+                            DefinitionSelection::Versioned { .. } => {
+                                Some(ConfigSelection::Versioned)
+                            }
+                        }?,
+                    })
                 })
-            })
-        });
+            });
 
         match config {
             Some(config) => {
@@ -497,7 +568,8 @@ impl PipelineActionId {
 
                 log::debug!("reify pipeline action id {self:?} got config {settings:?}@{id:?}");
 
-                let resolved_action = settings.reify(definition, ctx)?;
+                let mut resolved_action = settings.reify(definition, ctx)?;
+                resolved_action.inherited_from = inherited_from;
 
                 Ok(Some(resolved_action))
             }
@@ -526,6 +598,29 @@ fn resolve_action_from_profile_override<'a>(
         .get(id, ctx.target)
 }
 
+/// Finds the settings for `id` in the nearest profile of the inheritance chain that has them.
+/// Toplevel actions are matched by id, or by root if the parent has no toplevel with the same id.
+fn resolve_action_from_parents<'a>(
+    id: &PipelineActionId,
+    ctx: &ReificationCtx<'a>,
+) -> Option<(ProfileId, &'a PipelineActionSettings<ConfigSelection>)> {
+    let chain: &'a [&'a CategoryProfile] = ctx.chain;
+
+    chain.iter().copied().find_map(|profile| {
+        let toplevel = profile.pipeline.all_toplevel();
+        let toplevel = toplevel
+            .iter()
+            .find(|v| v.id == ctx.toplevel_id)
+            .or_else(|| toplevel.iter().find(|v| v.root == *ctx.toplevel_root))
+            .copied()?;
+
+        toplevel
+            .actions
+            .get(id, ctx.target)
+            .map(|settings| (profile.id, settings))
+    })
+}
+
 impl PipelineActionSettings<ConfigSelection> {
     fn reify(
         &self,
@@ -541,6 +636,7 @@ impl PipelineActionSettings<ConfigSelection> {
             enabled: self.enabled,
             is_visible_on_qam: self.is_visible_on_qam,
             profile_override: self.profile_override,
+            inherited_from: None,
             selection,
         })
     }
@@ -679,14 +775,20 @@ mod tests {
         let profiles = ProfileDb::new(
             "test/out/.config/DeckDS/template_reification.db".into(),
             registrar,
-        );
+        )
+        .unwrap();
 
         let registrar = PipelineActionRegistrar::builder().with_core().build();
         let ctx = &mut PipelineContext::new(None, Default::default(), Default::default());
-        let res: Vec<_> = profiles
-            .get_templates()
-            .into_iter()
-            .map(|t| (&t.pipeline, t.pipeline.clone().reify(&[], ctx, &registrar)))
+        let templates = profiles.get_templates().unwrap();
+        let res: Vec<_> = templates
+            .iter()
+            .map(|t| {
+                (
+                    &t.pipeline,
+                    t.pipeline.clone().reify(&[], None, ctx, &registrar),
+                )
+            })
             .collect();
 
         assert!(res.len() > 0);
@@ -745,7 +847,9 @@ mod tests {
             target: PipelineTarget::Desktop,
             ctx: pipeline_cxt,
             profiles: &[],
+            chain: &[],
             toplevel_id: toplevel_id,
+            toplevel_root: &root,
         };
 
         let reified = root
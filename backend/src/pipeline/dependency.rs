@@ -8,10 +8,7 @@ use which::which;
 
 use crate::{secondary_app::SecondaryAppPresetId, sys::flatpak::list_installed_flatpaks};
 
-use super::{
-    action::{multi_window::secondary_app::LaunchSecondaryFlatpakApp, ActionId, ActionImpl},
-    executor::PipelineContext,
-};
+use super::executor::PipelineContext;
 
 #[derive(Error, Debug, Clone, Serialize, JsonSchema)]
 #[serde(tag = "type", content = "value")]
@@ -52,6 +49,8 @@ pub enum Dependency {
     Display,
     /// EmuDeck; `PathBuf` points to required settings file
     EmuDeckSettings(PathBuf),
+    /// RetroDECK; `PathBuf` points to required `retrodeck.cfg`
+    RetroDeckSettings(PathBuf),
 }
 
 impl Dependency {
@@ -99,24 +98,16 @@ impl Dependency {
                 let presets = ctx.secondary_app.get_presets();
 
                 if let Some(preset) = presets.get(id) {
-                    match &preset.app {
-                        crate::secondary_app::SecondaryApp::Flatpak(app) => {
-                            LaunchSecondaryFlatpakApp {
-                                id: ActionId::nil(),
-                                app: app.clone(),
-                                windowing_behavior: Default::default(),
-                                screen_preference: Default::default(),
-                            }
-                            .get_dependencies(ctx)
-                            .into_iter()
-                            .try_for_each(|v| v.verify_config(ctx))
-                        }
-                    }
+                    preset
+                        .app
+                        .get_dependencies()
+                        .into_iter()
+                        .try_for_each(|v| v.verify_config(ctx))
                 } else {
                     Err(DependencyError::SecondaryAppPresetNotFound(*id))
                 }
             }
-            Dependency::EmuDeckSettings(path) => {
+            Dependency::EmuDeckSettings(path) | Dependency::RetroDeckSettings(path) => {
                 if path.exists() {
                     Ok(())
                 } else {
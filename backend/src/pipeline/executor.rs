@@ -25,7 +25,8 @@ use crate::pipeline::action::melonds_layout::MelonDSLayout;
 use crate::pipeline::action::multi_window::main_app_automatic_windowing::MainAppAutomaticWindowing;
 use crate::pipeline::action::multi_window::primary_windowing::MultiWindow;
 use crate::pipeline::action::multi_window::secondary_app::{
-    LaunchSecondaryAppPreset, LaunchSecondaryFlatpakApp,
+    supervise_secondary_apps, LaunchSecondaryApp, LaunchSecondaryAppGroup,
+    LaunchSecondaryAppPreset, LaunchSecondaryFlatpakApp, SupervisionOutcome,
 };
 use crate::pipeline::action::session_handler::DesktopSessionHandler;
 use crate::pipeline::action::touch_config::TouchConfig;
@@ -37,6 +38,7 @@ use crate::settings::{AppId, GameId, GlobalConfig, SteamLaunchInfo};
 use crate::sys::app_process::AppProcess;
 use crate::sys::kwin::screen_tracking::KWinScreenTrackingScope;
 use crate::sys::kwin::{next_active_window, KWin};
+use crate::sys::steam::SteamUserConfig;
 use crate::sys::x_display::XDisplay;
 
 use super::action::session_handler::UiEvent;
@@ -105,7 +107,7 @@ impl PipelineContext {
             display: XDisplay::new().ok(),
             state: TypeMap::new(),
             have_run: vec![],
-            secondary_app: SecondaryAppManager::new(decky_env.asset_manager()),
+            secondary_app: SecondaryAppManager::new(&decky_env),
             exit_hooks: None,
             next_window_hooks: None,
             on_launch_callbacks: vec![],
@@ -119,6 +121,16 @@ impl PipelineContext {
         self.on_launch_callbacks.push(callback);
     }
 
+    /// Steam config for the user launching the current app.
+    pub fn steam_user_config(&self) -> Result<SteamUserConfig> {
+        let launch_info = self
+            .launch_info
+            .as_ref()
+            .context("steam user config requires launch info")?;
+
+        SteamUserConfig::from_launch_info(&self.decky_env, launch_info)
+    }
+
     pub fn load(global_config: GlobalConfig, decky_env: Arc<DeckyEnv>) -> Result<Option<Self>> {
         let mut default: PipelineContext = PipelineContext::new(None, global_config, decky_env);
 
@@ -150,6 +162,8 @@ impl PipelineContext {
         register_type::<MelonDSLayout>(&mut type_reg);
         register_type::<DisplayConfig>(&mut type_reg);
         register_type::<LaunchSecondaryAppPreset>(&mut type_reg);
+        register_type::<LaunchSecondaryApp>(&mut type_reg);
+        register_type::<LaunchSecondaryAppGroup>(&mut type_reg);
         register_type::<LaunchSecondaryFlatpakApp>(&mut type_reg);
         register_type::<MainAppAutomaticWindowing>(&mut type_reg);
         register_type::<Lime3dsLayout>(&mut type_reg);
@@ -211,6 +225,12 @@ impl PipelineContext {
                     ActionType::LaunchSecondaryAppPreset => {
                         load_state::<LaunchSecondaryAppPreset>(&mut default, &type_map)
                     }
+                    ActionType::LaunchSecondaryApp => {
+                        load_state::<LaunchSecondaryApp>(&mut default, &type_map)
+                    }
+                    ActionType::LaunchSecondaryAppGroup => {
+                        load_state::<LaunchSecondaryAppGroup>(&mut default, &type_map)
+                    }
                     ActionType::MainAppAutomaticWindowing => {
                         load_state::<MainAppAutomaticWindowing>(&mut default, &type_map)
                     }
@@ -243,7 +263,7 @@ impl PipelineContext {
         }
 
         default.kwin = KWin::new(env.asset_manager());
-        default.secondary_app = SecondaryAppManager::new(env.asset_manager());
+        default.secondary_app = SecondaryAppManager::new(&env);
         default.decky_env = Arc::new(env.clone());
         default.launch_info = launch_info.clone();
 
@@ -281,6 +301,8 @@ impl PipelineContext {
                 Action::SourceFile(a) => insert_action(self, &mut map, a),
                 Action::LaunchSecondaryFlatpakApp(a) => insert_action(self, &mut map, a),
                 Action::LaunchSecondaryAppPreset(a) => insert_action(self, &mut map, a),
+                Action::LaunchSecondaryApp(a) => insert_action(self, &mut map, a),
+                Action::LaunchSecondaryAppGroup(a) => insert_action(self, &mut map, a),
                 Action::MainAppAutomaticWindowing(a) => insert_action(self, &mut map, a),
                 Action::Lime3dsLayout(a) => insert_action(self, &mut map, a),
                 Action::DesktopControllerLayoutHack(a) => insert_action(self, &mut map, a),
@@ -345,6 +367,16 @@ impl PipelineContext {
             .and_then(|v| v.as_mut())
     }
 
+    /// Gets the state of every running instance of `P`, in the order they were set up.
+    pub fn get_states_mut<P: ActionImpl + 'static>(
+        &mut self,
+    ) -> impl Iterator<Item = &mut P::State> {
+        self.state
+            .get_mut::<StateKey<P>>()
+            .into_iter()
+            .flat_map(|states| states.iter_mut().filter_map(|v| v.as_mut()))
+    }
+
     pub fn set_state<P: ActionImpl + 'static>(&mut self, state: P::State) -> Option<P::State> {
         let entry = self.state.entry::<StateKey<P>>().or_insert(vec![]);
         entry
@@ -353,6 +385,25 @@ impl PipelineContext {
             .replace(state)
     }
 
+    /// Applies the exit policies of secondary apps for one iteration of the session loop,
+    /// persisting changed state. If an app with the `EndSession` policy exited, ends the
+    /// session by calling `kill_app` to kill the main app.
+    pub fn supervise_secondary_apps<T>(&mut self, kill_app: impl FnOnce() -> T) -> Option<T> {
+        match supervise_secondary_apps(self) {
+            SupervisionOutcome::Unchanged => None,
+            SupervisionOutcome::Changed => {
+                if let Err(err) = self.persist() {
+                    log::warn!("Failed to persist secondary app state: {err:#?}");
+                }
+                None
+            }
+            SupervisionOutcome::EndSession => {
+                log::debug!("secondary app exited; killing app process...");
+                Some(kill_app())
+            }
+        }
+    }
+
     pub fn send_ui_event(&mut self, event: UiEvent) {
         let ui_state = self.get_state_mut::<DesktopSessionHandler>();
         if let Some(ui_state) = ui_state {
@@ -414,6 +465,8 @@ impl PipelineContext {
             ActionType::LaunchSecondaryAppPreset => {
                 handle::<LaunchSecondaryAppPreset>(self, is_push)
             }
+            ActionType::LaunchSecondaryApp => handle::<LaunchSecondaryApp>(self, is_push),
+            ActionType::LaunchSecondaryAppGroup => handle::<LaunchSecondaryAppGroup>(self, is_push),
             ActionType::Lime3dsLayout => handle::<Lime3dsLayout>(self, is_push),
             ActionType::DesktopControllerLayoutHack => {
                 handle::<DesktopControllerLayoutHack>(self, is_push)
@@ -610,6 +663,10 @@ impl PipelineExecutor {
                 }
             }
 
+            if let Some(res) = self.ctx.supervise_secondary_apps(|| app_process.kill()) {
+                return res;
+            }
+
             if let Some(hooks) = self.ctx.next_window_hooks {
                 if hooks.matches(&state) {
                     if !ignore_next_window_input {
@@ -746,6 +803,7 @@ mod tests {
                 cemu: None,
                 citra: None,
                 dolphin: None,
+                melonds: None,
                 custom: None,
             }
             .into(),
@@ -0,0 +1,293 @@
+//! Action definitions loaded from versioned JSON/TOML files in the `registry` asset dir,
+//! so new platform trees don't require recompiling.
+//!
+//! A file defines a single plugin scope:
+//!
+//! ```toml
+//! version = 1
+//! plugin = "community"
+//!
+//! [groups.dolphin.platform]
+//! name = "Dolphin"
+//! selection = { type = "AllOf", value = ["community:dolphin:source"] }
+//! ```
+//!
+//! Action values may leave out their `id`, since registrar actions always have nil ids.
+
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::asset::{AssetDirEntry, AssetManager};
+
+use super::{
+    action::{ActionId, ActionType},
+    action_registar::PipelineActionDefinitionBuilder,
+    data::{DefinitionSelection, PipelineActionId, PipelineTarget},
+};
+
+pub const REGISTRY_DIR: &str = "registry";
+
+/// The newest registry file version this build understands.
+pub const REGISTRY_FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+struct RegistryFile {
+    version: u32,
+    plugin: String,
+    #[serde(default)]
+    groups: HashMap<String, HashMap<String, RegistryAction>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryAction {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// Defines the action only for this target
+    #[serde(default)]
+    target: Option<PipelineTarget>,
+    #[serde(default)]
+    enabled: Option<bool>,
+    #[serde(default)]
+    is_visible_on_qam: bool,
+    /// Parsed separately, so a single invalid action doesn't discard the whole file
+    selection: serde_json::Value,
+}
+
+/// A problem found while loading registry files. The affected file or action is skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegistryIssue {
+    /// The file the issue was found in
+    pub origin: String,
+    pub action: Option<PipelineActionId>,
+    pub message: String,
+}
+
+/// An action parsed from a registry file, not yet merged into the registrar.
+#[derive(Debug)]
+pub struct RegistryFileAction {
+    pub plugin: String,
+    pub group: String,
+    pub name: String,
+    pub target: Option<PipelineTarget>,
+    pub builder: PipelineActionDefinitionBuilder,
+}
+
+impl RegistryFileAction {
+    pub fn id(&self) -> PipelineActionId {
+        id_of(&self.plugin, &self.group, &self.name, self.target)
+    }
+}
+
+fn id_of(
+    plugin: &str,
+    group: &str,
+    name: &str,
+    target: Option<PipelineTarget>,
+) -> PipelineActionId {
+    let id = PipelineActionId::new(&format!("{plugin}:{group}:{name}"));
+
+    match target {
+        Some(target) => id.variant(target),
+        None => id,
+    }
+}
+
+/// Parses the registry file `contents`, using the extension of `origin` to pick the format.
+/// Returns an error if the file can't be used at all; invalid actions are returned as issues.
+pub fn parse_registry_file(
+    origin: &str,
+    contents: &str,
+) -> Result<(Vec<RegistryFileAction>, Vec<RegistryIssue>)> {
+    let value: serde_json::Value = match Path::new(origin).extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(contents)?,
+        Some("toml") => toml_to_json(contents.parse::<DocumentMut>()?.as_item()),
+        ext => anyhow::bail!("unsupported registry file extension {ext:?}"),
+    };
+
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .context("registry file is missing its version")?;
+    anyhow::ensure!(
+        version <= REGISTRY_FILE_VERSION as u64,
+        "registry file v{version} is newer than the supported v{REGISTRY_FILE_VERSION}"
+    );
+
+    let file: RegistryFile = serde_json::from_value(value)?;
+    anyhow::ensure!(
+        !file.plugin.is_empty() && !file.plugin.contains(':'),
+        "invalid plugin name {:?}",
+        file.plugin
+    );
+
+    let mut actions = vec![];
+    let mut issues = vec![];
+
+    for (group, group_actions) in file.groups {
+        for (name, action) in group_actions {
+            let id = id_of(&file.plugin, &group, &name, action.target);
+
+            let selection = if group.contains(':') || name.contains(':') {
+                Err(anyhow::anyhow!("group and action names cannot contain ':'"))
+            } else {
+                parse_selection(action.selection)
+            };
+
+            match selection {
+                Ok(selection) => actions.push(RegistryFileAction {
+                    plugin: file.plugin.clone(),
+                    group: group.clone(),
+                    name,
+                    target: action.target,
+                    builder: PipelineActionDefinitionBuilder {
+                        name: action.name,
+                        description: action.description,
+                        enabled: action.enabled,
+                        profile_override: None,
+                        is_visible_on_qam: action.is_visible_on_qam,
+                        selection,
+                    },
+                }),
+                Err(err) => issues.push(RegistryIssue {
+                    origin: origin.to_string(),
+                    action: Some(id),
+                    message: format!("{err:#}"),
+                }),
+            }
+        }
+    }
+
+    Ok((actions, issues))
+}
+
+fn toml_to_json(item: &Item) -> serde_json::Value {
+    fn value_to_json(value: &Value) -> serde_json::Value {
+        match value {
+            Value::String(v) => v.value().clone().into(),
+            Value::Integer(v) => (*v.value()).into(),
+            Value::Float(v) => (*v.value()).into(),
+            Value::Boolean(v) => (*v.value()).into(),
+            Value::Datetime(v) => v.value().to_string().into(),
+            Value::Array(v) => v.iter().map(value_to_json).collect(),
+            Value::InlineTable(v) => v
+                .iter()
+                .map(|(k, v)| (k.to_string(), value_to_json(v)))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+
+    match item {
+        Item::None => serde_json::Value::Null,
+        Item::Value(v) => value_to_json(v),
+        Item::Table(v) => v
+            .iter()
+            .map(|(k, v)| (k.to_string(), toml_to_json(v)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        Item::ArrayOfTables(v) => v
+            .iter()
+            .map(|t| {
+                t.iter()
+                    .map(|(k, v)| (k.to_string(), toml_to_json(v)))
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .collect(),
+    }
+}
+
+/// Parses a selection, checking that its action type is one this build knows about.
+fn parse_selection(mut selection: serde_json::Value) -> Result<DefinitionSelection> {
+    if selection.get("type").and_then(|t| t.as_str()) == Some("Action") {
+        let action = selection
+            .get_mut("value")
+            .and_then(|v| v.as_object_mut())
+            .context("action selection is missing its value")?;
+
+        let action_type = action
+            .get("type")
+            .and_then(|t| t.as_str())
+            .context("action is missing its type")?;
+        ActionType::from_str(action_type)
+            .with_context(|| format!("unknown action type {action_type:?}"))?;
+
+        if let Some(value) = action.get_mut("value").and_then(|v| v.as_object_mut()) {
+            if !value.contains_key("id") {
+                value.insert("id".into(), serde_json::to_value(ActionId::nil())?);
+            }
+        }
+    }
+
+    serde_json::from_value(selection).context("invalid action selection")
+}
+
+/// Reads every registry file in the asset dir, returning `(origin, contents)` pairs
+/// sorted by origin, so files are always merged in the same order.
+pub fn read_registry_files(asset_manager: &AssetManager) -> Vec<(String, String)> {
+    let mut files = asset_manager
+        .get_dir(REGISTRY_DIR)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| match entry {
+            AssetDirEntry::Dir(_) => None,
+            AssetDirEntry::File(file) => asset_manager.get_file(file),
+        })
+        .filter_map(|asset| {
+            let path = asset.file_path().ok()?;
+            let contents = asset
+                .contents_to_string()
+                .inspect_err(|err| log::warn!("failed to read registry file at {path:?}: {err}"))
+                .ok()?;
+
+            Some((path.to_string_lossy().into_owned(), contents.into_owned()))
+        })
+        .collect::<Vec<_>>();
+
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_registry_file() -> Result<()> {
+        let contents = std::fs::read_to_string("test/assets/registry/community.toml")?;
+        let (actions, issues) = parse_registry_file("community.toml", &contents)?;
+
+        let mut ids = actions.iter().map(|a| a.id()).collect::<Vec<_>>();
+        ids.sort_by(|a, b| a.raw().cmp(b.raw()));
+
+        assert_eq!(
+            vec![
+                PipelineActionId::new("community:dolphin:layout:desktop"),
+                PipelineActionId::new("community:dolphin:platform"),
+                PipelineActionId::new("community:dolphin:window"),
+            ],
+            ids
+        );
+        assert_eq!(1, issues.len());
+        assert_eq!(
+            Some(PipelineActionId::new("community:dolphin:unknown")),
+            issues[0].action
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_newer_registry_file() {
+        let contents = format!(
+            r#"{{ "version": {}, "plugin": "community", "groups": {{}} }}"#,
+            REGISTRY_FILE_VERSION + 1
+        );
+
+        assert!(parse_registry_file("community.json", &contents).is_err());
+        assert!(parse_registry_file("community.yaml", "version: 1").is_err());
+    }
+}
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
+use anyhow::{anyhow, Context, Result};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{asset::AssetManager, macros::newtype_uuid};
+use crate::{
+    asset::AssetManager, decky_env::DeckyEnv, macros::newtype_uuid, settings::GameId,
+    util::create_dir_all,
+};
 
 newtype_uuid!(SecondaryAppPresetId);
 
@@ -11,6 +15,10 @@ newtype_uuid!(SecondaryAppPresetId);
 #[serde(tag = "type")]
 pub enum SecondaryApp {
     Flatpak(FlatpakApp),
+    Native(NativeApp),
+    AppImage(AppImageApp),
+    Steam(SteamApp),
+    Url(UrlApp),
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
@@ -19,23 +27,76 @@ pub struct FlatpakApp {
     pub args: Vec<String>,
 }
 
+/// A native executable or command on the `PATH`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct NativeApp {
+    pub path: PathBuf,
+    pub args: Vec<String>,
+    /// Additional environment variables for the process
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub working_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct AppImageApp {
+    pub path: PathBuf,
+    pub args: Vec<String>,
+}
+
+/// A Steam app or non-Steam shortcut, launched through the Steam client.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct SteamApp {
+    /// Steam app id, or the 64-bit game id of a non-Steam shortcut
+    pub game_id: GameId,
+}
+
+/// A URL opened in the default browser.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct UrlApp {
+    pub url: String,
+}
+
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Deserialize, JsonSchema)]
 pub struct SecondaryAppPreset {
     pub name: String,
     pub app: SecondaryApp,
 }
 
-#[derive(Debug)]
+/// File in the plugin settings dir containing user-defined presets.
+const USER_PRESETS_FILE: &str = "secondary_apps.json";
+
+/// Loads secondary app presets bundled in `assets/secondary_apps`, along with user presets
+/// stored in the plugin settings dir. User presets take precedence over bundled presets
+/// with the same id; bundled presets themselves are read-only.
+#[derive(Debug, Clone)]
 pub struct SecondaryAppManager {
     asset_manager: AssetManager<'static>,
+    user_presets_path: PathBuf,
 }
 
 impl SecondaryAppManager {
-    pub fn new(asset_manager: AssetManager<'static>) -> Self {
-        Self { asset_manager }
+    pub fn new(decky_env: &DeckyEnv) -> Self {
+        Self {
+            asset_manager: decky_env.asset_manager(),
+            user_presets_path: decky_env.decky_plugin_settings_dir.join(USER_PRESETS_FILE),
+        }
     }
 
+    /// Returns bundled presets, overridden by user presets with the same id.
     pub fn get_presets(&self) -> HashMap<SecondaryAppPresetId, SecondaryAppPreset> {
+        let mut presets = self.get_builtin_presets();
+
+        let user_presets = self
+            .get_user_presets()
+            .inspect_err(|err| log::warn!("failed to load user presets; ignoring presets: {err}"))
+            .unwrap_or_default();
+
+        presets.extend(user_presets);
+        presets
+    }
+
+    pub fn get_builtin_presets(&self) -> HashMap<SecondaryAppPresetId, SecondaryAppPreset> {
         self.asset_manager
             .get_dir("secondary_apps")
             .map(|v| {
@@ -67,23 +128,96 @@ impl SecondaryAppManager {
             .unwrap_or_default()
     }
 
-    // TODO::new/update preset user presets
+    pub fn get_user_presets(&self) -> Result<HashMap<SecondaryAppPresetId, SecondaryAppPreset>> {
+        if !self.user_presets_path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let contents = std::fs::read_to_string(&self.user_presets_path)
+            .with_context(|| format!("failed to read {:?}", self.user_presets_path))?;
+
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse presets at {:?}", self.user_presets_path))
+    }
+
+    pub fn create_preset(&self, preset: SecondaryAppPreset) -> Result<SecondaryAppPresetId> {
+        let mut user_presets = self.get_user_presets()?;
+        let id = SecondaryAppPresetId::new();
+
+        user_presets.insert(id, preset);
+        self.set_user_presets(&user_presets)?;
+
+        Ok(id)
+    }
+
+    pub fn update_preset(
+        &self,
+        id: SecondaryAppPresetId,
+        preset: SecondaryAppPreset,
+    ) -> Result<()> {
+        let mut user_presets = self.get_user_presets()?;
+
+        match user_presets.get_mut(&id) {
+            Some(existing) => *existing = preset,
+            None => return Err(self.missing_user_preset_err(id)),
+        }
+
+        self.set_user_presets(&user_presets)
+    }
+
+    pub fn delete_preset(&self, id: SecondaryAppPresetId) -> Result<()> {
+        let mut user_presets = self.get_user_presets()?;
+
+        if user_presets.remove(&id).is_none() {
+            return Err(self.missing_user_preset_err(id));
+        }
+
+        self.set_user_presets(&user_presets)
+    }
+
+    fn missing_user_preset_err(&self, id: SecondaryAppPresetId) -> anyhow::Error {
+        if self.get_builtin_presets().contains_key(&id) {
+            anyhow!("secondary app preset {id:?} is built-in and cannot be modified")
+        } else {
+            anyhow!("secondary app preset {id:?} not found")
+        }
+    }
+
+    fn set_user_presets(
+        &self,
+        presets: &HashMap<SecondaryAppPresetId, SecondaryAppPreset>,
+    ) -> Result<()> {
+        let parent = self
+            .user_presets_path
+            .parent()
+            .expect("user presets path should have parent");
+
+        create_dir_all(parent)?;
+
+        std::fs::write(
+            &self.user_presets_path,
+            serde_json::to_string_pretty(presets)?,
+        )
+        .with_context(|| "failed to write user secondary app presets")
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use log::Level;
 
+    use anyhow::Result;
+
     use crate::decky_env::DeckyEnv;
 
-    use super::SecondaryAppManager;
+    use super::*;
 
     #[test]
     fn test_parsed_embedded_secondary_apps() {
         testing_logger::setup();
 
-        let asset_manager = DeckyEnv::new_test("parsed_embedded_secondary_apps").asset_manager();
-        let secondary_apps = SecondaryAppManager::new(asset_manager).get_presets();
+        let decky_env = DeckyEnv::new_test("parsed_embedded_secondary_apps");
+        let secondary_apps = SecondaryAppManager::new(&decky_env).get_presets();
 
         testing_logger::validate(|logs| {
             for log in logs {
@@ -96,4 +230,68 @@ mod tests {
             "should find at least one secondary app preset"
         )
     }
+
+    #[test]
+    fn test_user_presets_crud() -> Result<()> {
+        let decky_env = DeckyEnv::new_test("user_secondary_app_presets");
+        let manager = SecondaryAppManager::new(&decky_env);
+
+        let builtin = manager.get_builtin_presets();
+        let (builtin_id, _) = builtin.iter().next().expect("should have builtin preset");
+
+        let discord = SecondaryAppPreset {
+            name: "Discord".to_string(),
+            app: SecondaryApp::Flatpak(FlatpakApp {
+                app_id: "com.discordapp.Discord".to_string(),
+                args: vec![],
+            }),
+        };
+
+        let id = manager.create_preset(discord.clone())?;
+        let presets = manager.get_presets();
+        assert_eq!(Some(&discord), presets.get(&id));
+        assert_eq!(builtin.len() + 1, presets.len());
+
+        let renamed = SecondaryAppPreset {
+            name: "Discord (bottom screen)".to_string(),
+            ..discord
+        };
+        manager.update_preset(id, renamed.clone())?;
+        assert_eq!(Some(&renamed), manager.get_presets().get(&id));
+
+        assert!(manager.update_preset(*builtin_id, renamed.clone()).is_err());
+        assert!(manager.delete_preset(*builtin_id).is_err());
+
+        manager.delete_preset(id)?;
+        assert!(!manager.get_presets().contains_key(&id));
+        assert!(manager.delete_preset(id).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_user_presets_override_builtin() -> Result<()> {
+        let decky_env = DeckyEnv::new_test("user_secondary_app_presets_override");
+        let manager = SecondaryAppManager::new(&decky_env);
+
+        let builtin_id = *manager
+            .get_builtin_presets()
+            .keys()
+            .next()
+            .expect("should have builtin preset");
+
+        let preset = SecondaryAppPreset {
+            name: "Overridden".to_string(),
+            app: SecondaryApp::Flatpak(FlatpakApp {
+                app_id: "org.mozilla.firefox".to_string(),
+                args: vec![],
+            }),
+        };
+
+        manager.set_user_presets(&HashMap::from([(builtin_id, preset.clone())]))?;
+
+        assert_eq!(Some(&preset), manager.get_presets().get(&builtin_id));
+
+        Ok(())
+    }
 }
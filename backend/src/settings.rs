@@ -20,10 +20,14 @@ newtype_strid!("The GameId in Steam", GameId);
 newtype_strid!("The UserId in steam", SteamUserId64);
 
 use crate::{
+    db::revision::DEFAULT_REVISION_RETENTION,
     decky_env::DeckyEnv,
     macros::{newtype_strid, newtype_uuid},
     pipeline::{
-        action::session_handler::DesktopSessionHandler,
+        action::{
+            multi_window::main_app_automatic_windowing::LearnedWindowMatch,
+            session_handler::DesktopSessionHandler,
+        },
         data::{BtnChord, Pipeline, PipelineDefinition, PipelineTarget, PressType},
     },
     util::create_dir_all,
@@ -41,6 +45,7 @@ pub struct Settings {
 
 #[serde_as]
 #[derive(Debug, SmartDefault, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
 pub struct GlobalConfig {
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub display_restoration: DesktopSessionHandler,
@@ -72,6 +77,9 @@ pub struct GlobalConfig {
     #[default(false)]
     /// Overwrite the desktop layout with the game layout
     pub use_nonsteam_desktop_controller_layout_hack: bool,
+    /// Number of revisions kept per profile and per app override; 0 disables revision history
+    #[default(DEFAULT_REVISION_RETENTION)]
+    pub profile_revision_retention: usize,
 }
 
 impl Settings {
@@ -208,6 +216,9 @@ pub struct SteamLaunchInfo {
     pub user_id_64: SteamUserId64,
     pub game_title: String,
     pub is_steam_game: bool,
+    /// The main window learned from previous launches, if any
+    #[serde(default)]
+    pub learned_window: Option<LearnedWindowMatch>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -215,6 +226,9 @@ pub struct CategoryProfile {
     pub id: ProfileId,
     pub tags: Vec<String>,
     pub pipeline: PipelineDefinition,
+    /// The profile unspecified actions are inherited from, if any
+    #[serde(default)]
+    pub parent: Option<ProfileId>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
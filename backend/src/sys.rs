@@ -3,5 +3,6 @@ pub mod audio;
 pub mod display_info;
 pub mod flatpak;
 pub mod kwin;
+pub mod steam;
 pub mod steamos_session_select;
 pub mod x_display;
@@ -43,7 +43,7 @@ impl AppProcess {
     }
 
     /// Kills the application process (very aggressively)
-    pub fn kill(self) -> Result<()> {
+    pub fn kill(&self) -> Result<()> {
         fn kill_timeout(pid: Pid, signal: Signal, timeout: Duration, kill_group: bool) -> bool {
             let join_handle = std::thread::spawn(move || {
                 if kill_group {
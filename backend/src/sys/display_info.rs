@@ -3,6 +3,8 @@ use std::cmp::Ordering;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::x_display::Resolution;
+
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DisplayValues {
     width: u16,
@@ -10,6 +12,19 @@ pub struct DisplayValues {
     refresh: Option<f32>, // can't fetch it now, but I'd like to in the future if possible
 }
 
+impl DisplayValues {
+    pub fn resolution(&self) -> Resolution {
+        Resolution {
+            w: self.width as u32,
+            h: self.height as u32,
+        }
+    }
+
+    pub fn refresh(&self) -> Option<f32> {
+        self.refresh
+    }
+}
+
 impl Eq for DisplayValues {}
 
 impl PartialOrd for DisplayValues {
@@ -46,22 +46,27 @@ pub fn check_running_flatpaks() -> Result<Vec<FlatpakStatus>> {
 pub struct FlatpakInfo {
     pub name: String,
     pub app_id: String,
+    /// The version reported by the app, if any
+    pub version: Option<String>,
 }
 
 pub fn list_installed_flatpaks() -> Result<Vec<FlatpakInfo>> {
     let output = Command::new("flatpak")
-        .args(["list", "--app", "--columns=application,name"])
+        .args(["list", "--app", "--columns=application,version,name"])
         .output()?;
     if output.status.success() {
         let status = String::from_utf8_lossy(&output.stdout)
             .lines()
-            .map(|v| v.split_ascii_whitespace())
+            // columns are tab separated; names and versions may contain spaces
+            .map(|v| v.split('\t').map(str::trim))
             .map(|mut v| {
                 let status = FlatpakInfo {
                     app_id: v
                         .next()
+                        .filter(|v| !v.is_empty())
                         .with_context(|| "expected flatpak app id")?
                         .to_string(),
+                    version: v.next().filter(|v| !v.is_empty()).map(|v| v.to_string()),
                     name: v.collect::<Vec<_>>().join(" "),
                 };
                 Ok(status)
@@ -75,3 +80,21 @@ pub fn list_installed_flatpaks() -> Result<Vec<FlatpakInfo>> {
         ))
     }
 }
+
+/// Reads the `Version` field from `flatpak info <app_id>`, if the app reports one.
+pub fn get_flatpak_version(app_id: &str) -> Result<Option<String>> {
+    let output = Command::new("flatpak").args(["info", app_id]).output()?;
+    if output.status.success() {
+        let version = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|v| v.trim().strip_prefix("Version:"))
+            .map(|v| v.trim().to_string())
+            .find(|v| !v.is_empty());
+        Ok(version)
+    } else {
+        Err(anyhow::anyhow!(
+            "failed to get flatpak info for {app_id}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
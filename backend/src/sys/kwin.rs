@@ -7,7 +7,7 @@ use std::{
 
 use crate::asset::{Asset, AssetManager};
 
-pub use window_tracking::KWinClientMatcher;
+pub use window_tracking::{KWinClientInfo, KWinClientMatcher, KWinWindowMatcher};
 
 pub mod screen_tracking;
 mod window_tracking;
@@ -230,6 +230,14 @@ impl KWin {
     }
 }
 
+/// Lists the currently open windows. Only works in Desktop mode.
+pub fn list_windows() -> Result<Vec<KWinClientInfo>> {
+    Ok(
+        window_tracking::KWinNewWindowTrackingScope::with_existing_windows()?
+            .get_current_clients(Duration::from_millis(500)),
+    )
+}
+
 pub fn next_active_window() -> Result<()> {
     let out = Command::new("qdbus")
         .args([
@@ -13,7 +13,9 @@ use dbus::{
     channel::MatchingReceiver,
     message::MatchRule,
 };
-use serde::Deserialize;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use str_distance::str_distance_normalized;
 
 use std::io::Write;
@@ -22,12 +24,22 @@ pub struct KWinNewWindowTrackingScope {
     script_name: uuid::Uuid,
     script_id: i32,
     kwin_conn: Connection,
+    info: Arc<Mutex<Vec<KWinClientInfo>>>,
     msg_thread: Option<JoinHandle<Option<KWinClientInfo>>>,
     kill_tx: Sender<Option<KWinClientMatcher>>,
 }
 
 impl KWinNewWindowTrackingScope {
     pub fn new() -> Result<Self> {
+        Self::start(false)
+    }
+
+    /// Tracks windows that are already open, in addition to new ones.
+    pub fn with_existing_windows() -> Result<Self> {
+        Self::start(true)
+    }
+
+    fn start(include_existing: bool) -> Result<Self> {
         let script_name = uuid::Uuid::new_v4();
         let kwin_conn = Connection::new_session()?;
 
@@ -35,7 +47,11 @@ impl KWinNewWindowTrackingScope {
 
         let self_conn = SyncConnection::new_session()?;
 
-        let script_text = Self::get_script_text(&self_conn.unique_name().to_string(), script_name);
+        let script_text = Self::get_script_text(
+            &self_conn.unique_name().to_string(),
+            script_name,
+            include_existing,
+        );
 
         let mut script_file = tempfile::NamedTempFile::with_prefix("DeckDS-windowtracking-")?;
 
@@ -53,6 +69,9 @@ impl KWinNewWindowTrackingScope {
         log::debug!("started window tracking scipt id: {script_id} @ {script_file_path:?}");
 
         let (kill_tx, kill_rx) = std::sync::mpsc::channel::<Option<KWinClientMatcher>>();
+        let info: Arc<Mutex<Vec<KWinClientInfo>>> = Arc::new(Mutex::new(vec![]));
+        let thread_info = info.clone();
+
         // setup message receiver
         let msg_thread = std::thread::spawn(move || {
             fn get_best_match<'a>(
@@ -120,8 +139,7 @@ impl KWinNewWindowTrackingScope {
                     .map(|v| v.client)
             }
 
-            let info: Arc<Mutex<Vec<KWinClientInfo>>> = Arc::new(Mutex::new(vec![]));
-
+            let info = thread_info;
             let info_ref = info.clone();
             let receiver =
                 self_conn.start_receive(
@@ -178,11 +196,25 @@ impl KWinNewWindowTrackingScope {
                     && (found_instant.is_none()
                         || found_instant.unwrap().elapsed() < matcher.min_delay)
                 {
-                    let has_match = {
+                    let (known, has_match) = {
                         let lock = info.lock().unwrap();
-                        get_best_match(&matcher.maybe_strings, lock.deref()).is_some()
+                        let known = matcher
+                            .known_window
+                            .as_ref()
+                            .and_then(|known| lock.iter().find(|c| known.matches(c)).cloned());
+
+                        (
+                            known,
+                            get_best_match(&matcher.maybe_strings, lock.deref()).is_some(),
+                        )
                     };
 
+                    if known.is_some() {
+                        log::debug!("found known window {known:?}; skipping delay");
+                        self_conn.stop_receive(receiver);
+                        return known;
+                    }
+
                     if has_match {
                         if found_instant.is_none() {
                             found_instant = Some(Instant::now());
@@ -215,6 +247,7 @@ impl KWinNewWindowTrackingScope {
             script_id,
             script_name,
             kwin_conn,
+            info,
             msg_thread: Some(msg_thread),
             kill_tx,
         };
@@ -246,6 +279,15 @@ impl KWinNewWindowTrackingScope {
         Ok(window)
     }
 
+    /// Waits for `settle` to let the script report windows, then returns the windows
+    /// currently tracked.
+    pub fn get_current_clients(self, settle: Duration) -> Vec<KWinClientInfo> {
+        std::thread::sleep(settle);
+
+        let lock = self.info.lock().unwrap();
+        lock.clone()
+    }
+
     fn get_kwin_proxy(kwin_conn: &Connection) -> Proxy<&Connection> {
         kwin_conn.with_proxy("org.kde.KWin", "/Scripting", Duration::from_secs(10))
     }
@@ -264,7 +306,17 @@ impl KWinNewWindowTrackingScope {
         )
     }
 
-    fn get_script_text(dbus_addr: &str, script_name: uuid::Uuid) -> String {
+    fn get_script_text(dbus_addr: &str, script_name: uuid::Uuid, include_existing: bool) -> String {
+        let existing = if include_existing {
+            r#"
+clients = workspace.clientList().filter((client) => client.normalWindow);
+clients.forEach((client) => client.captionChanged.connect(updateClients));
+updateClients();
+"#
+        } else {
+            ""
+        };
+
         format!(
             r#"
 console.log("!!!!!! Matching windows for {dbus_addr} !!!!!!");
@@ -296,7 +348,9 @@ function updateClients() {{
             return {{
                 id: client.windowId,
                 caption: client.caption,
-                window_classes: client.resourceClass.toString().toLowerCase().split(' ')
+                window_classes: client.resourceClass.toString().toLowerCase().split(' '),
+                pid: client.pid,
+                screen: client.screen
             }}
         }});
 
@@ -324,10 +378,10 @@ workspace.clientAdded.connect((client) => {{
 workspace.clientRemoved.connect((client) => {{
     console.log('matcher removed client');
 
-    clients = clients.filter((c) => c.id !== client.windowId);
+    clients = clients.filter((c) => c.windowId !== client.windowId);
     updateClients();
 }});
-"#
+{existing}"#
         )
     }
 }
@@ -344,10 +398,14 @@ impl Drop for KWinNewWindowTrackingScope {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct KWinClientInfo {
     pub caption: String,
     pub window_classes: Vec<String>,
+    #[serde(default)]
+    pub pid: Option<u32>,
+    #[serde(default)]
+    pub screen: Option<i32>,
 }
 pub struct KWinClientMatcher {
     /// minimum delay to wait for new windows after finding a matcher
@@ -358,4 +416,183 @@ pub struct KWinClientMatcher {
     pub preferred_ord_if_no_match: Ordering,
     /// Possible strings for either the window title or class. We're guessing here.
     pub maybe_strings: Vec<String>,
+    /// A window known to be correct; if one appears, it is used immediately without waiting for the delays.
+    pub known_window: Option<KWinWindowMatcher>,
+}
+
+/// Matches windows the same way the `emulatorwindowing` script does; the caption against
+/// a regex, and the window class against a list of (case-insensitive) substrings.
+///
+/// The script matches captions with JS regexes, so caption patterns are limited to the
+/// syntax both engines interpret the same way.
+#[derive(Debug, Clone)]
+pub struct KWinWindowMatcher {
+    caption: Option<Regex>,
+    classes: Vec<String>,
+}
+
+impl KWinWindowMatcher {
+    /// Missing or empty matchers match every window.
+    pub fn new(caption: Option<&str>, classes: &[String]) -> Result<Self> {
+        let caption = caption
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                ensure_portable_pattern(v)?;
+                Ok::<_, anyhow::Error>(Regex::new(v)?)
+            })
+            .transpose()
+            .context("invalid window matcher")?;
+
+        let classes = classes
+            .iter()
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+            .collect();
+
+        Ok(Self { caption, classes })
+    }
+
+    pub fn matches_caption(&self, client: &KWinClientInfo) -> bool {
+        self.caption
+            .as_ref()
+            .map(|rgx| rgx.is_match(&client.caption))
+            .unwrap_or(true)
+    }
+
+    pub fn matches_class(&self, client: &KWinClientInfo) -> bool {
+        let window_class = client.window_classes.join(" ").to_lowercase();
+
+        self.classes.is_empty() || self.classes.iter().any(|c| window_class.contains(c))
+    }
+
+    pub fn matches(&self, client: &KWinClientInfo) -> bool {
+        self.matches_caption(client) && self.matches_class(client)
+    }
+}
+
+/// Rejects regex syntax that JS and the `regex` crate don't share, or interpret differently.
+fn ensure_portable_pattern(pattern: &str) -> Result<()> {
+    let unsupported = |what: &str| {
+        Err(anyhow::anyhow!(
+            "{what} in {pattern:?} are not supported by window matchers"
+        ))
+    };
+
+    let mut chars = pattern.chars().peekable();
+    let mut in_class = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('1'..='9') | Some('k') => return unsupported("backreferences"),
+                Some('A' | 'z' | 'Z') => return unsupported("text anchors"),
+                Some('p' | 'P') => return unsupported("unicode classes"),
+                _ => (),
+            },
+            '[' if in_class => return unsupported("nested classes"),
+            '&' if in_class && chars.peek() == Some(&'&') => {
+                return unsupported("class set operations")
+            }
+            '[' => {
+                in_class = true;
+                if chars.peek() == Some(&'^') {
+                    chars.next();
+                }
+                // JS reads `[]` as an empty class, the regex crate as a literal `]`
+                if chars.peek() == Some(&']') {
+                    return unsupported("classes starting with `]`");
+                }
+            }
+            ']' => in_class = false,
+            '(' if !in_class && chars.peek() == Some(&'?') => {
+                chars.next();
+                match chars.next() {
+                    Some(':') => (),
+                    Some('=' | '!') => return unsupported("lookarounds"),
+                    Some('<') if matches!(chars.peek(), Some('=' | '!')) => {
+                        return unsupported("lookarounds")
+                    }
+                    Some('<') => (),
+                    Some('P') => return unsupported("`(?P<name>)` groups"),
+                    _ => return unsupported("inline flags"),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(caption: &str, window_classes: &[&str]) -> KWinClientInfo {
+        KWinClientInfo {
+            caption: caption.to_string(),
+            window_classes: window_classes.iter().map(|v| v.to_string()).collect(),
+            pid: None,
+            screen: None,
+        }
+    }
+
+    #[test]
+    fn test_window_matcher() -> Result<()> {
+        let cemu = client("Cemu 2.0 - Breath of the Wild", &["cemu", "cemu"]);
+        let gamepad = client("GamePad View", &["cemu", "cemu"]);
+        let firefox = client("YouTube — Mozilla Firefox", &["navigator", "firefox"]);
+
+        let matcher = KWinWindowMatcher::new(Some("^Cemu"), &["Cemu".to_string()])?;
+        assert!(matcher.matches(&cemu));
+        assert!(!matcher.matches(&gamepad));
+        assert!(matcher.matches_class(&gamepad));
+        assert!(!matcher.matches(&firefox));
+
+        let any = KWinWindowMatcher::new(Some(" "), &["".to_string()])?;
+        assert!(any.matches(&firefox));
+
+        assert!(KWinWindowMatcher::new(Some("(unclosed"), &[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_window_matcher_rejects_non_portable_patterns() -> Result<()> {
+        for pattern in [
+            "^Cemu(?! GamePad)",
+            "(?<=Cemu) Main",
+            "(?<!Game)Pad",
+            "(Cemu) \\1",
+            "(?<name>Cemu) \\k<name>",
+            "(?i)cemu",
+            "(?P<name>Cemu)",
+            "\\ACemu\\z",
+            "[[:alpha:]]+",
+            "[a-z&&[^x]]",
+            "[]a]",
+            "\\p{L}+",
+        ] {
+            let err = KWinWindowMatcher::new(Some(pattern), &[]).unwrap_err();
+            assert!(
+                format!("{err:#}").contains("not supported"),
+                "{pattern} should be rejected as not portable: {err:#}"
+            );
+        }
+
+        for pattern in [
+            "^Cemu (- .*)?$",
+            "(?:Dolphin|Citra) [0-9]+",
+            "(?<name>Cemu)",
+            "[(?!]Cemu",
+            "[^\\]a]\\(?=",
+            "\\\\1",
+        ] {
+            KWinWindowMatcher::new(Some(pattern), &[])
+                .with_context(|| format!("{pattern} should be accepted"))?;
+        }
+
+        Ok(())
+    }
 }
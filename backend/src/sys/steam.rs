@@ -0,0 +1,180 @@
+//! Reads Steam's per-user config: non-Steam shortcuts in `shortcuts.vdf`.
+//!
+//! Steam caches the file while running and overwrites changes made to it, so launch
+//! options are changed through the frontend instead.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{
+    decky_env::DeckyEnv,
+    settings::{AppId, SteamLaunchInfo, SteamUserId64},
+};
+
+use self::vdf::{VdfObject, VdfValue};
+
+pub mod vdf;
+
+/// SteamID64 of account id 0; userdata directories are named by account id.
+const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+const SHORTCUTS_FILE: &str = "shortcuts.vdf";
+
+/// A non-Steam game, as listed in `shortcuts.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct SteamShortcut {
+    pub app_id: AppId,
+    pub app_name: String,
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+    pub icon: String,
+    pub is_hidden: bool,
+    pub tags: Vec<String>,
+}
+
+impl SteamShortcut {
+    fn from_vdf(obj: &VdfObject) -> Option<Self> {
+        let string = |key: &str| {
+            obj.get(key)
+                .and_then(VdfValue::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        Some(Self {
+            app_id: AppId::new(&obj.get("appid")?.as_int()?.to_string()),
+            app_name: string("AppName"),
+            exe: string("Exe"),
+            start_dir: string("StartDir"),
+            launch_options: string("LaunchOptions"),
+            icon: string("icon"),
+            is_hidden: obj.get("IsHidden").and_then(VdfValue::as_int) == Some(1),
+            tags: obj
+                .get("tags")
+                .and_then(VdfValue::as_object)
+                .map(|tags| {
+                    tags.iter()
+                        .filter_map(|(_, v)| v.as_str().map(|v| v.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Config files for a single Steam user.
+#[derive(Debug, Clone)]
+pub struct SteamUserConfig {
+    config_dir: PathBuf,
+}
+
+impl SteamUserConfig {
+    pub fn new(decky_env: &DeckyEnv, user_id_64: &SteamUserId64) -> Result<Self> {
+        let account_id = user_id_64
+            .raw()
+            .parse::<u64>()
+            .ok()
+            .and_then(|id| id.checked_sub(STEAM_ID64_BASE))
+            .with_context(|| format!("invalid steam user id {}", user_id_64.raw()))?;
+
+        Ok(Self {
+            config_dir: decky_env
+                .steam_dir()
+                .join("userdata")
+                .join(account_id.to_string())
+                .join("config"),
+        })
+    }
+
+    pub fn from_launch_info(decky_env: &DeckyEnv, launch_info: &SteamLaunchInfo) -> Result<Self> {
+        Self::new(decky_env, &launch_info.user_id_64)
+    }
+
+    pub fn get_shortcuts(&self) -> Result<Vec<SteamShortcut>> {
+        let root = self.read_shortcuts()?;
+
+        Ok(root
+            .get("shortcuts")
+            .and_then(VdfValue::as_object)
+            .map(|shortcuts| {
+                shortcuts
+                    .iter()
+                    .filter_map(|(_, v)| v.as_object().and_then(SteamShortcut::from_vdf))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    pub fn get_shortcut(&self, app_id: &AppId) -> Result<Option<SteamShortcut>> {
+        Ok(self
+            .get_shortcuts()?
+            .into_iter()
+            .find(|shortcut| shortcut.app_id == *app_id))
+    }
+
+    fn read_shortcuts(&self) -> Result<VdfObject> {
+        let path = self.config_dir.join(SHORTCUTS_FILE);
+        if !path.exists() {
+            return Ok(VdfObject::new());
+        }
+
+        vdf::parse_binary(&std::fs::read(&path)?)
+            .with_context(|| format!("failed to parse {path:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::util::create_dir_all;
+
+    use super::*;
+
+    fn setup_config(name: &str) -> Result<(DeckyEnv, SteamUserConfig)> {
+        let decky_env = DeckyEnv::new_test(name);
+        let config = SteamUserConfig::new(
+            &decky_env,
+            &SteamUserId64::new(&(STEAM_ID64_BASE + 1234).to_string()),
+        )?;
+
+        create_dir_all(&config.config_dir)?;
+        std::fs::copy(
+            PathBuf::from("test/assets/steam").join(SHORTCUTS_FILE),
+            config.config_dir.join(SHORTCUTS_FILE),
+        )?;
+
+        Ok((decky_env, config))
+    }
+
+    #[test]
+    fn test_read_shortcuts() -> Result<()> {
+        let (_env, config) = setup_config("steam_read_shortcuts")?;
+
+        let shortcuts = config.get_shortcuts()?;
+
+        assert_eq!(2, shortcuts.len());
+        assert_eq!(
+            SteamShortcut {
+                app_id: AppId::new("3141592653"),
+                app_name: "Test Emulator".to_string(),
+                exe: "\"/usr/bin/test-emulator\"".to_string(),
+                start_dir: "\"/usr/bin/\"".to_string(),
+                launch_options: "--fullscreen".to_string(),
+                icon: "".to_string(),
+                is_hidden: false,
+                tags: vec!["Emulators".to_string()],
+            },
+            shortcuts[0]
+        );
+        // lowercase keys
+        assert_eq!("Other Game", shortcuts[1].app_name);
+        assert!(shortcuts[1].is_hidden);
+
+        Ok(())
+    }
+}
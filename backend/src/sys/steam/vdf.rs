@@ -0,0 +1,171 @@
+//! Valve's KeyValues format, in the binary encoding used by `shortcuts.vdf`.
+
+use anyhow::{Context, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    Object(VdfObject),
+    String(String),
+    Int32(u32),
+    // parsed to read past them; no shortcut field uses them
+    #[allow(dead_code)]
+    Float(f32),
+    #[allow(dead_code)]
+    UInt64(u64),
+}
+
+impl VdfValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<u32> {
+        match self {
+            VdfValue::Int32(v) => Some(*v),
+            VdfValue::String(v) => v.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&VdfObject> {
+        match self {
+            VdfValue::Object(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Ordered key/value pairs. Keys are matched case-insensitively, as Steam itself
+/// is inconsistent about casing (e.g. `AppName` vs `appname`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VdfObject(Vec<(String, VdfValue)>);
+
+impl VdfObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &VdfValue)> {
+        self.0.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        self.0
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+}
+
+// Binary
+
+const BINARY_OBJECT: u8 = 0x00;
+const BINARY_STRING: u8 = 0x01;
+const BINARY_INT32: u8 = 0x02;
+const BINARY_FLOAT: u8 = 0x03;
+const BINARY_UINT64: u8 = 0x07;
+const BINARY_END: u8 = 0x08;
+
+pub fn parse_binary(input: &[u8]) -> Result<VdfObject> {
+    let mut reader = BinaryReader { input, pos: 0 };
+    let root = parse_binary_object(&mut reader)?;
+
+    if reader.pos != input.len() {
+        anyhow::bail!("unexpected trailing data in binary vdf at {}", reader.pos);
+    }
+
+    Ok(root)
+}
+
+struct BinaryReader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl BinaryReader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let bytes = self
+            .input
+            .get(self.pos..self.pos + len)
+            .context("unexpected end of binary vdf")?;
+        self.pos += len;
+
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn cstring(&mut self) -> Result<String> {
+        let len = self.input[self.pos..]
+            .iter()
+            .position(|b| *b == 0)
+            .context("unterminated string in binary vdf")?;
+        let value = String::from_utf8(self.take(len)?.to_vec())?;
+        self.pos += 1; // nul
+
+        Ok(value)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+}
+
+fn parse_binary_object(reader: &mut BinaryReader) -> Result<VdfObject> {
+    let mut obj = VdfObject::new();
+
+    loop {
+        let kind = reader.byte()?;
+        if kind == BINARY_END {
+            return Ok(obj);
+        }
+
+        let key = reader.cstring()?;
+        let value = match kind {
+            BINARY_OBJECT => VdfValue::Object(parse_binary_object(reader)?),
+            BINARY_STRING => VdfValue::String(reader.cstring()?),
+            BINARY_INT32 => VdfValue::Int32(u32::from_le_bytes(reader.array()?)),
+            BINARY_FLOAT => VdfValue::Float(f32::from_le_bytes(reader.array()?)),
+            BINARY_UINT64 => VdfValue::UInt64(u64::from_le_bytes(reader.array()?)),
+            kind => anyhow::bail!("unknown binary vdf type {kind:#x} for {key}"),
+        };
+
+        obj.0.push((key, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_binary_vdf() -> Result<()> {
+        let source = std::fs::read("test/assets/steam/shortcuts.vdf")?;
+        let parsed = parse_binary(&source)?;
+
+        let shortcut = parsed
+            .get("shortcuts")
+            .and_then(VdfValue::as_object)
+            .and_then(|shortcuts| shortcuts.get("0"))
+            .and_then(VdfValue::as_object)
+            .expect("shortcut 0 should exist");
+
+        assert_eq!(
+            Some(3141592653),
+            shortcut.get("appid").and_then(VdfValue::as_int)
+        );
+        assert_eq!(
+            Some("Test Emulator"),
+            shortcut.get("AppName").and_then(VdfValue::as_str)
+        );
+
+        Ok(())
+    }
+}
@@ -39,6 +39,7 @@ pub fn ui_test(decky_env: Arc<DeckyEnv>) -> Result<()> {
         cemu: None,
         citra: None,
         dolphin: None,
+        melonds: None,
         custom: None,
     };
 
@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+# Regenerates profiles.db and profiles.json with the models of the last release without
+# schema versioning, so the migration tests open a db that release really wrote.
+#
+# Usage: test/assets/db/v1/generate.sh [release commit]
+
+set -euo pipefail
+
+RELEASE=${1:-ffaf78b}
+FIXTURE_DIR=$(cd "$(dirname "$0")" && pwd)
+WORKTREE=$(mktemp -d)
+
+cleanup() {
+    git -C "$FIXTURE_DIR" worktree remove --force "$WORKTREE"
+}
+trap cleanup EXIT
+
+git -C "$FIXTURE_DIR" worktree add --detach "$WORKTREE" "$RELEASE"
+
+cd "$WORKTREE/backend"
+cp "$FIXTURE_DIR/generate_fixture.rs" src/db/generate_fixture.rs
+printf '\n#[cfg(test)]\nmod generate_fixture;\n' >> src/db.rs
+
+cargo test generate_v1_fixture -- --ignored
+
+cp test/out/fixture/profiles.db test/out/fixture/profiles.json "$FIXTURE_DIR/"
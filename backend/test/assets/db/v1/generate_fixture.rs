@@ -0,0 +1,96 @@
+//! Writes the v1 profile db fixture. This runs against the last release without schema
+//! versioning, not the current tree; see `generate.sh`.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{
+    db::ProfileDb,
+    pipeline::{
+        action_registar::PipelineActionRegistrar,
+        data::{
+            PipelineActionId, PipelineDefinition, PipelineDefinitionId, TopLevelDefinition,
+            TopLevelId,
+        },
+    },
+    settings::AppId,
+    util::create_dir_all,
+};
+
+#[test]
+#[ignore]
+fn generate_v1_fixture() -> Result<()> {
+    let registrar = PipelineActionRegistrar::builder().with_core().build();
+
+    let path: PathBuf = "test/out/fixture/profiles.db".into();
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    create_dir_all(path.parent().unwrap())?;
+
+    let db = ProfileDb::new(path.clone(), registrar.clone());
+    let secondary = PipelineActionId::new("core:toplevel:secondary");
+
+    let mut profiles = vec![];
+    for (name, root) in [
+        ("Citra", "core:citra:platform"),
+        ("melonDS", "core:melonds:platform"),
+        ("Cemu", "core:cemu:platform"),
+    ] {
+        let root = PipelineActionId::new(root);
+
+        profiles.push(db.create_profile(PipelineDefinition {
+            id: PipelineDefinitionId::new(),
+            name: name.to_string(),
+            primary_target_override: None,
+            platform: TopLevelDefinition {
+                id: TopLevelId::new(),
+                actions: registrar.make_lookup(&root),
+                root,
+            },
+            toplevel: vec![TopLevelDefinition {
+                id: TopLevelId::new(),
+                actions: registrar.make_lookup(&secondary),
+                root: secondary.clone(),
+            }],
+            desktop_controller_layout_hack: Default::default(),
+        })?);
+    }
+
+    let apps = [AppId::new("12345"), AppId::new("123456")];
+
+    db.set_app_profile_override(
+        apps[0].clone(),
+        profiles[0].id,
+        profiles[0].pipeline.clone(),
+    )?;
+    db.set_app_profile_override(
+        apps[0].clone(),
+        profiles[1].id,
+        profiles[1].pipeline.clone(),
+    )?;
+    db.set_app_profile_override(
+        apps[1].clone(),
+        profiles[0].id,
+        profiles[2].pipeline.clone(),
+    )?;
+    db.set_app_profile_settings(apps[0].clone(), Some(profiles[1].id))?;
+
+    let expected = serde_json::json!({
+        "profiles": db.get_profiles()?,
+        "apps": apps
+            .iter()
+            .map(|id| db.get_app_profile(id))
+            .collect::<Result<Vec<_>>>()?,
+    });
+
+    drop(db);
+
+    std::fs::write(
+        path.with_file_name("profiles.json"),
+        serde_json::to_string_pretty(&expected)?,
+    )?;
+
+    Ok(())
+}
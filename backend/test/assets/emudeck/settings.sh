@@ -0,0 +1,26 @@
+#!/bin/bash
+expert=false
+doSetupRA=true
+doSetupDolphin=true
+doSetupCitra=true
+doSetupLime3DS=false
+doSetupAzahar=false
+doSetupCemu=true
+doSetupCemuNative=false
+doSetupMelonDS=true
+doInstallRA=true
+doInstallDolphin=true
+doInstallCitra=true
+doInstallLime3DS=false
+doInstallAzahar=false
+doInstallCemu=true
+doInstallCemuNative=false
+doInstallMelonDS=true
+# paths
+emulationPath=/run/media/mmcblk0p1/Emulation
+romsPath=/run/media/mmcblk0p1/Emulation/roms
+toolsPath=/run/media/mmcblk0p1/Emulation/tools
+biosPath=/run/media/mmcblk0p1/Emulation/bios
+savesPath=/run/media/mmcblk0p1/Emulation/saves
+storagePath="/run/media/mmcblk0p1/Emulation/storage"
+emuMULTI="ra"
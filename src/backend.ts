@@ -1,22 +1,25 @@
 import {
     AddClientTeardownActionRequest,
+    ApplyGamescopeLaunchOptionsRequest,
+    ApplyGamescopeLaunchOptionsResponse,
     AutoStartRequest,
     CategoryProfile,
     CitraLayoutOption,
     CreateProfileRequest,
     CreateProfileResponse,
     DeleteProfileRequest,
+    GetActionUiSchemasResponse,
     GetAppProfileRequest,
     GetAppProfileResponse,
-    GetAudioDeviceInfoResponse,
     GetClientTeardownActionsResponse,
     GetDefaultAppOverrideForProfileRequest,
     GetDefaultAppOverrideForProfileResponse,
     GetDisplayInfoResponse,
+    GetOptionsRequest,
+    GetOptionsResponse,
     GetProfileRequest,
     GetProfileResponse,
     GetProfilesResponse,
-    GetSecondaryAppInfoResponse,
     GetSettingsResponse,
     GetTemplatesResponse,
     GetTopLevelResponse,
@@ -50,8 +53,9 @@ import { Err, Ok, Result } from './util/result';
 
 export {
     Action,
+    ActionUiSchema,
+    AppMetadata,
     AppProfile,
-    AudioDeviceInfo,
     AutoStartRequest,
     CategoryProfile,
     CemuAudio,
@@ -80,6 +84,8 @@ export {
     LimitedMultiWindowLayout,
     ModePreference,
     MultiWindowLayout,
+    OptionList,
+    OptionSource,
     Pipeline,
     PipelineAction,
     PipelineDefinition,
@@ -91,6 +97,8 @@ export {
     SecondaryAppWindowingBehavior,
     SetProfileRequest,
     Template,
+    UiField,
+    UiOption,
 } from './types/backend_api';
 
 const USDPL_PORT: number = 44666;
@@ -486,18 +494,18 @@ export async function getClientTeardownActions(): Response<GetClientTeardownActi
     return await call_backend_typed('get_client_teardown_actions');
 }
 
+export async function applyGamescopeLaunchOptions(
+    request: ApplyGamescopeLaunchOptionsRequest,
+): Response<ApplyGamescopeLaunchOptionsResponse> {
+    return await call_backend_typed('apply_gamescope_launch_options', request);
+}
+
 // Templates
 
 export async function getTemplates(): Response<GetTemplatesResponse> {
     return await call_backend_typed('get_templates');
 }
 
-// Secondary Apps
-
-export async function getSecondaryAppInfo(): Response<GetSecondaryAppInfoResponse> {
-    return await call_backend_typed('get_secondary_app_info');
-}
-
 // Settings
 
 export async function getSettings(): Response<GetSettingsResponse> {
@@ -514,8 +522,14 @@ export async function getDisplayInfo(): Response<GetDisplayInfoResponse> {
     return await call_backend_typed('get_display_info');
 }
 
-export async function getAudioDeviceInfo(): Response<GetAudioDeviceInfoResponse> {
-    return await call_backend_typed('get_audio_device_info');
+export async function getActionUiSchemas(): Response<GetActionUiSchemasResponse> {
+    return await call_backend_typed('get_action_ui_schemas');
+}
+
+export async function getOptions(
+    request: GetOptionsRequest,
+): Response<GetOptionsResponse> {
+    return await call_backend_typed('get_options', request);
 }
 
 // Test
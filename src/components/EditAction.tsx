@@ -14,7 +14,6 @@ import { FaFile } from 'react-icons/fa';
 import { FaPlus, FaTrash } from 'react-icons/fa6';
 import {
     Action,
-    AudioDeviceInfo,
     CemuAudio,
     CemuAudioChannels,
     CemuAudioSetting,
@@ -29,19 +28,15 @@ import {
     LaunchSecondaryAppPreset,
     LaunchSecondaryFlatpakApp,
     LimitedMultiWindowLayout,
-    ModePreference,
     MultiWindowLayout,
     RelativeLocation,
+    UiOption,
     citraLayoutOptions,
-    melonDSLayoutOptions,
-    melonDSSizingOptions,
     secondaryAppScreenPreferences,
     secondaryAppWindowingOptions,
-    touchSelectionModeOptions,
 } from '../backend';
 import useAudioDeviceInfo from '../hooks/useAudioDeviceInfo';
-import useDisplayInfo from '../hooks/useDisplayInfo';
-import useSecondaryAppInfo from '../hooks/useSecondaryAppPresetInfo';
+import useOptions from '../hooks/useOptions';
 
 import { labelForCamelCase, labelForKebabCase } from '../util/display';
 import {
@@ -50,6 +45,7 @@ import {
     ActionChildSliderBuilder,
 } from './ActionChild';
 import HandleLoading from './HandleLoading';
+import SchemaEditAction from './SchemaEditAction';
 
 interface EditActionProps {
     action: Action;
@@ -69,6 +65,8 @@ export function EditAction(props: EditActionProps): ReactElement | null {
 type InternalEditActionProps = {
     actionChildBuilder: ActionChildBuilder;
     actionChildSliderBuilder: ActionChildSliderBuilder;
+    /// Only shows the fields placed on the QAM, for schema rendered actions
+    qam?: boolean;
 } & EditActionProps;
 
 export function InternalEditAction({
@@ -77,6 +75,7 @@ export function InternalEditAction({
     onChange,
     actionChildBuilder,
     actionChildSliderBuilder,
+    qam,
 }: InternalEditActionProps): ReactElement | null {
     const Builder = actionChildBuilder;
     const SliderBuilder = actionChildSliderBuilder;
@@ -300,59 +299,14 @@ export function InternalEditAction({
             });
         case 'MelonDSLayout':
             return (
-                <>
-                    <Builder indentLevel={indentLevel} label="Layout Option">
-                        <Dropdown
-                            selectedOption={cloned.value.layout_option}
-                            rgOptions={melonDSLayoutOptions.map((a) => {
-                                return {
-                                    label: labelForCamelCase(a),
-                                    data: a,
-                                };
-                            })}
-                            onChange={(option) => {
-                                cloned.value.layout_option = option.data;
-                                onChange(cloned);
-                            }}
-                        />
-                    </Builder>
-                    <Builder indentLevel={indentLevel} label="Sizing Option">
-                        <Dropdown
-                            selectedOption={cloned.value.sizing_option}
-                            rgOptions={melonDSSizingOptions.map((a) => {
-                                return {
-                                    label: labelForCamelCase(a),
-                                    data: a,
-                                };
-                            })}
-                            onChange={(option) => {
-                                cloned.value.sizing_option = option.data;
-                                onChange(cloned);
-                            }}
-                        />
-                    </Builder>
-                    <Builder indentLevel={indentLevel} label="Swap Screens">
-                        <Toggle
-                            value={cloned.value.swap_screens}
-                            onChange={(isEnabled) => {
-                                cloned.value.swap_screens = isEnabled;
-                                onChange(cloned);
-                            }}
-                        />
-                    </Builder>
-                    <Builder
-                        indentLevel={indentLevel}
-                        label="Book Mode (Rotate Screens)"
-                    >
-                        <Toggle
-                            value={cloned.value.book_mode}
-                            onChange={(isEnabled) => {
-                                cloned.value.book_mode = isEnabled;
-                                onChange(cloned);
-                            }}
-                        />
-                    </Builder>
-                </>
+                <SchemaEditAction
+                    action={cloned}
+                    indentLevel={indentLevel}
+                    onChange={onChange}
+                    Builder={Builder}
+                    SliderBuilder={SliderBuilder}
+                    qam={qam ?? false}
+                />
             );
         case 'SourceFile':
             const sourceValue = cloned.value;
@@ -857,21 +811,14 @@ export function InternalEditAction({
             );
         case 'TouchConfig':
             return (
-                <Builder indentLevel={indentLevel} label="Touch Mode">
-                    <Dropdown
-                        selectedOption={cloned.value.touch_mode}
-                        rgOptions={touchSelectionModeOptions.map((a) => {
-                            return {
-                                label: labelForCamelCase(a),
-                                data: a,
-                            };
-                        })}
-                        onChange={(option) => {
-                            cloned.value.touch_mode = option.data;
-                            onChange(cloned);
-                        }}
-                    />
-                </Builder>
+                <SchemaEditAction
+                    action={cloned}
+                    indentLevel={indentLevel}
+                    onChange={onChange}
+                    Builder={Builder}
+                    SliderBuilder={SliderBuilder}
+                    qam={qam ?? false}
+                />
             );
 
         case 'VirtualScreen':
@@ -900,13 +847,13 @@ function SecondaryFlatpakApp({
     onChange,
     Builder,
 }: LaunchSecondaryFlatpakAppProps): ReactElement {
-    const secondaryInfo = useSecondaryAppInfo();
+    const flatpaks = useOptions({ type: 'InstalledFlatpaks' });
     const [args, setArgs] = useState(cloned.value.app.args);
 
     return (
         <HandleLoading
-            value={secondaryInfo}
-            onOk={(secondaryInfo) => {
+            value={flatpaks}
+            onOk={(flatpaks) => {
                 const windowing = cloned.value.windowing_behavior;
 
                 var i = 0;
@@ -984,14 +931,12 @@ function SecondaryFlatpakApp({
                         >
                             <Dropdown
                                 selectedOption={cloned.value.app.app_id}
-                                rgOptions={secondaryInfo.installed_flatpaks.map(
-                                    (v) => {
-                                        return {
-                                            label: `${v.name} (${v.app_id})`,
-                                            data: v.app_id,
-                                        };
-                                    },
-                                )}
+                                rgOptions={flatpaks.options.map((v) => {
+                                    return {
+                                        label: `${v.label} (${v.value})`,
+                                        data: v.value,
+                                    };
+                                })}
                                 onChange={(value) => {
                                     cloned.value.app.app_id = value.data;
                                     onChange(cloned);
@@ -1057,35 +1002,24 @@ function SecondaryAppPreset({
     onChange,
     Builder,
 }: SecondaryAppPresetProps): ReactElement {
-    const secondaryInfo = useSecondaryAppInfo();
     const [filtered, setFiltered] = useState(true);
+    const presets = useOptions({
+        type: 'SecondaryAppPresets',
+        value: { installed_only: filtered },
+    });
 
     // TODO::ability to create new presets (probably as a separate main toplevel tab, and a "convert to preset" option on "custom")
 
     return (
         <HandleLoading
-            value={secondaryInfo}
-            onOk={(secondaryInfo) => {
-                const options = [];
-
-                for (const k in secondaryInfo.presets) {
-                    const preset = secondaryInfo.presets[k];
-                    const flatpakInstalled =
-                        preset.app.type === 'Flatpak' &&
-                        secondaryInfo.installed_flatpaks.find(
-                            (v) => v.app_id === preset.app.app_id,
-                        );
-                    const shouldPush = !filtered || flatpakInstalled;
-
-                    if (shouldPush) {
-                        options.push({
-                            label: preset.name,
-                            data: k,
-                        });
-                    }
-                }
-
-                options.sort((a, b) => a.label.localeCompare(b.label));
+            value={presets}
+            onOk={(presets) => {
+                const options = presets.options.map((v) => {
+                    return {
+                        label: v.label,
+                        data: v.value,
+                    };
+                });
 
                 return (
                     <>
@@ -1181,87 +1115,31 @@ function ExternalDisplaySettingsSelector({
     onChange,
     Builder,
 }: ExternalDisplaySettingsSelectorProps): ReactElement {
-    const displayInfo = useDisplayInfo();
+    const displaySettings = useOptions({ type: 'DisplayResolutions' });
 
     return (
         <HandleLoading
-            value={displayInfo}
-            onOk={(displayInfo) => {
-                const fixed: ExternalDisplaySettings[] = [
-                    { type: 'Previous' },
-                    { type: 'Native' },
-                ];
-
-                const options: DropdownOption[] = fixed.map((setting) => {
+            value={displaySettings}
+            onOk={(displaySettings) => {
+                const settingsOptions = displaySettings.options.map((v) => {
                     return {
-                        label: labelForCamelCase(setting.type),
-                        data: setting,
+                        label: v.label,
+                        data: v.value as ExternalDisplaySettings,
                     };
                 });
 
+                const options: DropdownOption[] = settingsOptions.filter(
+                    (v) => v.data.type !== 'Preference',
+                );
+                const custom = settingsOptions.filter(
+                    (v) => v.data.type === 'Preference',
+                );
+
                 // only show display options if we actually have some
-                if (displayInfo != null && displayInfo.length > 0) {
+                if (custom.length > 0) {
                     options.push({
                         label: 'Custom',
-                        options: displayInfo.map((info) => {
-                            // note: most settings are "AtMost" in case the user changes displays without changing the plugin settings.
-
-                            if (info.refresh) {
-                                // If we have a refresh rate, use it
-                                const value: ModePreference = {
-                                    aspect_ratio: {
-                                        type: 'Exact',
-                                        value: info.width / info.height,
-                                    },
-                                    refresh: {
-                                        type: 'AtMost',
-                                        value: info.refresh,
-                                    },
-                                    resolution: {
-                                        type: 'AtMost',
-                                        value: {
-                                            h: info.height,
-                                            w: info.width,
-                                        },
-                                    },
-                                };
-                                return {
-                                    label: `${info.width}x${
-                                        info.height
-                                    } @ ${info.refresh.toFixed(2)}`,
-                                    data: {
-                                        type: 'Preference',
-                                        value,
-                                    },
-                                };
-                            } else {
-                                // If not, have the system scale up as high as possible
-                                const value: ModePreference = {
-                                    aspect_ratio: {
-                                        type: 'Exact',
-                                        value: info.width / info.height,
-                                    },
-                                    refresh: {
-                                        type: 'AtMost',
-                                        value: 2000.0,
-                                    },
-                                    resolution: {
-                                        type: 'AtMost',
-                                        value: {
-                                            h: info.height,
-                                            w: info.width,
-                                        },
-                                    },
-                                };
-                                return {
-                                    label: `${info.width}x${info.height}`,
-                                    data: {
-                                        type: 'Preference',
-                                        value,
-                                    },
-                                };
-                            }
-                        }),
+                        options: custom,
                     });
                 }
                 function comparator(value: any, other: any) {
@@ -1350,7 +1228,7 @@ function CemuAudioSelector({
                     label: string;
                     dir: string;
                     channelOptions: CemuAudioChannels[];
-                    devices: AudioDeviceInfo[];
+                    devices: UiOption[];
                     prefs: CemuAudioSetting;
                 }[] = [
                     {
@@ -1409,8 +1287,8 @@ function CemuAudioSelector({
                                         label: 'Custom',
                                         options: devices.map((info) => {
                                             return {
-                                                label: info.description,
-                                                data: info.name,
+                                                label: info.label,
+                                                data: info.value,
                                             };
                                         }),
                                     });
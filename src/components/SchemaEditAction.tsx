@@ -0,0 +1,294 @@
+import { Dropdown, DropdownOption, TextField, Toggle } from '@decky/ui';
+import _ from 'lodash';
+import { Fragment, ReactElement } from 'react';
+import { Action, OptionSource, UiField, UiOption } from '../backend';
+import useActionUiSchema from '../hooks/useActionUiSchema';
+import useOptions from '../hooks/useOptions';
+import { ActionChildBuilder, ActionChildSliderBuilder } from './ActionChild';
+import HandleLoading from './HandleLoading';
+
+interface SchemaEditActionProps {
+    action: Action;
+    indentLevel: number;
+    onChange: (action: Action) => void;
+    Builder: ActionChildBuilder;
+    SliderBuilder: ActionChildSliderBuilder;
+    /// Only shows the fields placed on the QAM
+    qam: boolean;
+}
+
+/// Renders an action editor from the backend UI schema of the action type.
+export default function SchemaEditAction({
+    action,
+    indentLevel,
+    onChange,
+    Builder,
+    SliderBuilder,
+    qam,
+}: SchemaEditActionProps): ReactElement {
+    const schema = useActionUiSchema(action.type);
+
+    return (
+        <HandleLoading
+            value={schema}
+            onOk={(schema) => {
+                if (!schema) {
+                    return <p>No editor for {action.type}</p>;
+                }
+
+                const fields = schema.fields.filter(
+                    (field) =>
+                        (!qam || field.placement === 'Qam') &&
+                        isVisible(field, action.value),
+                );
+
+                let group: string | null | undefined = null;
+
+                return (
+                    <>
+                        {fields.map((field) => {
+                            const header =
+                                field.group && field.group !== group ? (
+                                    <Builder
+                                        indentLevel={indentLevel}
+                                        label={field.group}
+                                    />
+                                ) : null;
+                            group = field.group;
+
+                            return (
+                                <Fragment key={field.path.join('.')}>
+                                    {header}
+                                    <SchemaField
+                                        field={field}
+                                        value={getValue(
+                                            action.value,
+                                            field.path,
+                                        )}
+                                        indentLevel={
+                                            field.group
+                                                ? indentLevel + 1
+                                                : indentLevel
+                                        }
+                                        Builder={Builder}
+                                        SliderBuilder={SliderBuilder}
+                                        onChange={(value) => {
+                                            const cloned = _.cloneDeep(action);
+                                            cloned.value = setValue(
+                                                cloned.value,
+                                                field.path,
+                                                value,
+                                            );
+                                            onChange(cloned);
+                                        }}
+                                    />
+                                </Fragment>
+                            );
+                        })}
+                    </>
+                );
+            }}
+        />
+    );
+}
+
+interface SchemaFieldProps {
+    field: UiField;
+    value: unknown;
+    indentLevel: number;
+    onChange: (value: unknown) => void;
+    Builder: ActionChildBuilder;
+    SliderBuilder: ActionChildSliderBuilder;
+}
+
+function SchemaField({
+    field,
+    value,
+    indentLevel,
+    onChange,
+    Builder,
+    SliderBuilder,
+}: SchemaFieldProps): ReactElement | null {
+    const widget = field.widget;
+    const builderProps = {
+        indentLevel,
+        label: field.label,
+        description: field.description ?? undefined,
+    };
+
+    switch (widget.type) {
+        case 'Toggle':
+            return (
+                <Builder {...builderProps}>
+                    <Toggle
+                        value={!!value}
+                        onChange={(isEnabled) => onChange(isEnabled)}
+                    />
+                </Builder>
+            );
+        case 'Number':
+            return (
+                <Builder {...builderProps}>
+                    <TextField
+                        value={
+                            value === null || value === undefined
+                                ? ''
+                                : `${value}`
+                        }
+                        mustBeNumeric={true}
+                        rangeMin={widget.value.min ?? undefined}
+                        rangeMax={widget.value.max ?? undefined}
+                        onChange={(e) => {
+                            const text = e.target.value;
+                            onChange(
+                                text === '' && field.optional
+                                    ? null
+                                    : Number(text),
+                            );
+                        }}
+                    />
+                </Builder>
+            );
+        case 'Slider':
+            return (
+                <SliderBuilder
+                    {...builderProps}
+                    value={typeof value === 'number' ? value : widget.value.min}
+                    min={widget.value.min}
+                    max={widget.value.max}
+                    step={widget.value.step}
+                    showValue={true}
+                    onChange={(value) => onChange(value)}
+                />
+            );
+        case 'Text':
+            return (
+                <Builder {...builderProps}>
+                    <TextField
+                        value={typeof value === 'string' ? value : ''}
+                        onChange={(e) => {
+                            const text = e.target.value;
+                            onChange(
+                                text === '' && field.optional ? null : text,
+                            );
+                        }}
+                    />
+                </Builder>
+            );
+        case 'Dropdown':
+            return (
+                <Builder {...builderProps}>
+                    <OptionDropdown
+                        options={widget.value}
+                        optional={field.optional}
+                        value={value}
+                        onChange={onChange}
+                    />
+                </Builder>
+            );
+        case 'SourceDropdown':
+            return (
+                <Builder {...builderProps}>
+                    <SourceDropdown
+                        source={widget.value}
+                        optional={field.optional}
+                        value={value}
+                        onChange={onChange}
+                    />
+                </Builder>
+            );
+        case 'Custom':
+            // no generic widget fits; these fields need a dedicated editor
+            return null;
+        default:
+            const typecheck: never = widget;
+            throw `ui widget failed to typecheck: ${typecheck}`;
+    }
+}
+
+interface OptionDropdownProps {
+    options: UiOption[];
+    optional: boolean;
+    value: unknown;
+    onChange: (value: unknown) => void;
+}
+
+function OptionDropdown({
+    options,
+    optional,
+    value,
+    onChange,
+}: OptionDropdownProps): ReactElement {
+    const all = optional
+        ? [{ label: 'None', value: null }, ...options]
+        : options;
+
+    // options are selected by index, since their values may be objects
+    const rgOptions: DropdownOption[] = all.map((option, i) => {
+        return {
+            label: option.label,
+            data: i,
+        };
+    });
+
+    return (
+        <Dropdown
+            selectedOption={all.findIndex((option) =>
+                _.isEqual(option.value, value ?? null),
+            )}
+            rgOptions={rgOptions}
+            onChange={(option) => onChange(all[option.data].value)}
+        />
+    );
+}
+
+interface SourceDropdownProps {
+    source: OptionSource;
+    optional: boolean;
+    value: unknown;
+    onChange: (value: unknown) => void;
+}
+
+function SourceDropdown({
+    source,
+    optional,
+    value,
+    onChange,
+}: SourceDropdownProps): ReactElement {
+    const options = useOptions(source);
+
+    return (
+        <HandleLoading
+            value={options}
+            onOk={(list) => (
+                <OptionDropdown
+                    options={list.options}
+                    optional={optional}
+                    value={value ?? list.default}
+                    onChange={onChange}
+                />
+            )}
+        />
+    );
+}
+
+function isVisible(field: UiField, value: unknown): boolean {
+    const condition = field.visible_if;
+
+    return (
+        !condition ||
+        condition.one_of.some((v) =>
+            _.isEqual(v, getValue(value, condition.path)),
+        )
+    );
+}
+
+function getValue(value: unknown, path: string[]): unknown {
+    return path.length === 0 ? value : _.get(value, path);
+}
+
+function setValue<T>(value: T, path: string[], field: unknown): T {
+    return path.length === 0
+        ? (field as T)
+        : (_.set(value as object, path, field) as T);
+}
@@ -3,6 +3,7 @@
 import { createContext, FC, useContext, useEffect, useState } from 'react';
 import {
     ApiError,
+    AppMetadata,
     AppProfile,
     getAppProfile,
     getDefaultAppOverrideForProfileRequest,
@@ -56,6 +57,9 @@ export interface AppTargetSelection {
 interface PublicAppState {
     appDetails: ShortAppDetails | null;
     appProfile: Loading<AppProfile>;
+    /// The profile to launch the app with, selected by the backend from the
+    /// default profile and the profile rules
+    selectedProfileId: string | null;
     reifiedPipelines: { [k: string]: Result<ReifyPipelineResponse, ApiError> };
     openViews: { [k: string]: { [k: string]: boolean } };
 }
@@ -86,6 +90,7 @@ export class ShortAppDetailsState {
     private readonly delayMs = 1000;
     private appDetails: ShortAppDetails | null = null;
     private appProfile: Loading<AppProfile>;
+    private selectedProfileId: string | null = null;
     private reifiedPipelines: ReifiedPipelines = {};
     private openViews: { [k: string]: { [k: string]: boolean } } = {};
     private lastOnAppPageTime: number = 0;
@@ -98,6 +103,7 @@ export class ShortAppDetailsState {
         return {
             appDetails: this.appDetails ? { ...this.appDetails } : null,
             appProfile: this.appProfile ? { ...this.appProfile } : null,
+            selectedProfileId: this.selectedProfileId,
             reifiedPipelines: { ...this.reifiedPipelines },
             openViews: { ...this.openViews },
         };
@@ -230,6 +236,8 @@ export class ShortAppDetailsState {
             if (overrides[profileId]) {
                 const res = await reifyPipeline({
                     pipeline: overrides[profileId],
+                    profile_id: profileId,
+                    app_id: appId.toString(),
                 });
                 this.reifiedPipelines[profileId] = res;
                 this.appTargets[profileId] = await computeAppTargetSelection(
@@ -337,11 +345,11 @@ export class ShortAppDetailsState {
                 this.appDetails &&
                 (!appIdToMatch || this.appDetails?.appId == appIdToMatch)
             ) {
-                const newProfile = (
-                    await getAppProfile({
-                        app_id: this.appDetails.appId.toString(),
-                    })
-                ).map((a) => a.app ?? null);
+                const res = await getAppProfile({
+                    app_id: this.appDetails.appId.toString(),
+                    metadata: getAppMetadata(this.appDetails),
+                });
+                const newProfile = res.map((a) => a.app ?? null);
 
                 if (this.appProfile?.isOk && newProfile.isOk) {
                     for (const key in this.appProfile.data.overrides) {
@@ -351,6 +359,9 @@ export class ShortAppDetailsState {
                 }
 
                 this.appProfile = newProfile;
+                this.selectedProfileId = res.isOk
+                    ? res.data.selected_profile ?? null
+                    : null;
 
                 if (!this.appProfile?.isOk) {
                     logger.toastWarn(
@@ -364,6 +375,8 @@ export class ShortAppDetailsState {
                     for (const k in overrides) {
                         let reified = await reifyPipeline({
                             pipeline: overrides[k],
+                            profile_id: k,
+                            app_id: this.appDetails.appId.toString(),
                         });
 
                         this.reifiedPipelines[k] = reified;
@@ -407,6 +420,7 @@ export class ShortAppDetailsState {
 
         this.appDetails = appDetails;
         this.appProfile = null;
+        this.selectedProfileId = null;
         this.openViews = {};
         this.reifiedPipelines = {};
         this.lastOnAppPageTime = time;
@@ -416,17 +430,30 @@ export class ShortAppDetailsState {
 
     private async fetchProfile(appDetails: ShortAppDetails | null) {
         if (appDetails) {
-            const profile = (
-                await getAppProfile({ app_id: appDetails.appId.toString() })
-            ).map((v) => v.app ?? null);
+            const res = await getAppProfile({
+                app_id: appDetails.appId.toString(),
+                metadata: getAppMetadata(appDetails),
+            });
+            const profile = res.map((v) => v.app ?? null);
             if (this.appDetails?.appId == appDetails.appId) {
                 this.appProfile = profile;
+                this.selectedProfileId = res.isOk
+                    ? res.data.selected_profile ?? null
+                    : null;
 
                 if (profile.isOk) {
                     const profileIds = await getProfileIdsForAppId(
                         appDetails.appId,
                     );
 
+                    // profiles selected by rule aren't necessarily tagged with the app's collections
+                    if (
+                        this.selectedProfileId &&
+                        !profileIds.includes(this.selectedProfileId)
+                    ) {
+                        profileIds.push(this.selectedProfileId);
+                    }
+
                     const profiles = profileIds.map((profileId) =>
                         this.loadProfileOverride(appDetails.appId, profileId),
                     );
@@ -532,6 +559,24 @@ export const ShortAppDetailsStateContextProvider: FC<
     );
 };
 
+function getAppMetadata(appDetails: ShortAppDetails): AppMetadata {
+    const overview = appStore.GetAppOverviewByAppID(appDetails.appId);
+    const tags = appStore.m_mapStoreTagLocalization._data;
+
+    return {
+        app_id: appDetails.appId.toString(),
+        title: appDetails.sortAs,
+        is_steam_game: appDetails.isSteamGame,
+        collections: collectionStore.userCollections
+            .filter((uc) => uc.apps.has(appDetails.appId))
+            .map((uc) => uc.displayName),
+        tags: (overview?.store_tag ?? [])
+            .map((tag) => tags.get(tag)?.value)
+            .filter((tag): tag is string => !!tag),
+        executable: null,
+    };
+}
+
 async function getProfileIdsForAppId(appId: number): Promise<string[]> {
     const loadedProfiles = (await getProfiles()).unwrap().profiles;
     const includedProfiles = new Set<string>();
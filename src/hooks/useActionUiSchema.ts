@@ -0,0 +1,39 @@
+import { useEffect, useState } from 'react';
+import { ActionUiSchema, getActionUiSchemas } from '../backend';
+import { ActionType } from '../types/backend_api';
+import { Loading } from '../util/loading';
+
+const useActionUiSchema = (
+    actionType: ActionType,
+): Loading<ActionUiSchema | undefined> => {
+    const [result, setResult] =
+        useState<Loading<ActionUiSchema | undefined>>(null);
+
+    useEffect(() => {
+        let active = true;
+
+        if (result === null) {
+            (async function load() {
+                const res = await getActionUiSchemas();
+
+                if (!active) {
+                    return;
+                }
+
+                setResult(
+                    res.map((v) =>
+                        v.schemas.find((s) => s.action_type === actionType),
+                    ),
+                );
+            })();
+        }
+
+        return () => {
+            active = false;
+        };
+    });
+
+    return result;
+};
+
+export default useActionUiSchema;
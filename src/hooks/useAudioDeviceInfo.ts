@@ -1,27 +1,50 @@
 import { useEffect, useState } from 'react';
-import { getAudioDeviceInfo } from '../backend';
-import { GetAudioDeviceInfoResponse } from '../types/backend_api';
+import { OptionList, UiOption, getOptions } from '../backend';
 import { Loading } from '../util/loading';
 
-const useAudioDeviceInfo = (): Loading<GetAudioDeviceInfoResponse> => {
-    const [result, setResult] =
-        useState<Loading<GetAudioDeviceInfoResponse>>(null);
+export interface AudioDevices {
+    sinks: UiOption[];
+    sources: UiOption[];
+}
+
+// the default device is listed separately, as a fixed option
+function devicesOf(list: OptionList): UiOption[] {
+    return list.options.filter((v) => v.value !== list.default);
+}
+
+const useAudioDeviceInfo = (): Loading<AudioDevices> => {
+    const [result, setResult] = useState<Loading<AudioDevices>>(null);
 
     useEffect(() => {
         let active = true;
 
         if (result === null) {
             (async function load() {
-                const res = await getAudioDeviceInfo();
+                const [sinks, sources] = await Promise.all([
+                    getOptions({
+                        source: { type: 'AudioDevices', value: { kind: 'Sink' } },
+                    }),
+                    getOptions({
+                        source: {
+                            type: 'AudioDevices',
+                            value: { kind: 'Source' },
+                        },
+                    }),
+                ]);
 
                 if (!active) {
                     return;
                 }
 
                 setResult(
-                    res.map((v) => {
-                        return v;
-                    }),
+                    sinks.andThen((sinks) =>
+                        sources.map((sources) => {
+                            return {
+                                sinks: devicesOf(sinks.list),
+                                sources: devicesOf(sources.list),
+                            };
+                        }),
+                    ),
                 );
             })();
         }
@@ -84,6 +84,7 @@ const useLaunchActions = (
 
                         const reified = await reifyPipeline({
                             pipeline: p.pipeline,
+                            profile_id: pid,
                         });
 
                         if (reified.isOk) {
@@ -0,0 +1,30 @@
+import { useEffect, useState } from 'react';
+import { OptionList, OptionSource, getOptions } from '../backend';
+import { Loading } from '../util/loading';
+
+const useOptions = (source: OptionSource): Loading<OptionList> => {
+    const [result, setResult] = useState<Loading<OptionList>>(null);
+    const key = JSON.stringify(source);
+
+    useEffect(() => {
+        let active = true;
+
+        (async function load() {
+            const res = await getOptions({ source });
+
+            if (!active) {
+                return;
+            }
+
+            setResult(res.map((v) => v.list));
+        })();
+
+        return () => {
+            active = false;
+        };
+    }, [key]);
+
+    return result;
+};
+
+export default useOptions;
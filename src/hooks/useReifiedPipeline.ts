@@ -1,13 +1,29 @@
 import { useEffect, useState } from 'react';
 import {
-    PipelineDefinition,
+    PipelineContainer,
+    ReifyPipelineRequest,
     ReifyPipelineResponse,
+    isCategoryProfile,
     reifyPipeline,
 } from '../backend';
 import { Loading } from '../util/loading';
 
+function reifyRequest(container: PipelineContainer): ReifyPipelineRequest {
+    if (isCategoryProfile(container)) {
+        return { pipeline: container.pipeline, profile_id: container.id };
+    } else if ('appId' in container) {
+        return {
+            pipeline: container.pipeline,
+            profile_id: container.profileId,
+            app_id: container.appId,
+        };
+    } else {
+        return { pipeline: container.pipeline };
+    }
+}
+
 const useReifiedPipeline = (
-    definition: PipelineDefinition,
+    container: PipelineContainer,
 ): Loading<ReifyPipelineResponse> => {
     const [result, setResult] = useState<Loading<ReifyPipelineResponse>>(null);
 
@@ -15,9 +31,7 @@ const useReifiedPipeline = (
         let active = true;
 
         (async function load() {
-            const res = await reifyPipeline({
-                pipeline: definition,
-            });
+            const res = await reifyPipeline(reifyRequest(container));
 
             if (!active) {
                 return;
@@ -29,7 +43,7 @@ const useReifiedPipeline = (
         return () => {
             active = false;
         };
-    }, [definition]);
+    }, [container]);
 
     return result;
 };
@@ -23,6 +23,7 @@ const useActionButtonProps = ({
     const {
         appDetails,
         appProfile,
+        selectedProfileId,
         useAppTarget,
         ensureSelectedClientUpdated,
     } = useAppState();
@@ -30,9 +31,8 @@ const useActionButtonProps = ({
     const launchActions = useLaunchActions(appDetails);
 
     const action = appProfile?.isOk
-        ? launchActions.find(
-              (a) => a.profileId == appProfile.data.default_profile,
-          ) ?? launchActions[0]
+        ? launchActions.find((a) => a.profileId == selectedProfileId) ??
+          launchActions[0]
         : null;
 
     let target: PipelineTargetOrNative | null = useAppTarget({
@@ -50,6 +50,7 @@ const useActionButtonProps = ({
     const state = {
         appDetails,
         appProfile,
+        selectedProfileId,
         target,
     };
 
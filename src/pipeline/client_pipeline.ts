@@ -1,13 +1,12 @@
 import { sleep } from '@decky/ui';
-import { uniqueId } from 'lodash';
 import {
     Action,
     Pipeline,
     PipelineTarget,
     RuntimeSelection,
     addClientTeardownAction,
+    applyGamescopeLaunchOptions,
     getClientTeardownActions,
-    getDisplayInfo,
     log as logBackend,
     removeClientTeardownActions,
 } from '../backend';
@@ -15,8 +14,6 @@ import { ClientTeardownAction } from '../types/backend_api';
 import { LogLevel, logger } from '../util/log';
 import { Err, Ok, Result } from '../util/result';
 
-const gamescopeLaunchOptionsRxp = /$gamescope(.*)\s--\s(.*)/g;
-
 export async function setupClientPipeline(
     appId: number,
     pipeline: Pipeline,
@@ -86,107 +83,34 @@ async function execAction(
     const type = action.type;
     switch (type) {
         case 'MainAppAutomaticWindowing':
-            const gamescope = action.value.gamescope;
-            const general = action.value.general;
-            if (gamescope.use_gamescope) {
-                await logBackend(
-                    LogLevel.Debug,
-                    'configuring gamescope launch actions',
-                );
-
-                const dislayRes = await getPrimaryDisplayResolution(
-                    general.swap_screens,
-                );
-                if (!dislayRes.isOk) {
-                    return Err(dislayRes.err);
-                }
-
-                const previousLaunchOptions =
-                    appDetailsStore.GetAppDetails(appId).strLaunchOptions;
-
-                // strip existing gamescope prefix to ensure no duplicates
-                const strippedLaunchOptions = gamescopeLaunchOptionsRxp.exec(
-                    previousLaunchOptions,
-                );
-
-                const command = strippedLaunchOptions
-                    ? strippedLaunchOptions[2]
-                    : '%command%';
-
-                const gamescopeOptions = [
-                    `gamescope -e -W ${dislayRes.data.width | 0} -H ${
-                        dislayRes.data.height | 0
-                    } -F ${gamescope.filter.toLowerCase()} -S ${gamescope.scaler.toLowerCase()}`,
-                ];
-
-                if (gamescope.filter === 'Fsr') {
-                    gamescopeOptions.push(
-                        `--sharpness ${gamescope.fsr_sharpness}`,
-                    );
-                } else if (gamescope.filter === 'Nis') {
-                    gamescopeOptions.push(
-                        `--sharpness ${gamescope.nis_sharpness}`,
-                    );
-                }
-
-                switch (gamescope.fullscreen_option) {
-                    case 'Borderless':
-                        gamescopeOptions.push('-b');
-                    case 'Fullscreen':
-                        gamescopeOptions.push('-f');
-                }
-
-                if (gamescope.game_resolution) {
-                    gamescopeOptions.push(
-                        `-w ${gamescope.game_resolution.w | 0} -h ${
-                            gamescope.game_resolution.h | 0
-                        }`,
-                    );
-                }
-
-                if (gamescope.game_refresh) {
-                    gamescopeOptions.push(`-r ${gamescope.game_refresh | 0}`);
-                }
-
-                gamescopeOptions.push('--', command);
+            // The backend builds the launch options and records the teardown action
+            // restoring the current ones; only the steam client can apply them.
+            const res = await applyGamescopeLaunchOptions({
+                app_id: appId,
+                action: action.value,
+                launch_options:
+                    appDetailsStore.GetAppDetails(appId).strLaunchOptions,
+            });
+            if (!res.isOk) {
+                return Err(res.err.err);
+            }
 
-                const newLaunchOptions = gamescopeOptions.join(' ');
+            const launchOptions = res.data.launch_options;
+            if (launchOptions) {
                 logBackend(
                     LogLevel.Debug,
-                    `configuring gamescope for ${appId} with launch options ${newLaunchOptions}`,
+                    `configuring gamescope for ${appId} with launch options ${launchOptions}`,
                 );
 
-                SteamClient.Apps.SetAppLaunchOptions(appId, newLaunchOptions);
-
-                return Ok({
-                    type: 'MainAppAutomaticWindowing',
-                    app_id: appId,
-                    action_id: uniqueId(),
-                    previous_launch_options: previousLaunchOptions,
-                });
+                SteamClient.Apps.SetAppLaunchOptions(appId, launchOptions);
             }
+
+            return Ok(null);
         default:
             return Ok(null);
     }
 }
 
-async function getPrimaryDisplayResolution(
-    useInternal: boolean,
-): Promise<Result<{ width: number; height: number }, string>> {
-    if (useInternal) {
-        return Ok({ width: 1280, height: 800 }); // TODO::don't hardcode this
-    }
-
-    const res = await getDisplayInfo();
-    return res
-        .map((v) =>
-            v.available_values.reduce((acc, c) =>
-                acc.height * acc.width > c.height * c.width ? acc : c,
-            ),
-        )
-        .mapErr((err) => err.err);
-}
-
 /// Tears down client pipeline. If [appId] is undefined, will tear down all existing actions,
 /// otherwise will only tear down actions for the specified [appId].
 export async function teardownClientPipeline(appId?: number): Promise<void> {
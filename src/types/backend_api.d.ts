@@ -191,7 +191,7 @@ export type EmuSettingsSource =
       type: "Custom";
       value: CustomEmuSource;
     };
-export type FlatpakSource = "Cemu" | "Citra" | "MelonDSPrerelease" | "MelonDS" | "Lime3ds";
+export type FlatpakSource = "Cemu" | "Citra" | "MelonDSPrerelease" | "MelonDS" | "Lime3ds" | "MelonDSDetected";
 export type AppImageSource = "Cemu";
 export type EmuDeckSource = "CemuProton";
 export type SecondaryAppScreenPreference = "PreferSecondary" | "PreferPrimary";
@@ -235,6 +235,9 @@ export type PipelineActionUpdate =
       value: {
         is_visible: boolean;
       };
+    }
+  | {
+      type: "Inherit";
     };
 export type DependencyError =
   | {
@@ -294,26 +297,102 @@ export type RuntimeSelection =
       value: PipelineAction[];
     };
 
+export type OptionSource =
+  | {
+      type: "AudioDevices";
+      value: {
+        kind: AudioDeviceKind;
+      };
+    }
+  | {
+      type: "SecondaryAppPresets";
+      value: {
+        installed_only: boolean;
+      };
+    }
+  | {
+      type: "InstalledFlatpaks";
+    }
+  | {
+      type: "DisplayResolutions";
+    };
+export type AudioDeviceKind = "Sink" | "Source";
+export type ActionType =
+  | "CemuAudio"
+  | "CemuLayout"
+  | "CitraLayout"
+  | "DesktopControllerLayoutHack"
+  | "Lime3dsLayout"
+  | "DesktopSessionHandler"
+  | "DisplayConfig"
+  | "MultiWindow"
+  | "MainAppAutomaticWindowing"
+  | "MelonDSLayout"
+  | "SourceFile"
+  | "TouchConfig"
+  | "VirtualScreen"
+  | "LaunchSecondaryFlatpakApp"
+  | "LaunchSecondaryAppPreset"
+  | "LaunchSecondaryApp"
+  | "LaunchSecondaryAppGroup";
+export type UiWidget =
+  | {
+      type: "Toggle";
+    }
+  | {
+      type: "Number";
+      value: {
+        max?: number | null;
+        min?: number | null;
+        step?: number | null;
+      };
+    }
+  | {
+      type: "Slider";
+      value: {
+        max: number;
+        min: number;
+        step: number;
+      };
+    }
+  | {
+      type: "Text";
+    }
+  | {
+      type: "Dropdown";
+      value: UiOption[];
+    }
+  | {
+      type: "SourceDropdown";
+      value: OptionSource;
+    }
+  | {
+      type: "Custom";
+    };
+export type UiPlacement = "Qam" | "FullPage";
 /**
  * Marker type for generating API json schema types for ts
  */
 export interface Api {
   add_client_teardown_action_request: AddClientTeardownActionRequest;
+  apply_gamescope_launch_options_request: ApplyGamescopeLaunchOptionsRequest;
+  apply_gamescope_launch_options_response: ApplyGamescopeLaunchOptionsResponse;
   autostart_request: AutoStartRequest;
   create_profile_request: CreateProfileRequest;
   create_profile_response: CreateProfileResponse;
   delete_profile_request: DeleteProfileRequest;
+  get_action_ui_schemas_response: GetActionUiSchemasResponse;
   get_app_profile_request: GetAppProfileRequest;
   get_app_profile_response: GetAppProfileResponse;
-  get_audio_device_info: GetAudioDeviceInfoResponse;
   get_client_teardown_actions_response: GetClientTeardownActionsResponse;
   get_default_app_override_for_profile_request: GetDefaultAppOverrideForProfileRequest;
   get_default_app_override_for_profile_response: GetDefaultAppOverrideForProfileResponse;
   get_display_info: GetDisplayInfoResponse;
+  get_options_request: GetOptionsRequest;
+  get_options_response: GetOptionsResponse;
   get_profile_request: GetProfileRequest;
   get_profile_response: GetProfileResponse;
   get_profiles_response: GetProfilesResponse;
-  get_secondary_app_info: GetSecondaryAppInfoResponse;
   get_settings_response: GetSettingsResponse;
   get_templates_response: GetTemplatesResponse;
   get_toplevel_response: GetTopLevelResponse;
@@ -330,6 +409,20 @@ export interface Api {
 export interface AddClientTeardownActionRequest {
   action: ClientTeardownAction;
 }
+export interface ApplyGamescopeLaunchOptionsRequest {
+  action: MainAppAutomaticWindowing;
+  app_id: number;
+  /**
+   * The app's launch options, as currently set in Steam
+   */
+  launch_options: string;
+}
+export interface ApplyGamescopeLaunchOptionsResponse {
+  /**
+   * The launch options to set in Steam, if gamescope is enabled. The originals are restored by the recorded client teardown action.
+   */
+  launch_options?: string | null;
+}
 export interface AutoStartRequest {
   app_id: string;
   game_id?: string | null;
@@ -552,9 +645,31 @@ export interface DeleteProfileRequest {
 }
 export interface GetAppProfileRequest {
   app_id: string;
+  /**
+   * Used to select the profile by rule, if the app has no default profile
+   */
+  metadata?: AppMetadata | null;
+}
+/**
+ * What Steam knows about an app, as sent by the frontend.
+ */
+export interface AppMetadata {
+  app_id: string;
+  collections?: string[];
+  /**
+   * The launching executable, if known
+   */
+  executable?: string | null;
+  is_steam_game: boolean;
+  tags?: string[];
+  title: string;
 }
 export interface GetAppProfileResponse {
   app: AppProfile;
+  /**
+   * The profile to launch the app with; the default profile if no rule applies
+   */
+  selected_profile?: string | null;
 }
 export interface AppProfile {
   default_profile?: string | null;
@@ -563,14 +678,68 @@ export interface AppProfile {
     [k: string]: PipelineDefinition;
   };
 }
-export interface GetAudioDeviceInfoResponse {
-  sinks: AudioDeviceInfo[];
-  sources: AudioDeviceInfo[];
+export interface GetActionUiSchemasResponse {
+  schemas: ActionUiSchema[];
 }
-export interface AudioDeviceInfo {
-  channels?: number | null;
-  description: string;
-  name: string;
+export interface ActionUiSchema {
+  action_type: ActionType;
+  /**
+   * The editable fields, in display order
+   */
+  fields: UiField[];
+  /**
+   * The JSON schema of the action value
+   */
+  schema: unknown;
+}
+export interface UiField {
+  description?: string | null;
+  /**
+   * Label of the section the field is shown in, if any
+   */
+  group?: string | null;
+  label: string;
+  /**
+   * Whether the field may be unset (`null`)
+   */
+  optional: boolean;
+  /**
+   * Path of the field in the action value; empty if the whole value is a single field
+   */
+  path: string[];
+  placement: UiPlacement;
+  /**
+   * If set, the field is only shown while the condition holds
+   */
+  visible_if?: UiCondition | null;
+  widget: UiWidget;
+}
+export interface UiCondition {
+  /**
+   * The condition holds while the field has one of these values
+   */
+  one_of: unknown[];
+  /**
+   * Path of the field the condition checks
+   */
+  path: string[];
+}
+export interface GetOptionsRequest {
+  source: OptionSource;
+}
+export interface GetOptionsResponse {
+  list: OptionList;
+}
+export interface OptionList {
+  /**
+   * The value to use when none is selected, if any
+   */
+  default?: unknown;
+  options: UiOption[];
+}
+export interface UiOption {
+  label: string;
+  value: unknown;
 }
 export interface GetClientTeardownActionsResponse {
   actions: ClientTeardownAction[];
@@ -603,16 +772,6 @@ export interface CategoryProfile {
 export interface GetProfilesResponse {
   profiles: CategoryProfile[];
 }
-export interface GetSecondaryAppInfoResponse {
-  installed_flatpaks: FlatpakInfo[];
-  presets: {
-    [k: string]: SecondaryAppPreset;
-  };
-}
-export interface FlatpakInfo {
-  app_id: string;
-  name: string;
-}
 export interface SecondaryAppPreset {
   app: SecondaryApp;
   name: string;
@@ -683,7 +842,15 @@ export interface PatchPipelineActionResponse {
   pipeline: PipelineDefinition;
 }
 export interface ReifyPipelineRequest {
+  /**
+   * The app the pipeline overrides `profile_id` for, if it is an app override. Actions the override doesn't specify are inherited from the profile itself.
+   */
+  app_id?: string | null;
   pipeline: PipelineDefinition;
+  /**
+   * The stored profile the pipeline belongs to, if any. Actions the pipeline doesn't specify are inherited from the profile's parent.
+   */
+  profile_id?: string | null;
 }
 export interface ReifyPipelineResponse {
   config_errors: {
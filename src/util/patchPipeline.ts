@@ -64,6 +64,12 @@ export type PipelineUpdate =
           toplevel_id: string;
           target: PipelineTarget;
           visible: boolean;
+      }
+    | {
+          type: 'inherit';
+          action_id: string;
+          toplevel_id: string;
+          target: PipelineTarget;
       };
 
 export interface PipelineInfo {
@@ -179,6 +185,10 @@ export async function patchPipeline(
                             is_visible: update.visible,
                         },
                     };
+                case 'inherit':
+                    return {
+                        type: 'Inherit',
+                    };
                 default:
                     const typecheck: never = type;
                     throw `failed to typecheck PipelineActionUpdate: ${typecheck}`;
@@ -24,7 +24,7 @@ export default function PipelineDisplay({
     const [currentTabRoute, setCurrentTabRoute] = useState<string>('general');
 
     const { state } = useModifiablePipelineContainer();
-    const result = useReifiedPipeline(state.container.pipeline);
+    const result = useReifiedPipeline(state.container);
 
     let containerRef = useRef<HTMLDivElement>(null);
     let [headerHeight, setHeaderHeight] = useState<number | null>(null);
@@ -27,6 +27,16 @@ export default function AppDefaultProfileDropdown({
         launchActions.find((a) => a.profileId == appProfile.default_profile)
             ?.profileId ?? null;
 
+    const profileName = (profileId: string) =>
+        availableProfiles.find((v) => v.id === profileId)?.pipeline.name ?? '';
+
+    // without a default profile, the profile rules pick one
+    const ruleProfileId = appDetailsState.selectedProfileId;
+    const defaultLabel =
+        !selected && ruleProfileId
+            ? `Default (${profileName(ruleProfileId)})`
+            : 'Default';
+
     return (
         <PanelSection title="Default Profile">
             <PanelSectionRow>
@@ -34,15 +44,12 @@ export default function AppDefaultProfileDropdown({
                     selectedOption={selected}
                     rgOptions={[
                         {
-                            label: 'Default',
+                            label: defaultLabel,
                             data: null,
                         },
                         ...launchActions.map((a) => {
                             return {
-                                label:
-                                    availableProfiles.find(
-                                        (v) => v.id === a.profileId,
-                                    )?.pipeline.name ?? '',
+                                label: profileName(a.profileId),
                                 data: a.profileId,
                             };
                         }),
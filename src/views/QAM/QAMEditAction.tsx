@@ -27,6 +27,7 @@ export default function QAMEditAction({
         indentLevel: 0,
         actionChildBuilder: QAMActionChild,
         actionChildSliderBuilder: QAMActionChildSliderBuilder,
+        qam: true,
     };
     return InternalEditAction(internalProps);
 }